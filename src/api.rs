@@ -1,61 +1,474 @@
+use crate::api_error;
 use crate::core_model;
+use crate::core_model::GraphError;
+use crate::graph_algorithms;
+use crate::graph_export;
+use crate::graph_import::ImportDTO;
+use crate::graph_paths;
+use crate::graph_transaction::{
+    TransactionDTO, TransactionOperation, TransactionOperationsResultDTO,
+};
+use crate::kv_model::KVError;
+use crate::property_index::PropertyIndexDefinition;
+use crate::query_engine;
 use crate::AppState;
-use actix_web::{web, HttpResponse, Responder};
-use serde_json::Result;
+use actix_web::{web, HttpResponse};
+use uuid::Uuid;
 
-pub async fn get_test_val_by_key(data: web::Data<AppState>) -> impl Responder {
-    let arc_string_value = data
-        .kv_collection
-        .get_value("foo".to_string())
-        .await
-        .unwrap();
-    HttpResponse::Ok().body(format!("{}", arc_string_value))
+pub async fn get_test_val_by_key(data: web::Data<AppState>) -> Result<HttpResponse, KVError> {
+    let arc_string_value = data.kv_collection.get_value("foo".to_string()).await?;
+    Ok(HttpResponse::Ok().body(format!("{}", arc_string_value)))
 }
 
-pub async fn create_graph(data: web::Data<AppState>, body: String) -> impl Responder {
-    let deser_result: Result<core_model::CreateGraphDTO> = serde_json::from_str(&body.to_string());
-    let dto = match deser_result {
+pub async fn create_graph(data: web::Data<AppState>, body: String) -> HttpResponse {
+    let dto: core_model::CreateGraphDTO = match serde_json::from_str(&body) {
         Ok(s) => s,
-        Err(_) => panic!(),
+        Err(e) => return api_error::bad_request("INVALID_BODY", e.to_string()),
     };
 
-    match core_model::validate_and_map_graph(dto, &data.graph_collection) {
-        Err(_) => {
-            let graph_collection = data
-                .graph_collection
-                .in_memory_graph_collection
-                .read()
-                .unwrap();
-            let len = graph_collection.len();
-            let answer = format!(
-                "failed creating graph number is: {} body is \"{}\"",
-                len, body
-            );
-            HttpResponse::Conflict().body(answer)
+    let created = core_model::validate_and_map_graph(dto, &data.graph_collection)
+        .and_then(|img| data.graph_collection.add_graph(img));
+    if let Err(e) = created {
+        return actix_web::ResponseError::error_response(&e);
+    }
+    let graphs = data.graph_collection.list_graphs();
+    let answer = format!("number is: {} body is \"{:?}\"", graphs.len(), graphs);
+    HttpResponse::Ok().body(answer)
+}
+
+/// Creates graph from JSON body, the same as create_graph but resource oriented
+pub async fn add_graph(
+    data: web::Data<AppState>,
+    dto: web::Json<core_model::CreateGraphDTO>,
+) -> Result<HttpResponse, GraphError> {
+    let graph = core_model::validate_and_map_graph(dto.into_inner(), &data.graph_collection)?;
+    data.graph_collection.add_graph(graph)?;
+    Ok(HttpResponse::Created().finish())
+}
+
+/// Lists graphs with node and bond counts
+pub async fn get_graphs(data: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok().json(data.graph_collection.list_graphs())
+}
+
+pub async fn delete_graph(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+) -> Result<HttpResponse, GraphError> {
+    data.graph_collection.delete_graph(&graph_name)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Renames graph to name from body
+pub async fn rename_graph(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+    dto: web::Json<core_model::RenameGraphDTO>,
+) -> Result<HttpResponse, GraphError> {
+    data.graph_collection
+        .rename_graph(&graph_name, dto.into_inner().name)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Snapshots graph and truncates its log, no-op for graphs kept in memory only
+pub async fn snapshot_graph(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+) -> Result<HttpResponse, GraphError> {
+    data.graph_collection.snapshot_graph(&graph_name)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+pub async fn get_graph_by_name(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+) -> Result<HttpResponse, GraphError> {
+    with_graph(&data, &graph_name, |graph| {
+        Ok(HttpResponse::Ok().json(graph))
+    })
+}
+
+pub async fn add_node(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+    dto: web::Json<core_model::CreateNodeDTO>,
+) -> Result<HttpResponse, GraphError> {
+    let id = data
+        .graph_collection
+        .add_node(&graph_name, dto.into_inner().into())?;
+    Ok(HttpResponse::Created().json(id))
+}
+
+/// Looks nodes up by ids or by labels, both filtered by properties
+pub async fn get_nodes(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+    query: web::Query<core_model::NodesQueryDTO>,
+) -> Result<HttpResponse, GraphError> {
+    let node_properties = parse_properties(&query.properties)?;
+    let ids = match &query.ids {
+        None => None,
+        Some(_) => Some(
+            split_list(&query.ids)
+                .iter()
+                .map(|x| Uuid::parse_str(x))
+                .collect::<Result<Vec<Uuid>, _>>()
+                .map_err(|e| GraphError::InvalidQuery(format!("ids: {}", e)))?,
+        ),
+    };
+    let labels = split_list(&query.labels);
+
+    with_graph(&data, &graph_name, |graph| {
+        let nodes = match ids {
+            Some(ids) => graph
+                .get_nodes_by_id_list(ids)?
+                .into_iter()
+                .filter(|x| core_model::properties_match(&x.properties, &node_properties))
+                .collect(),
+            None => graph.get_nodes_by_label_list(labels, node_properties)?,
+        };
+        Ok(nodes_response(nodes))
+    })
+}
+
+pub async fn get_node(
+    data: web::Data<AppState>,
+    web::Path((graph_name, node_id)): web::Path<(String, Uuid)>,
+) -> Result<HttpResponse, GraphError> {
+    with_graph(&data, &graph_name, |graph| {
+        let nodes = graph.get_nodes_by_id_list(vec![node_id])?;
+        match nodes.first() {
+            None => Err(GraphError::NodeNotFound(node_id)),
+            Some(node) => Ok(HttpResponse::Ok().json(core_model::ReturnNodeDTO::from(*node))),
         }
-        Ok(img) => {
-            let mut graph_collection = data
-                .graph_collection
-                .in_memory_graph_collection
-                .write()
-                .unwrap();
-            graph_collection.push(img);
-            let len = graph_collection.len();
-            let answer = format!("number is: {} body is \"{:?}\"", len, graph_collection);
-            HttpResponse::Ok().body(answer)
+    })
+}
+
+pub async fn update_node_properties(
+    data: web::Data<AppState>,
+    web::Path((graph_name, node_id)): web::Path<(String, Uuid)>,
+    properties: web::Json<core_model::Properties>,
+) -> Result<HttpResponse, GraphError> {
+    data.graph_collection
+        .update_node_properties(&graph_name, node_id, properties.into_inner())?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Deletes node, `?detach=true` deletes its bonds as well
+pub async fn delete_node(
+    data: web::Data<AppState>,
+    web::Path((graph_name, node_id)): web::Path<(String, Uuid)>,
+    query: web::Query<core_model::DeleteNodeQueryDTO>,
+) -> Result<HttpResponse, GraphError> {
+    data.graph_collection
+        .delete_node(&graph_name, node_id, query.detach)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Nodes connected with given one, see core_model::NeighboursQueryDTO for query string
+pub async fn get_neighbours(
+    data: web::Data<AppState>,
+    web::Path((graph_name, node_id)): web::Path<(String, Uuid)>,
+    query: web::Query<core_model::NeighboursQueryDTO>,
+) -> Result<HttpResponse, GraphError> {
+    let node_properties = parse_properties(&query.properties)?;
+    let bond_types = split_list(&query.bond_types);
+    let node_labels = split_list(&query.labels);
+    let direction = query
+        .direction
+        .unwrap_or(core_model::BondDirection::Outgoing);
+
+    with_graph(&data, &graph_name, |graph| {
+        // both lookups return current node first
+        let nodes: Vec<&core_model::Node> = match query.depth {
+            None => graph.get_connected_nodes(
+                node_id,
+                bond_types,
+                node_labels,
+                node_properties,
+                direction,
+            )?,
+            Some(depth) => graph
+                .get_connected_nodes_by_depth(
+                    node_id,
+                    depth,
+                    bond_types,
+                    node_labels,
+                    node_properties,
+                    direction,
+                )?
+                .into_iter()
+                .map(|(node, _)| node)
+                .collect(),
+        };
+        Ok(nodes_response(nodes.into_iter().skip(1).collect()))
+    })
+}
+
+pub async fn add_bond(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+    dto: web::Json<core_model::CreateBondDTO>,
+) -> Result<HttpResponse, GraphError> {
+    let id = data
+        .graph_collection
+        .add_bond(&graph_name, dto.into_inner().into())?;
+    Ok(HttpResponse::Created().json(id))
+}
+
+pub async fn get_bond(
+    data: web::Data<AppState>,
+    web::Path((graph_name, bond_id)): web::Path<(String, Uuid)>,
+) -> Result<HttpResponse, GraphError> {
+    with_graph(&data, &graph_name, |graph| {
+        match graph.get_bond_by_id(bond_id) {
+            None => Err(GraphError::BondNotFound(bond_id)),
+            Some(bond) => Ok(HttpResponse::Ok().json(core_model::ReturnBondDTO::from(bond))),
+        }
+    })
+}
+
+pub async fn delete_bond(
+    data: web::Data<AppState>,
+    web::Path((graph_name, bond_id)): web::Path<(String, Uuid)>,
+) -> Result<HttpResponse, GraphError> {
+    data.graph_collection.delete_bond(&graph_name, bond_id)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+pub async fn get_property_indexes(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+) -> Result<HttpResponse, GraphError> {
+    with_graph(&data, &graph_name, |graph| {
+        Ok(HttpResponse::Ok().json(graph.get_property_indexes()))
+    })
+}
+
+pub async fn create_property_index(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+    definition: web::Json<PropertyIndexDefinition>,
+) -> Result<HttpResponse, GraphError> {
+    let definition = definition.into_inner();
+    data.graph_collection.create_property_index(
+        &graph_name,
+        definition.label,
+        definition.property,
+    )?;
+    Ok(HttpResponse::Created().finish())
+}
+
+pub async fn drop_property_index(
+    data: web::Data<AppState>,
+    web::Path((graph_name, label, property)): web::Path<(String, String, String)>,
+) -> Result<HttpResponse, GraphError> {
+    data.graph_collection
+        .drop_property_index(&graph_name, label, property)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Finds paths between two nodes, see graph_paths::PathQueryDTO for query string
+pub async fn get_paths(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+    query: web::Query<graph_paths::PathQueryDTO>,
+) -> Result<HttpResponse, GraphError> {
+    with_graph(&data, &graph_name, |graph| {
+        Ok(HttpResponse::Ok().json(graph.find_paths(&query)?))
+    })
+}
+
+/// Runs graph algorithm, see graph_algorithms::Algorithm for the list
+pub async fn run_algorithm(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+    dto: web::Json<graph_algorithms::AlgorithmDTO>,
+) -> Result<HttpResponse, GraphError> {
+    let result = data.graph_collection.run_algorithm(&graph_name, &dto)?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
+/// Downloads whole graph or neighbourhood of node as GraphML, GEXF or DOT file
+pub async fn export_graph(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+    query: web::Query<graph_export::ExportQueryDTO>,
+) -> Result<HttpResponse, GraphError> {
+    with_graph(&data, &graph_name, |graph| {
+        let body = graph.select_export(&query)?.export(query.format);
+        let file_name: String = graph_name
+            .chars()
+            .map(|x| {
+                if x.is_ascii_alphanumeric() || x == '-' {
+                    x
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        Ok(HttpResponse::Ok()
+            .content_type(query.format.content_type())
+            .header(
+                "Content-Disposition",
+                format!(
+                    "attachment; filename=\"{}.{}\"",
+                    file_name,
+                    query.format.file_extension()
+                ),
+            )
+            .body(body))
+    })
+}
+
+/// Runs query language text against graph, see query_parser for syntax
+pub async fn run_query(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+    dto: web::Json<query_engine::QueryDTO>,
+) -> Result<HttpResponse, GraphError> {
+    with_graph(&data, &graph_name, |graph| {
+        Ok(HttpResponse::Ok().json(graph.execute_query(&dto.query)?))
+    })
+}
+
+/// Applies operations atomically: all of them or, if one fails, none
+pub async fn run_batch(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+    operations: web::Json<Vec<TransactionOperation>>,
+) -> Result<HttpResponse, GraphError> {
+    let ids = data
+        .graph_collection
+        .run_transaction(&graph_name, |transaction| {
+            operations
+                .into_inner()
+                .into_iter()
+                .map(|x| transaction.apply(x))
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+    Ok(HttpResponse::Ok().json(TransactionOperationsResultDTO { ids }))
+}
+
+pub async fn import_graph(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+    dto: web::Json<ImportDTO>,
+) -> Result<HttpResponse, GraphError> {
+    let report = data.graph_collection.import_graph(&graph_name, &dto)?;
+    Ok(HttpResponse::Ok().json(report))
+}
+
+pub async fn begin_transaction(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+) -> Result<HttpResponse, GraphError> {
+    let id = data
+        .graph_transactions
+        .begin(&data.graph_collection, &graph_name)?;
+    Ok(HttpResponse::Created().json(TransactionDTO { id }))
+}
+
+/// Applies operations inside open transaction.
+/// If one of them fails the transaction is rolled back.
+pub async fn apply_transaction_operations(
+    data: web::Data<AppState>,
+    web::Path((graph_name, id)): web::Path<(String, Uuid)>,
+    operations: web::Json<Vec<TransactionOperation>>,
+) -> Result<HttpResponse, GraphError> {
+    let applied = data.graph_transactions.with_transaction(
+        &data.graph_collection,
+        &graph_name,
+        id,
+        |transaction| {
+            operations
+                .into_inner()
+                .into_iter()
+                .map(|x| transaction.apply(x))
+                .collect::<Result<Vec<_>, _>>()
+        },
+    );
+    match applied {
+        Ok(ids) => Ok(HttpResponse::Ok().json(TransactionOperationsResultDTO { ids })),
+        Err(e) => {
+            if e != GraphError::TransactionNotFound(id) {
+                let _ = data.graph_transactions.rollback(&graph_name, id);
+            }
+            Err(e)
         }
     }
 }
 
-async fn get_graph_by_name(
-    data: web::Data<core_model::GraphCollectionFacade>,
-    graph_name: String,
-) -> impl Responder {
-    let graph_collection = data.in_memory_graph_collection.read().unwrap();
-    let graph = match graph_collection.iter().find(|x| x.name == graph_name) {
-        None => return HttpResponse::NotFound().body(""),
-        Some(g) => g,
-    };
-    let responce = serde_json::to_string(&graph).unwrap();
-    HttpResponse::Ok().body(responce)
+/// Runs query against graph as seen by open transaction
+pub async fn run_transaction_query(
+    data: web::Data<AppState>,
+    web::Path((graph_name, id)): web::Path<(String, Uuid)>,
+    dto: web::Json<query_engine::QueryDTO>,
+) -> Result<HttpResponse, GraphError> {
+    let result = data.graph_transactions.with_transaction(
+        &data.graph_collection,
+        &graph_name,
+        id,
+        |transaction| transaction.graph().execute_query(&dto.query),
+    )?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
+pub async fn commit_transaction(
+    data: web::Data<AppState>,
+    web::Path((graph_name, id)): web::Path<(String, Uuid)>,
+) -> Result<HttpResponse, GraphError> {
+    data.graph_transactions
+        .commit(&data.graph_collection, &graph_name, id)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+pub async fn rollback_transaction(
+    data: web::Data<AppState>,
+    web::Path((graph_name, id)): web::Path<(String, Uuid)>,
+) -> Result<HttpResponse, GraphError> {
+    data.graph_transactions.rollback(&graph_name, id)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Runs handler over graph by name under its read lock
+fn with_graph(
+    data: &AppState,
+    graph_name: &str,
+    handler: impl FnOnce(&core_model::InMemoryGraph) -> Result<HttpResponse, GraphError>,
+) -> Result<HttpResponse, GraphError> {
+    match data.graph_collection.get_graph(graph_name) {
+        None => Err(GraphError::GraphNotFound(graph_name.to_string())),
+        Some(graph) => handler(&graph.read().unwrap()),
+    }
+}
+
+fn nodes_response(nodes: Vec<&core_model::Node>) -> HttpResponse {
+    let dtos: Vec<core_model::ReturnNodeDTO> = nodes
+        .into_iter()
+        .map(core_model::ReturnNodeDTO::from)
+        .collect();
+    HttpResponse::Ok().json(dtos)
+}
+
+/// Splits comma separated query list, skipping blank items
+fn split_list(list: &Option<String>) -> Vec<String> {
+    match list {
+        None => Vec::new(),
+        Some(l) => l
+            .split(',')
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty())
+            .map(String::from)
+            .collect(),
+    }
+}
+
+/// Parses JSON object passed in query string, missing one means no filter
+fn parse_properties(properties: &Option<String>) -> Result<core_model::Properties, GraphError> {
+    match properties {
+        None => Ok(core_model::Properties::new()),
+        Some(p) => serde_json::from_str(p)
+            .map_err(|e| GraphError::InvalidQuery(format!("properties: {}", e))),
+    }
 }
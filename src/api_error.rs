@@ -0,0 +1,104 @@
+use crate::core_model::GraphError;
+use crate::kv_model::KVError;
+use actix_web::error::{InternalError, JsonPayloadError, PathError, QueryPayloadError};
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
+use serde::{Deserialize, Serialize};
+
+/// Error body of every handler and websocket responce
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorResponceDto {
+    pub code: String,
+    pub message: String,
+}
+
+impl From<&GraphError> for ErrorResponceDto {
+    fn from(e: &GraphError) -> Self {
+        ErrorResponceDto {
+            code: e.code().to_string(),
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<&KVError> for ErrorResponceDto {
+    fn from(e: &KVError) -> Self {
+        ErrorResponceDto {
+            code: e.code().to_string(),
+            message: e.to_string(),
+        }
+    }
+}
+
+impl ResponseError for GraphError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            GraphError::GraphNotFound(_)
+            | GraphError::NodeNotFound(_)
+            | GraphError::BondNotFound(_)
+            | GraphError::PropertyIndexNotFound { .. }
+            | GraphError::TransactionNotFound(_) => StatusCode::NOT_FOUND,
+            GraphError::DuplicateGraphName(_)
+            | GraphError::DuplicateNodeId(_)
+            | GraphError::DuplicateBondId(_)
+            | GraphError::DuplicatePropertyIndex { .. }
+            | GraphError::NodeHasBonds(_)
+            | GraphError::TransactionConflict(_) => StatusCode::CONFLICT,
+            GraphError::CursorExpired(_) => StatusCode::GONE,
+            GraphError::TooManyTransactions(_) => StatusCode::TOO_MANY_REQUESTS,
+            GraphError::InvalidGraphName
+            | GraphError::InvalidLabel
+            | GraphError::InvalidProperty(_)
+            | GraphError::DanglingBondEndpoint(_)
+            | GraphError::InvalidPropertyIndex
+            | GraphError::InvalidQuery(_)
+            | GraphError::InvalidImport(_) => StatusCode::BAD_REQUEST,
+            GraphError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(ErrorResponceDto::from(self))
+    }
+}
+
+impl ResponseError for KVError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            KVError::DuplicateKey(_) => StatusCode::CONFLICT,
+            KVError::KeyNotFound(_) => StatusCode::NOT_FOUND,
+            KVError::InvalidExpiry(_) => StatusCode::BAD_REQUEST,
+            KVError::InvalidPattern(_) => StatusCode::BAD_REQUEST,
+            KVError::SubscriptionNotFound(_) => StatusCode::NOT_FOUND,
+            KVError::WatchClosed => StatusCode::GONE,
+            KVError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(ErrorResponceDto::from(self))
+    }
+}
+
+/// 400 with error body, used for requests rejected before reaching a handler
+pub fn bad_request(code: &str, message: String) -> HttpResponse {
+    HttpResponse::BadRequest().json(ErrorResponceDto {
+        code: code.to_string(),
+        message,
+    })
+}
+
+pub fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let responce = bad_request("INVALID_BODY", err.to_string());
+    InternalError::from_response(err, responce).into()
+}
+
+pub fn query_error_handler(err: QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let responce = bad_request("INVALID_QUERY", err.to_string());
+    InternalError::from_response(err, responce).into()
+}
+
+pub fn path_error_handler(err: PathError, _req: &HttpRequest) -> actix_web::Error {
+    let responce = bad_request("INVALID_PATH", err.to_string());
+    InternalError::from_response(err, responce).into()
+}
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod error_responce_tests {
+    use crate::api_error::ErrorResponceDto;
+    use crate::core_model::GraphError;
+    use crate::kv_model::KVError;
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use uuid::Uuid;
+
+    #[test]
+    fn graph_error_status_codes_passed() {
+        let id = Uuid::new_v4();
+
+        assert_eq!(
+            StatusCode::NOT_FOUND,
+            GraphError::GraphNotFound(String::from("some")).status_code()
+        );
+        assert_eq!(
+            StatusCode::NOT_FOUND,
+            GraphError::NodeNotFound(id).status_code()
+        );
+        assert_eq!(
+            StatusCode::CONFLICT,
+            GraphError::DuplicateNodeId(id).status_code()
+        );
+        assert_eq!(
+            StatusCode::CONFLICT,
+            GraphError::NodeHasBonds(id).status_code()
+        );
+        assert_eq!(
+            StatusCode::BAD_REQUEST,
+            GraphError::InvalidLabel.status_code()
+        );
+        assert_eq!(
+            StatusCode::BAD_REQUEST,
+            GraphError::DanglingBondEndpoint(id).status_code()
+        );
+        assert_eq!(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            GraphError::Storage(String::from("disk full")).status_code()
+        );
+    }
+
+    #[test]
+    fn kv_error_status_codes_passed() {
+        assert_eq!(
+            StatusCode::CONFLICT,
+            KVError::DuplicateKey(String::from("foo")).status_code()
+        );
+        assert_eq!(
+            StatusCode::NOT_FOUND,
+            KVError::KeyNotFound(String::from("foo")).status_code()
+        );
+    }
+
+    #[test]
+    fn error_body_has_code_and_message_passed() {
+        let dto = ErrorResponceDto::from(&KVError::KeyNotFound(String::from("foo")));
+
+        let json = serde_json::to_value(&dto).unwrap();
+
+        assert_eq!("KEY_NOT_FOUND", json["code"]);
+        assert_eq!("key \"foo\" not found", json["message"]);
+    }
+}
@@ -0,0 +1,350 @@
+#[cfg(test)]
+mod rest_api_tests {
+    use crate::api;
+    use crate::api_error;
+    use crate::core_model::GraphCollectionFacade;
+    use crate::graph_transaction::TransactionRegistry;
+    use crate::kv_model::InMemoryKVStore;
+    use crate::AppState;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::{web, App};
+    use serde_json::{json, Value};
+    use std::future::Future;
+    use uuid::Uuid;
+
+    fn block_on<F: Future + 'static>(fut: F) -> F::Output {
+        actix_web::rt::System::new("api_tests").block_on(fut)
+    }
+
+    /// State with "MyGraph" created through the api
+    async fn initialize_state() -> web::Data<AppState> {
+        let state = web::Data::new(AppState {
+            graph_collection: GraphCollectionFacade::new(Vec::new(), None),
+            graph_transactions: TransactionRegistry::default(),
+            kv_collection: Box::new(InMemoryKVStore::new()),
+        });
+        let (status, _) = send(&state, post("/graphs", json!({"name": "MyGraph"}))).await;
+        assert_eq!(StatusCode::CREATED, status);
+        state
+    }
+
+    /// Graph resource routes and error handlers, registered the same way as in main
+    fn graph_routes(cfg: &mut web::ServiceConfig) {
+        cfg.app_data(web::JsonConfig::default().error_handler(api_error::json_error_handler))
+            .app_data(web::QueryConfig::default().error_handler(api_error::query_error_handler))
+            .app_data(web::PathConfig::default().error_handler(api_error::path_error_handler))
+            .route("/graphs", web::post().to(api::add_graph))
+            .route("/graphs/{name}", web::get().to(api::get_graph_by_name))
+            .route("/graphs/{name}/nodes", web::post().to(api::add_node))
+            .route("/graphs/{name}/nodes", web::get().to(api::get_nodes))
+            .route("/graphs/{name}/nodes/{id}", web::get().to(api::get_node))
+            .route(
+                "/graphs/{name}/nodes/{id}",
+                web::delete().to(api::delete_node),
+            )
+            .route(
+                "/graphs/{name}/nodes/{id}/properties",
+                web::put().to(api::update_node_properties),
+            )
+            .route(
+                "/graphs/{name}/nodes/{id}/neighbours",
+                web::get().to(api::get_neighbours),
+            )
+            .route("/graphs/{name}/bonds", web::post().to(api::add_bond))
+            .route("/graphs/{name}/bonds/{id}", web::get().to(api::get_bond))
+            .route(
+                "/graphs/{name}/bonds/{id}",
+                web::delete().to(api::delete_bond),
+            )
+            .route(
+                "/graphs/{name}/indexes",
+                web::get().to(api::get_property_indexes),
+            )
+            .route(
+                "/graphs/{name}/indexes",
+                web::post().to(api::create_property_index),
+            )
+            .route(
+                "/graphs/{name}/indexes/{label}/{property}",
+                web::delete().to(api::drop_property_index),
+            );
+    }
+
+    /// Status and JSON body of responce to request, Null for empty body
+    async fn send(state: &web::Data<AppState>, request: TestRequest) -> (StatusCode, Value) {
+        let mut app =
+            test::init_service(App::new().app_data(state.clone()).configure(graph_routes)).await;
+        let responce = test::call_service(&mut app, request.to_request()).await;
+        let status = responce.status();
+        let body = test::read_body(responce).await;
+        if body.is_empty() {
+            return (status, Value::Null);
+        }
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn post(uri: &str, body: Value) -> TestRequest {
+        TestRequest::post().uri(uri).set_json(&body)
+    }
+
+    fn put(uri: &str, body: Value) -> TestRequest {
+        TestRequest::put().uri(uri).set_json(&body)
+    }
+
+    fn get(uri: &str) -> TestRequest {
+        TestRequest::get().uri(uri)
+    }
+
+    fn delete(uri: &str) -> TestRequest {
+        TestRequest::delete().uri(uri)
+    }
+
+    /// Code of error body, checking it carries a message too
+    fn error_code(body: &Value) -> &str {
+        assert!(!body["message"].as_str().unwrap().is_empty());
+        body["code"].as_str().unwrap()
+    }
+
+    /// Adds node with label, returning its id
+    async fn add_node(state: &web::Data<AppState>, label: &str) -> Uuid {
+        let (status, body) = send(
+            state,
+            post("/graphs/MyGraph/nodes", json!({ "labels": [label] })),
+        )
+        .await;
+        assert_eq!(StatusCode::CREATED, status);
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn graph_routes_passed() {
+        block_on(async {
+            let state = initialize_state().await;
+
+            let (status, body) = send(&state, get("/graphs/MyGraph")).await;
+
+            assert_eq!(StatusCode::OK, status);
+            assert_eq!("MyGraph", body["name"]);
+        });
+    }
+
+    #[test]
+    fn graph_routes_failed() {
+        block_on(async {
+            let state = initialize_state().await;
+
+            let (status, body) = send(&state, post("/graphs", json!({"name": "MyGraph"}))).await;
+            assert_eq!(StatusCode::CONFLICT, status);
+            assert_eq!("DUPLICATE_GRAPH_NAME", error_code(&body));
+
+            let (status, body) = send(&state, post("/graphs", json!({"name": " "}))).await;
+            assert_eq!(StatusCode::BAD_REQUEST, status);
+            assert_eq!("INVALID_GRAPH_NAME", error_code(&body));
+
+            let (status, body) = send(&state, post("/graphs", json!({"title": "x"}))).await;
+            assert_eq!(StatusCode::BAD_REQUEST, status);
+            assert_eq!("INVALID_BODY", error_code(&body));
+
+            let (status, body) = send(&state, get("/graphs/OtherGraph")).await;
+            assert_eq!(StatusCode::NOT_FOUND, status);
+            assert_eq!("GRAPH_NOT_FOUND", error_code(&body));
+            assert_eq!("graph \"OtherGraph\" not found", body["message"]);
+        });
+    }
+
+    #[test]
+    fn node_routes_passed() {
+        block_on(async {
+            let state = initialize_state().await;
+            let ann = add_node(&state, "Person").await;
+            let node_uri = format!("/graphs/MyGraph/nodes/{}", ann);
+
+            let (status, body) = send(&state, get(&node_uri)).await;
+            assert_eq!(StatusCode::OK, status);
+            assert_eq!(json!(["Person"]), body["labels"]);
+
+            let (status, body) = send(&state, get("/graphs/MyGraph/nodes?labels=Person")).await;
+            assert_eq!(StatusCode::OK, status);
+            assert_eq!(json!(ann), body[0]["id"]);
+
+            let properties_uri = format!("{}/properties", node_uri);
+            let (status, _) = send(&state, put(&properties_uri, json!({"name": "Ann"}))).await;
+            assert_eq!(StatusCode::OK, status);
+            let (_, body) = send(&state, get(&node_uri)).await;
+            assert_eq!("Ann", body["properties"]["name"]);
+
+            let (status, _) = send(&state, delete(&node_uri)).await;
+            assert_eq!(StatusCode::OK, status);
+        });
+    }
+
+    #[test]
+    fn node_routes_failed() {
+        block_on(async {
+            let state = initialize_state().await;
+            let missing_uri = format!("/graphs/MyGraph/nodes/{}", Uuid::new_v4());
+
+            let (status, body) =
+                send(&state, post("/graphs/MyGraph/nodes", json!({"labels": []}))).await;
+            assert_eq!(StatusCode::BAD_REQUEST, status);
+            assert_eq!("INVALID_LABEL", error_code(&body));
+
+            let (status, body) = send(
+                &state,
+                post("/graphs/OtherGraph/nodes", json!({"labels": ["Person"]})),
+            )
+            .await;
+            assert_eq!(StatusCode::NOT_FOUND, status);
+            assert_eq!("GRAPH_NOT_FOUND", error_code(&body));
+
+            let (status, body) = send(&state, get(&missing_uri)).await;
+            assert_eq!(StatusCode::NOT_FOUND, status);
+            assert_eq!("NODE_NOT_FOUND", error_code(&body));
+
+            let (status, body) = send(&state, get("/graphs/MyGraph/nodes/not-a-uuid")).await;
+            assert_eq!(StatusCode::BAD_REQUEST, status);
+            assert_eq!("INVALID_PATH", error_code(&body));
+
+            let (status, body) = send(&state, get("/graphs/MyGraph/nodes?ids=not-a-uuid")).await;
+            assert_eq!(StatusCode::BAD_REQUEST, status);
+            assert_eq!("INVALID_QUERY", error_code(&body));
+
+            let properties_uri = format!("{}/properties", missing_uri);
+            let (status, body) = send(&state, put(&properties_uri, json!({"name": null}))).await;
+            assert_eq!(StatusCode::BAD_REQUEST, status);
+            assert_eq!("INVALID_PROPERTY", error_code(&body));
+
+            let (status, body) = send(&state, delete(&missing_uri)).await;
+            assert_eq!(StatusCode::NOT_FOUND, status);
+            assert_eq!("NODE_NOT_FOUND", error_code(&body));
+        });
+    }
+
+    #[test]
+    fn bond_routes_passed() {
+        block_on(async {
+            let state = initialize_state().await;
+            let ann = add_node(&state, "Person").await;
+            let bob = add_node(&state, "Person").await;
+
+            let (status, body) = send(
+                &state,
+                post(
+                    "/graphs/MyGraph/bonds",
+                    json!({"label": "KNOWS", "src": ann, "dst": bob}),
+                ),
+            )
+            .await;
+            assert_eq!(StatusCode::CREATED, status);
+            let knows: Uuid = serde_json::from_value(body).unwrap();
+
+            let bond_uri = format!("/graphs/MyGraph/bonds/{}", knows);
+            let (status, body) = send(&state, get(&bond_uri)).await;
+            assert_eq!(StatusCode::OK, status);
+            assert_eq!("KNOWS", body["label"]);
+
+            let neighbours_uri =
+                format!("/graphs/MyGraph/nodes/{}/neighbours?bond_types=KNOWS", ann);
+            let (status, body) = send(&state, get(&neighbours_uri)).await;
+            assert_eq!(StatusCode::OK, status);
+            assert_eq!(json!(bob), body[0]["id"]);
+
+            // node with bonds is deleted only detached
+            let node_uri = format!("/graphs/MyGraph/nodes/{}", ann);
+            let (status, body) = send(&state, delete(&node_uri)).await;
+            assert_eq!(StatusCode::CONFLICT, status);
+            assert_eq!("NODE_HAS_BONDS", error_code(&body));
+            let (status, _) = send(&state, delete(&format!("{}?detach=true", node_uri))).await;
+            assert_eq!(StatusCode::OK, status);
+            let (status, _) = send(&state, delete(&bond_uri)).await;
+            assert_eq!(StatusCode::NOT_FOUND, status);
+        });
+    }
+
+    #[test]
+    fn bond_routes_failed() {
+        block_on(async {
+            let state = initialize_state().await;
+            let ann = add_node(&state, "Person").await;
+            let missing = Uuid::new_v4();
+
+            let (status, body) = send(
+                &state,
+                post(
+                    "/graphs/MyGraph/bonds",
+                    json!({"label": "KNOWS", "src": ann, "dst": missing}),
+                ),
+            )
+            .await;
+            assert_eq!(StatusCode::BAD_REQUEST, status);
+            assert_eq!("DANGLING_BOND_ENDPOINT", error_code(&body));
+
+            let bond_uri = format!("/graphs/MyGraph/bonds/{}", missing);
+            let (status, body) = send(&state, get(&bond_uri)).await;
+            assert_eq!(StatusCode::NOT_FOUND, status);
+            assert_eq!("BOND_NOT_FOUND", error_code(&body));
+
+            let (status, body) = send(&state, delete(&bond_uri)).await;
+            assert_eq!(StatusCode::NOT_FOUND, status);
+            assert_eq!("BOND_NOT_FOUND", error_code(&body));
+
+            let neighbours_uri = format!("/graphs/MyGraph/nodes/{}/neighbours?direction=up", ann);
+            let (status, body) = send(&state, get(&neighbours_uri)).await;
+            assert_eq!(StatusCode::BAD_REQUEST, status);
+            assert_eq!("INVALID_QUERY", error_code(&body));
+        });
+    }
+
+    #[test]
+    fn index_routes_passed() {
+        block_on(async {
+            let state = initialize_state().await;
+            let index = json!({"label": "Person", "property": "name"});
+
+            let (status, _) = send(&state, post("/graphs/MyGraph/indexes", index.clone())).await;
+            assert_eq!(StatusCode::CREATED, status);
+
+            let (status, body) = send(&state, get("/graphs/MyGraph/indexes")).await;
+            assert_eq!(StatusCode::OK, status);
+            assert_eq!(json!([index]), body);
+
+            let (status, _) = send(&state, delete("/graphs/MyGraph/indexes/Person/name")).await;
+            assert_eq!(StatusCode::OK, status);
+            let (_, body) = send(&state, get("/graphs/MyGraph/indexes")).await;
+            assert_eq!(json!([]), body);
+        });
+    }
+
+    #[test]
+    fn index_routes_failed() {
+        block_on(async {
+            let state = initialize_state().await;
+            let index = json!({"label": "Person", "property": "name"});
+            send(&state, post("/graphs/MyGraph/indexes", index.clone())).await;
+
+            let (status, body) = send(&state, post("/graphs/MyGraph/indexes", index)).await;
+            assert_eq!(StatusCode::CONFLICT, status);
+            assert_eq!("DUPLICATE_PROPERTY_INDEX", error_code(&body));
+
+            let (status, body) = send(
+                &state,
+                post(
+                    "/graphs/MyGraph/indexes",
+                    json!({"label": " ", "property": "name"}),
+                ),
+            )
+            .await;
+            assert_eq!(StatusCode::BAD_REQUEST, status);
+            assert_eq!("INVALID_PROPERTY_INDEX", error_code(&body));
+
+            let (status, body) = send(&state, delete("/graphs/MyGraph/indexes/Person/email")).await;
+            assert_eq!(StatusCode::NOT_FOUND, status);
+            assert_eq!("PROPERTY_INDEX_NOT_FOUND", error_code(&body));
+
+            let (status, body) = send(&state, get("/graphs/OtherGraph/indexes")).await;
+            assert_eq!(StatusCode::NOT_FOUND, status);
+            assert_eq!("GRAPH_NOT_FOUND", error_code(&body));
+        });
+    }
+}
@@ -1,18 +1,45 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::Bound;
 use std::sync::{Arc, RwLock};
 use uuid::Uuid;
 
+use crate::graph_feed::{GraphChange, GraphFeed};
+use crate::graph_storage::{GraphStorage, GraphWalRecord};
+use crate::property_index::{IndexValue, PropertyIndex, PropertyIndexDefinition};
+
+/// Longest graph name in bytes, its hex encoded directory name has to fit NAME_MAX
+pub const MAX_GRAPH_NAME_LEN: usize = 120;
+
 #[derive(Serialize, Deserialize)]
 pub struct CreateGraphDTO {
     pub name: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct RenameGraphDTO {
+    pub name: String,
+}
+
+/// Graph listing entry
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct GraphInfoDTO {
+    pub name: String,
+    pub nodes: usize,
+    pub bonds: usize,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ReturnNodeDTO {
-    pub id: u32,
-    pub label: String,
+    pub id: Uuid,
+    pub labels: Vec<String>,
+    pub properties: Properties,
     pub bonds: Option<Vec<ReturnBondDTO>>,
 }
 
@@ -22,42 +49,265 @@ pub struct ReturnBondDTO {
     pub label: String,
     pub src: Uuid,
     pub dst: Uuid,
+    pub properties: Properties,
+}
+
+/// Body of node creation, nil or missing id is generated
+#[derive(Serialize, Deserialize)]
+pub struct CreateNodeDTO {
+    #[serde(default)]
+    pub id: Uuid,
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub properties: Properties,
+}
+
+/// Body of bond creation, nil or missing id is generated
+#[derive(Serialize, Deserialize)]
+pub struct CreateBondDTO {
+    #[serde(default)]
+    pub id: Uuid,
+    pub label: String,
+    pub src: Uuid,
+    pub dst: Uuid,
+    #[serde(default)]
+    pub properties: Properties,
+}
+
+/// Query string of node lookup, lists are comma separated,
+/// properties is JSON object every returned node has to match
+#[derive(Serialize, Deserialize)]
+pub struct NodesQueryDTO {
+    pub ids: Option<String>,
+    pub labels: Option<String>,
+    pub properties: Option<String>,
+}
+
+/// Query string of neighbours lookup, without depth only direct neighbours are returned
+#[derive(Serialize, Deserialize)]
+pub struct NeighboursQueryDTO {
+    pub direction: Option<BondDirection>,
+    pub bond_types: Option<String>,
+    pub labels: Option<String>,
+    pub properties: Option<String>,
+    pub depth: Option<u32>,
+}
+
+/// Query string of node deletion
+#[derive(Serialize, Deserialize)]
+pub struct DeleteNodeQueryDTO {
+    #[serde(default)]
+    pub detach: bool,
+}
+
+/// Arbitrary JSON document attached to Node or Bond
+pub type Properties = Map<String, Value>;
+
+/// Reason graph operation failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphError {
+    GraphNotFound(String),
+    DuplicateGraphName(String),
+    InvalidGraphName,
+    NodeNotFound(Uuid),
+    BondNotFound(Uuid),
+    DuplicateNodeId(Uuid),
+    DuplicateBondId(Uuid),
+    /// Node without labels, blank node or bond label
+    InvalidLabel,
+    /// Blank key or null value, holds the key
+    InvalidProperty(String),
+    /// Bond src or dst is not a node of the graph
+    DanglingBondEndpoint(Uuid),
+    /// Node still has bonds and deletion is not detached
+    NodeHasBonds(Uuid),
+    DuplicatePropertyIndex {
+        label: String,
+        property: String,
+    },
+    PropertyIndexNotFound {
+        label: String,
+        property: String,
+    },
+    InvalidPropertyIndex,
+    InvalidQuery(String),
+    /// Malformed import file or row
+    InvalidImport(String),
+    TransactionNotFound(Uuid),
+    /// Graph was changed by another commit after transaction began, holds graph name
+    TransactionConflict(String),
+    /// Limit of transactions open at once is reached, holds the limit
+    TooManyTransactions(usize),
+    /// Changes after feed cursor are no longer retained
+    CursorExpired(u64),
+    Storage(String),
+}
+
+impl GraphError {
+    /// Stable machine readable code
+    pub fn code(&self) -> &'static str {
+        match self {
+            GraphError::GraphNotFound(_) => "GRAPH_NOT_FOUND",
+            GraphError::DuplicateGraphName(_) => "DUPLICATE_GRAPH_NAME",
+            GraphError::InvalidGraphName => "INVALID_GRAPH_NAME",
+            GraphError::NodeNotFound(_) => "NODE_NOT_FOUND",
+            GraphError::BondNotFound(_) => "BOND_NOT_FOUND",
+            GraphError::DuplicateNodeId(_) => "DUPLICATE_NODE_ID",
+            GraphError::DuplicateBondId(_) => "DUPLICATE_BOND_ID",
+            GraphError::InvalidLabel => "INVALID_LABEL",
+            GraphError::InvalidProperty(_) => "INVALID_PROPERTY",
+            GraphError::DanglingBondEndpoint(_) => "DANGLING_BOND_ENDPOINT",
+            GraphError::NodeHasBonds(_) => "NODE_HAS_BONDS",
+            GraphError::DuplicatePropertyIndex { .. } => "DUPLICATE_PROPERTY_INDEX",
+            GraphError::PropertyIndexNotFound { .. } => "PROPERTY_INDEX_NOT_FOUND",
+            GraphError::InvalidPropertyIndex => "INVALID_PROPERTY_INDEX",
+            GraphError::InvalidQuery(_) => "INVALID_QUERY",
+            GraphError::InvalidImport(_) => "INVALID_IMPORT",
+            GraphError::TransactionNotFound(_) => "TRANSACTION_NOT_FOUND",
+            GraphError::TransactionConflict(_) => "TRANSACTION_CONFLICT",
+            GraphError::TooManyTransactions(_) => "TOO_MANY_TRANSACTIONS",
+            GraphError::CursorExpired(_) => "CURSOR_EXPIRED",
+            GraphError::Storage(_) => "STORAGE_ERROR",
+        }
+    }
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::GraphNotFound(name) => write!(f, "graph \"{}\" not found", name),
+            GraphError::DuplicateGraphName(name) => {
+                write!(f, "graph \"{}\" already exists", name)
+            }
+            GraphError::InvalidGraphName => write!(
+                f,
+                "graph name must not be blank or longer than {} bytes",
+                MAX_GRAPH_NAME_LEN
+            ),
+            GraphError::NodeNotFound(id) => write!(f, "node {} not found", id),
+            GraphError::BondNotFound(id) => write!(f, "bond {} not found", id),
+            GraphError::DuplicateNodeId(id) => write!(f, "node {} already exists", id),
+            GraphError::DuplicateBondId(id) => write!(f, "bond {} already exists", id),
+            GraphError::InvalidLabel => write!(f, "label must not be blank"),
+            GraphError::InvalidProperty(key) => write!(
+                f,
+                "property \"{}\" is invalid, keys must not be blank and values must not be null",
+                key
+            ),
+            GraphError::DanglingBondEndpoint(id) => {
+                write!(f, "bond endpoint {} is not a node of the graph", id)
+            }
+            GraphError::NodeHasBonds(id) => {
+                write!(f, "node {} has bonds, delete them first or detach", id)
+            }
+            GraphError::DuplicatePropertyIndex { label, property } => {
+                write!(f, "index on ({}, {}) already exists", label, property)
+            }
+            GraphError::PropertyIndexNotFound { label, property } => {
+                write!(f, "index on ({}, {}) not found", label, property)
+            }
+            GraphError::InvalidPropertyIndex => {
+                write!(f, "index label and property must not be blank")
+            }
+            GraphError::InvalidQuery(message) => write!(f, "invalid query: {}", message),
+            GraphError::InvalidImport(message) => write!(f, "invalid import: {}", message),
+            GraphError::TransactionNotFound(id) => write!(f, "transaction {} not found", id),
+            GraphError::TransactionConflict(name) => write!(
+                f,
+                "graph \"{}\" was changed after transaction began, retry it",
+                name
+            ),
+            GraphError::TooManyTransactions(limit) => write!(
+                f,
+                "{} transactions are already open, commit or roll back some of them",
+                limit
+            ),
+            GraphError::CursorExpired(cursor) => write!(
+                f,
+                "changes after cursor {} are not available, reload the graph and reconnect",
+                cursor
+            ),
+            GraphError::Storage(message) => write!(f, "storage error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+impl From<std::io::Error> for GraphError {
+    fn from(e: std::io::Error) -> Self {
+        GraphError::Storage(e.to_string())
+    }
 }
 
 pub trait Graph {
-    fn create_node(&mut self, node: Node) -> Result<(), ()>;
-    fn create_bond(&mut self, bond: Bond) -> Result<(), ()>;
+    fn create_node(&mut self, node: Node) -> Result<(), GraphError>;
+    fn create_bond(&mut self, bond: Bond) -> Result<(), GraphError>;
 }
 /// Main Graph Model
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InMemoryGraph {
     pub name: String,
     pub nodes_collection: Vec<Node>,
     pub bonds_collection: Vec<Bond>,
+    // Indexes are not persisted - they are rebuilt from collections on load
+    #[serde(skip)]
     pub nodes_id_index: BTreeMap<Uuid, usize>,
+    #[serde(skip)]
     pub bonds_id_index: BTreeMap<Uuid, usize>,
+    /// Node id -> ids of bonds where node is src
+    #[serde(skip)]
+    pub outgoing_bonds: BTreeMap<Uuid, Vec<Uuid>>,
+    /// Node id -> ids of bonds where node is dst
+    #[serde(skip)]
+    pub incoming_bonds: BTreeMap<Uuid, Vec<Uuid>>,
+    /// Label -> positions in nodes_collection of nodes having it
+    #[serde(skip)]
+    pub labels_index: BTreeMap<String, BTreeSet<usize>>,
+    /// Bond label -> number of bonds having it, read by query planner
+    #[serde(skip)]
+    pub bond_type_counts: BTreeMap<String, usize>,
+    /// Secondary indexes on (label, property), definitions are persisted
+    #[serde(default)]
+    pub property_indexes: Vec<PropertyIndex>,
+    /// Number of mutations committed since load, transactions compare it to detect
+    /// commits made after they began
+    #[serde(skip)]
+    pub version: u64,
+    /// Committed node and bond changes
+    #[serde(skip)]
+    pub change_feed: Arc<GraphFeed>,
 }
+/// Registry of graphs by name, every graph is locked on its own
 pub struct GraphCollectionFacade {
-    pub in_memory_graph_collection: Arc<RwLock<Vec<InMemoryGraph>>>,
+    /// Graph lock may be held while locking registry, never the other way round
+    pub in_memory_graph_collection: RwLock<HashMap<String, Arc<RwLock<InMemoryGraph>>>>,
+    /// Write-ahead log and snapshots, None keeps graphs in memory only
+    pub graph_storage: Option<Arc<GraphStorage>>,
 }
 
 /// Main Node(Vertex) document collection element
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Node {
     pub id: Uuid,
-    pub labels: Vec<String>, // TODO Create properties as JSON document
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub properties: Properties,
 }
 
 /// Main Bond(Relation) document collection element
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bond {
     pub id: Uuid,
     pub label: String,
     pub src: Uuid,
     pub dst: Uuid,
+    #[serde(default)]
+    pub properties: Properties,
 }
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum BondDirection {
     Outgoing,
     Ingoing,
@@ -73,6 +323,13 @@ impl InMemoryGraph {
             bonds_collection: Vec::new(),
             nodes_id_index: BTreeMap::new(),
             bonds_id_index: BTreeMap::new(),
+            outgoing_bonds: BTreeMap::new(),
+            incoming_bonds: BTreeMap::new(),
+            labels_index: BTreeMap::new(),
+            bond_type_counts: BTreeMap::new(),
+            property_indexes: Vec::new(),
+            version: 0,
+            change_feed: Arc::new(GraphFeed::default()),
         }
     }
 
@@ -83,207 +340,654 @@ impl InMemoryGraph {
     }
 
     /// Add Node to Graph
-    pub fn add_node(&mut self, mut node: Node) -> Result<(), ()> {
+    pub fn add_node(&mut self, mut node: Node) -> Result<(), GraphError> {
+        self.check_new_node(&mut node)?;
+
+        let len = self.nodes_collection.len();
+        self.nodes_id_index.insert(node.id, len);
+        for label in node.labels.iter() {
+            self.labels_index
+                .entry(label.clone())
+                .or_default()
+                .insert(len);
+        }
+        add_to_property_indexes(&mut self.property_indexes, &node);
+        self.nodes_collection.push(node);
+
+        Ok(())
+    }
+
+    /// Checks Node can be added, generating its id if not set
+    pub fn check_new_node(&self, node: &mut Node) -> Result<(), GraphError> {
         if node.labels.len() == 0 || node.labels[0].trim().is_empty() {
-            return Err(());
+            return Err(GraphError::InvalidLabel);
         }
 
+        validate_properties(&node.properties)?;
+
         if node.id == Uuid::default() {
             node.id = Uuid::new_v4();
         }
 
         if self.nodes_id_index.contains_key(&node.id) {
-            return Err(());
+            return Err(GraphError::DuplicateNodeId(node.id));
         }
-
-        let len = self.nodes_collection.len();
-        self.nodes_id_index.insert(node.id, len);
-        self.nodes_collection.push(node);
-
         Ok(())
     }
 
     /// Add Bond to Graph
-    pub fn add_bond(&mut self, mut bond: Bond) -> Result<(), ()> {
-        if bond.src == Uuid::default() || bond.dst == Uuid::default() {
-            return Err(());
-        }
+    pub fn add_bond(&mut self, mut bond: Bond) -> Result<(), GraphError> {
+        self.check_new_bond(&mut bond)?;
 
+        let len = self.bonds_collection.len();
+        self.bonds_id_index.insert(bond.id, len);
+        self.outgoing_bonds
+            .entry(bond.src)
+            .or_default()
+            .push(bond.id);
+        self.incoming_bonds
+            .entry(bond.dst)
+            .or_default()
+            .push(bond.id);
+        *self.bond_type_counts.entry(bond.label.clone()).or_default() += 1;
+        self.bonds_collection.push(bond);
+        Ok(())
+    }
+
+    /// Checks Bond can be added, generating its id if not set
+    pub fn check_new_bond(&self, bond: &mut Bond) -> Result<(), GraphError> {
         // Check if bond label not empty
         if bond.label.trim().is_empty() {
-            return Err(());
+            return Err(GraphError::InvalidLabel);
         }
 
-        // Check if src and dst exist in nodes:
-        let is_src_exists = self.nodes_collection.iter().any(|x| x.id == bond.src);
-        let is_dst_exists = self.nodes_collection.iter().any(|x| x.id == bond.dst);
+        validate_properties(&bond.properties)?;
 
-        if !is_src_exists || !is_dst_exists {
-            return Err(());
+        // Check if src and dst exist in nodes, nil id never does
+        for endpoint in [bond.src, bond.dst] {
+            if !self.nodes_id_index.contains_key(&endpoint) {
+                return Err(GraphError::DanglingBondEndpoint(endpoint));
+            }
         }
 
-        // Generate bond id
-        let mut id_vec: Vec<Uuid> = self.bonds_collection.iter().map(|x| x.id).collect();
+        // Generate bond id if not set, keep given one otherwise (e.g. on log replay)
+        if bond.id == Uuid::default() {
+            bond.id = Uuid::new_v4();
+        }
 
-        bond.id = Uuid::new_v4();
+        if self.bonds_id_index.contains_key(&bond.id) {
+            return Err(GraphError::DuplicateBondId(bond.id));
+        }
+        Ok(())
+    }
 
-        self.bonds_collection.push(bond);
+    /// Deletes Bond, moving last bond into its slot and fixing up indexes
+    pub fn delete_bond(&mut self, bond_id: Uuid) -> Result<Bond, GraphError> {
+        let bond_index = match self.bonds_id_index.remove(&bond_id) {
+            None => return Err(GraphError::BondNotFound(bond_id)),
+            Some(i) => i,
+        };
+
+        let bond = self.bonds_collection.swap_remove(bond_index);
+        if bond_index < self.bonds_collection.len() {
+            let moved_id = self.bonds_collection[bond_index].id;
+            self.bonds_id_index.insert(moved_id, bond_index);
+        }
+        remove_from_adjacency(&mut self.outgoing_bonds, bond.src, bond.id);
+        remove_from_adjacency(&mut self.incoming_bonds, bond.dst, bond.id);
+        if let Some(count) = self.bond_type_counts.get_mut(&bond.label) {
+            *count -= 1;
+            if *count == 0 {
+                self.bond_type_counts.remove(&bond.label);
+            }
+        }
+        Ok(bond)
+    }
+
+    /// Deletes Node, moving last node into its slot and fixing up indexes.
+    /// Node with bonds is deleted only with `detach`, which deletes its bonds too.
+    pub fn delete_node(&mut self, node_id: Uuid, detach: bool) -> Result<Node, GraphError> {
+        self.delete_node_with_bonds(node_id, detach)
+            .map(|(node, _)| node)
+    }
+
+    /// Same as delete_node, also returning bonds deleted by `detach`
+    pub fn delete_node_with_bonds(
+        &mut self,
+        node_id: Uuid,
+        detach: bool,
+    ) -> Result<(Node, Vec<Bond>), GraphError> {
+        self.check_node_deletable(node_id, detach)?;
+        let node_index = self.nodes_id_index[&node_id];
+
+        let mut incident_bonds = self.incident_bonds(node_id);
+        // Self loop is both outgoing and incoming
+        incident_bonds.sort();
+        incident_bonds.dedup();
+        let mut deleted_bonds = Vec::with_capacity(incident_bonds.len());
+        for bond_id in incident_bonds {
+            deleted_bonds.push(self.delete_bond(bond_id)?);
+        }
+
+        self.nodes_id_index.remove(&node_id);
+        let last_index = self.nodes_collection.len() - 1;
+        let node = self.nodes_collection.swap_remove(node_index);
+        for label in node.labels.iter() {
+            remove_from_labels_index(&mut self.labels_index, label, node_index);
+        }
+        remove_from_property_indexes(&mut self.property_indexes, &node);
+
+        if node_index < last_index {
+            let moved_node = &self.nodes_collection[node_index];
+            self.nodes_id_index.insert(moved_node.id, node_index);
+            for label in moved_node.labels.iter() {
+                let positions = self.labels_index.entry(label.clone()).or_default();
+                positions.remove(&last_index);
+                positions.insert(node_index);
+            }
+        }
+        Ok((node, deleted_bonds))
+    }
+
+    /// Checks Node exists and, unless `detach`, has no bonds
+    pub fn check_node_deletable(&self, node_id: Uuid, detach: bool) -> Result<(), GraphError> {
+        if !self.nodes_id_index.contains_key(&node_id) {
+            return Err(GraphError::NodeNotFound(node_id));
+        }
+        if !detach && !self.incident_bonds(node_id).is_empty() {
+            return Err(GraphError::NodeHasBonds(node_id));
+        }
         Ok(())
     }
 
-    fn get_connected_nodes_by_depth(&self, node_id: Uuid, depth: u32) {
-        todo!();
+    /// Ids of outgoing and incoming bonds of node, self loops twice
+    fn incident_bonds(&self, node_id: Uuid) -> Vec<Uuid> {
+        self.outgoing_bonds
+            .get(&node_id)
+            .into_iter()
+            .chain(self.incoming_bonds.get(&node_id))
+            .flatten()
+            .copied()
+            .collect()
     }
 
-    /// SHITTY CODE - REFACTOR!!!!!!!!!!!!!
-    /// GETS CONNECTED NODES WITH CURRENT
-    pub fn get_connected_nodes(
+    /// Rebuilds id indexes from collections, used after loading a snapshot
+    pub fn rebuild_indexes(&mut self) {
+        self.nodes_id_index = self
+            .nodes_collection
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (x.id, i))
+            .collect();
+        self.bonds_id_index = self
+            .bonds_collection
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (x.id, i))
+            .collect();
+
+        self.outgoing_bonds.clear();
+        self.incoming_bonds.clear();
+        self.bond_type_counts.clear();
+        for bond in self.bonds_collection.iter() {
+            *self.bond_type_counts.entry(bond.label.clone()).or_default() += 1;
+            self.outgoing_bonds
+                .entry(bond.src)
+                .or_default()
+                .push(bond.id);
+            self.incoming_bonds
+                .entry(bond.dst)
+                .or_default()
+                .push(bond.id);
+        }
+
+        self.labels_index.clear();
+        for (i, node) in self.nodes_collection.iter().enumerate() {
+            for label in node.labels.iter() {
+                self.labels_index
+                    .entry(label.clone())
+                    .or_default()
+                    .insert(i);
+            }
+        }
+
+        for index in self.property_indexes.iter_mut() {
+            index.clear();
+        }
+        for node in self.nodes_collection.iter() {
+            add_to_property_indexes(&mut self.property_indexes, node);
+        }
+    }
+
+    /// Replaces properties of existing Node, keeping property indexes in sync
+    pub fn update_node_properties(
+        &mut self,
+        node_id: Uuid,
+        properties: Properties,
+    ) -> Result<(), GraphError> {
+        self.check_node_properties(node_id, &properties)?;
+        let node_index = self.nodes_id_index[&node_id];
+
+        let node = &mut self.nodes_collection[node_index];
+        remove_from_property_indexes(&mut self.property_indexes, node);
+        node.properties = properties;
+        add_to_property_indexes(&mut self.property_indexes, node);
+        Ok(())
+    }
+
+    /// Checks Node exists and properties can replace its ones
+    pub fn check_node_properties(
         &self,
         node_id: Uuid,
+        properties: &Properties,
+    ) -> Result<(), GraphError> {
+        validate_properties(properties)?;
+        if !self.nodes_id_index.contains_key(&node_id) {
+            return Err(GraphError::NodeNotFound(node_id));
+        }
+        Ok(())
+    }
+
+    /// Declares index on (label, property) and fills it from existing nodes
+    pub fn create_property_index(
+        &mut self,
+        label: String,
+        property: String,
+    ) -> Result<(), GraphError> {
+        self.check_new_property_index(&label, &property)?;
+
+        let mut index = PropertyIndex::new(PropertyIndexDefinition { label, property });
+        for node in self.nodes_collection.iter() {
+            if let Some(key) = index.index_key(&node.labels, &node.properties) {
+                index.insert(key, node.id);
+            }
+        }
+        self.property_indexes.push(index);
+        Ok(())
+    }
+
+    /// Checks index on (label, property) can be declared
+    pub fn check_new_property_index(&self, label: &str, property: &str) -> Result<(), GraphError> {
+        if label.trim().is_empty() || property.trim().is_empty() {
+            return Err(GraphError::InvalidPropertyIndex);
+        }
+        if self.find_property_index(label, property).is_some() {
+            return Err(GraphError::DuplicatePropertyIndex {
+                label: label.to_string(),
+                property: property.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Drops index on (label, property)
+    pub fn drop_property_index(&mut self, label: &str, property: &str) -> Result<(), GraphError> {
+        self.check_property_index_exists(label, property)?;
+        self.property_indexes
+            .retain(|x| x.definition.label != label || x.definition.property != property);
+        Ok(())
+    }
+
+    /// Checks index on (label, property) is declared
+    pub fn check_property_index_exists(
+        &self,
+        label: &str,
+        property: &str,
+    ) -> Result<(), GraphError> {
+        if self.find_property_index(label, property).is_none() {
+            return Err(GraphError::PropertyIndexNotFound {
+                label: label.to_string(),
+                property: property.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Applies logged mutation, returning node and bond changes it made
+    pub fn apply_record(&mut self, record: GraphWalRecord) -> Result<Vec<GraphChange>, GraphError> {
+        let mut changes = Vec::new();
+        match record {
+            GraphWalRecord::CreateGraph { .. } => {}
+            GraphWalRecord::AddNode(node) => {
+                self.add_node(node.clone())?;
+                changes.push(GraphChange::NodeCreated { node });
+            }
+            GraphWalRecord::AddBond(bond) => {
+                self.add_bond(bond.clone())?;
+                changes.push(GraphChange::BondCreated { bond });
+            }
+            GraphWalRecord::UpdateNodeProperties { id, properties } => {
+                self.update_node_properties(id, properties)?;
+            }
+            GraphWalRecord::CreatePropertyIndex { label, property } => {
+                self.create_property_index(label, property)?;
+            }
+            GraphWalRecord::DropPropertyIndex { label, property } => {
+                self.drop_property_index(&label, &property)?;
+            }
+            GraphWalRecord::DeleteNode { id, detach } => {
+                let (node, bonds) = self.delete_node_with_bonds(id, detach)?;
+                changes.extend(
+                    bonds
+                        .into_iter()
+                        .map(|bond| GraphChange::BondDeleted { bond }),
+                );
+                changes.push(GraphChange::NodeDeleted { node });
+            }
+            GraphWalRecord::DeleteBond { id } => {
+                let bond = self.delete_bond(id)?;
+                changes.push(GraphChange::BondDeleted { bond });
+            }
+            GraphWalRecord::Transaction(records) => {
+                for record in records {
+                    changes.extend(self.apply_record(record)?);
+                }
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Lists declared property indexes
+    pub fn get_property_indexes(&self) -> Vec<&PropertyIndexDefinition> {
+        self.property_indexes
+            .iter()
+            .map(|x| &x.definition)
+            .collect()
+    }
+
+    fn find_property_index(&self, label: &str, property: &str) -> Option<&PropertyIndex> {
+        self.property_indexes
+            .iter()
+            .find(|x| x.definition.label == label && x.definition.property == property)
+    }
+
+    /// GETS NODES WITH LABEL WHOSE PROPERTY IS INSIDE RANGE, USING INDEX IF THERE IS ONE
+    pub fn get_nodes_by_property_range(
+        &self,
+        label: &str,
+        property: &str,
+        from: Bound<Value>,
+        to: Bound<Value>,
+    ) -> Result<Vec<&Node>, GraphError> {
+        let from = map_bound(from)?;
+        let to = map_bound(to)?;
+
+        if let Some(index) = self.find_property_index(label, property) {
+            return Ok(self.nodes_by_ids(index.get_range(from, to)));
+        }
+
+        // No index - scan, ordering result the same way index does
+        let mut matched: Vec<(IndexValue, &Node)> = self
+            .nodes_collection
+            .iter()
+            .filter(|x| x.labels.iter().any(|l| l == label))
+            .filter_map(|x| {
+                x.properties
+                    .get(property)
+                    .and_then(IndexValue::from_json)
+                    .map(|v| (v, x))
+            })
+            .filter(|(v, _)| is_in_range(v, &from, &to))
+            .collect();
+        matched.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(matched.into_iter().map(|(_, x)| x).collect())
+    }
+
+    /// GETS NODES WITH LABEL WHOSE PROPERTY IS EQUAL TO VALUE
+    pub fn get_nodes_by_property_value(
+        &self,
+        label: &str,
+        property: &str,
+        value: Value,
+    ) -> Result<Vec<&Node>, GraphError> {
+        self.get_nodes_by_property_range(
+            label,
+            property,
+            Bound::Included(value.clone()),
+            Bound::Included(value),
+        )
+    }
+
+    /// Maps ids to nodes skipping unknown ones
+    fn nodes_by_ids(&self, ids: Vec<Uuid>) -> Vec<&Node> {
+        ids.iter()
+            .filter_map(|x| self.nodes_id_index.get(x))
+            .map(|x| &self.nodes_collection[*x])
+            .collect()
+    }
+
+    /// Whether property of nodes with label is indexed
+    pub(crate) fn has_property_index(&self, label: &str, property: &str) -> bool {
+        self.find_property_index(label, property).is_some()
+    }
+
+    /// Positions of nodes with label that may match filter, if some index covers it
+    pub(crate) fn property_index_candidates(
+        &self,
+        label: &str,
+        node_properties: &Properties,
+    ) -> Option<Vec<usize>> {
+        for (property, value) in node_properties {
+            let index = match self.find_property_index(label, property) {
+                None => continue,
+                Some(i) => i,
+            };
+            let key = match IndexValue::from_json(value) {
+                None => continue,
+                Some(k) => k,
+            };
+            let mut positions: Vec<usize> = index
+                .get_equal(&key)
+                .iter()
+                .filter_map(|x| self.nodes_id_index.get(x).copied())
+                .collect();
+            positions.sort();
+            return Some(positions);
+        }
+        None
+    }
+
+    /// GETS NODES REACHABLE FROM CURRENT WITHIN `depth` HOPS (BFS)
+    /// Every node is returned once with its hop distance, current node first with 0.
+    /// Filters apply on every hop: only matching bonds are followed and
+    /// only matching nodes are returned and expanded further.
+    pub fn get_connected_nodes_by_depth(
+        &self,
+        node_id: Uuid,
+        depth: u32,
         bond_types: Vec<String>,
         node_labels: Vec<String>,
+        node_properties: Properties,
         direction: BondDirection,
-    ) -> Result<Vec<&Node>, ()> {
-        let mut nodes_refs = Vec::<&Node>::new();
-        let node_index_opt = self.nodes_id_index.get(&node_id);
+    ) -> Result<Vec<(&Node, u32)>, GraphError> {
+        let start_node = match self.nodes_id_index.get(&node_id) {
+            None => return Err(GraphError::NodeNotFound(node_id)),
+            Some(i) => &self.nodes_collection[*i],
+        };
+
+        let mut result = vec![(start_node, 0)];
+        let mut visited = HashSet::<Uuid>::new();
+        visited.insert(node_id);
+        let mut queue = VecDeque::new();
+        queue.push_back((node_id, 0));
+
+        while let Some((curr_id, curr_depth)) = queue.pop_front() {
+            if curr_depth >= depth {
+                continue;
+            }
+
+            let mut neighbours = Vec::<&Node>::new();
+            if direction != BondDirection::Ingoing {
+                for bond in self.get_outgoing_bonds(curr_id) {
+                    self.push_if_matches(
+                        bond,
+                        bond.dst,
+                        &bond_types,
+                        &node_labels,
+                        &node_properties,
+                        &mut neighbours,
+                    );
+                }
+            }
+            if direction != BondDirection::Outgoing {
+                for bond in self.get_incoming_bonds(curr_id) {
+                    self.push_if_matches(
+                        bond,
+                        bond.src,
+                        &bond_types,
+                        &node_labels,
+                        &node_properties,
+                        &mut neighbours,
+                    );
+                }
+            }
 
-        if node_index_opt.is_none() {
-            return Err(());
+            for neighbour in neighbours {
+                if visited.insert(neighbour.id) {
+                    result.push((neighbour, curr_depth + 1));
+                    queue.push_back((neighbour.id, curr_depth + 1));
+                }
+            }
         }
 
-        let node_labels_len = node_labels.len();
-        let bond_types_len = bond_types.len();
-        let curr_node = &self.nodes_collection[*node_index_opt.unwrap()];
+        Ok(result)
+    }
+
+    /// GETS CONNECTED NODES WITH CURRENT
+    /// Returns current node first, then one entry per matching bond,
+    /// walking only bonds incident to the node
+    pub fn get_connected_nodes(
+        &self,
+        node_id: Uuid,
+        bond_types: Vec<String>,
+        node_labels: Vec<String>,
+        node_properties: Properties,
+        direction: BondDirection,
+    ) -> Result<Vec<&Node>, GraphError> {
+        let mut nodes_refs = Vec::<&Node>::new();
+        let curr_node = match self.nodes_id_index.get(&node_id) {
+            None => return Err(GraphError::NodeNotFound(node_id)),
+            Some(i) => &self.nodes_collection[*i],
+        };
         nodes_refs.push(curr_node);
 
         // If ingoing - skip
         if direction != BondDirection::Ingoing {
-            add_outgoing_nodes(
-                &self,
-                node_id,
-                &bond_types,
-                &node_labels,
-                &mut nodes_refs,
-                node_labels_len,
-                bond_types_len,
-            );
+            for bond in self.get_outgoing_bonds(node_id) {
+                self.push_if_matches(
+                    bond,
+                    bond.dst,
+                    &bond_types,
+                    &node_labels,
+                    &node_properties,
+                    &mut nodes_refs,
+                );
+            }
         }
 
         // If outgoing - skip
         if direction != BondDirection::Outgoing {
-            add_ingoing_nodes(
-                &self,
-                node_id,
-                &bond_types,
-                &node_labels,
-                &mut nodes_refs,
-                node_labels_len,
-                bond_types_len,
-            );
+            for bond in self.get_incoming_bonds(node_id) {
+                self.push_if_matches(
+                    bond,
+                    bond.src,
+                    &bond_types,
+                    &node_labels,
+                    &node_properties,
+                    &mut nodes_refs,
+                );
+            }
         }
 
-        return Ok(nodes_refs);
-
-        // INNER FUNCTIONS:
-        /// Get nodes nodes by outgoing bonds
-        fn add_outgoing_nodes<'a>(
-            self_graph: &'a InMemoryGraph,
-            node_id: Uuid,
-            bond_types: &Vec<String>,
-            node_labels: &Vec<String>,
-            nodes_refs: &mut Vec<&'a Node>,
-            node_labels_len: usize,
-            bond_types_len: usize,
-        ) {
-            // get id by outgoing
-            let nodes_by_outgoing_ids: Vec<Uuid> = self_graph
-                .bonds_collection
-                .iter()
-                .filter(|x| {
-                    x.src == node_id && {
-                        if bond_types_len == 0 {
-                            true
-                        } else {
-                            bond_types.contains(&x.label)
-                        }
-                    }
-                })
-                .map(|x| x.dst)
-                .collect();
+        Ok(nodes_refs)
+    }
 
-            for i in 0..nodes_by_outgoing_ids.len() {
-                let curr_node_index = self_graph
-                    .nodes_id_index
-                    .get(&nodes_by_outgoing_ids[i])
-                    .unwrap();
-                let dst_node = &self_graph.nodes_collection[*curr_node_index];
-
-                // if len is 0 - we include all labels
-                if node_labels_len == 0 {
-                    nodes_refs.push(dst_node);
-                    continue;
-                }
+    /// Pushes node on other end of bond if bond and node pass filters,
+    /// empty bond_types or node_labels means no filter
+    fn push_if_matches<'a>(
+        &'a self,
+        bond: &Bond,
+        other_node_id: Uuid,
+        bond_types: &[String],
+        node_labels: &[String],
+        node_properties: &Properties,
+        nodes_refs: &mut Vec<&'a Node>,
+    ) {
+        if !bond_types.is_empty() && !bond_types.contains(&bond.label) {
+            return;
+        }
 
-                // Add only if labels intersect
-                for label in &dst_node.labels {
-                    if node_labels.contains(label) {
-                        nodes_refs.push(dst_node);
-                        continue;
-                    }
-                }
-            }
+        let other_node = match self.nodes_id_index.get(&other_node_id) {
+            None => return,
+            Some(i) => &self.nodes_collection[*i],
+        };
+
+        if !properties_match(&other_node.properties, node_properties) {
+            return;
         }
 
-        /// Get nodes nodes by ingoing bonds
-        fn add_ingoing_nodes<'a>(
-            self_graph: &'a InMemoryGraph,
-            node_id: Uuid,
-            bond_types: &Vec<String>,
-            node_labels: &Vec<String>,
-            nodes_refs: &mut Vec<&'a Node>,
-            node_labels_len: usize,
-            bond_types_len: usize,
-        ) {
-            let nodes_by_ingoing_ids: Vec<Uuid> = self_graph
-                .bonds_collection
-                .iter()
-                .filter(|x| {
-                    x.dst == node_id && {
-                        if bond_types_len == 0 {
-                            true
-                        } else {
-                            bond_types.contains(&x.label)
-                        }
-                    }
-                })
-                .map(|x| x.src)
-                .collect();
-            for i in 0..nodes_by_ingoing_ids.len() {
-                let curr_node_index = self_graph
-                    .nodes_id_index
-                    .get(&nodes_by_ingoing_ids[i])
-                    .unwrap();
-                let src_node = &self_graph.nodes_collection[*curr_node_index];
-
-                // if len is 0 - we include all labels
-                if node_labels_len == 0 {
-                    nodes_refs.push(src_node);
-                    continue;
-                }
+        // Add only if labels intersect
+        if !node_labels.is_empty() && !other_node.labels.iter().any(|x| node_labels.contains(x)) {
+            return;
+        }
 
-                // Add only if labels intersect
-                for label in &src_node.labels {
-                    if node_labels.contains(label) {
-                        nodes_refs.push(src_node);
-                        continue;
-                    }
-                }
-            }
+        nodes_refs.push(other_node);
+    }
+
+    /// Bonds going out of node, in insertion order
+    pub fn get_outgoing_bonds(&self, node_id: Uuid) -> Vec<&Bond> {
+        self.bonds_by_adjacency(self.outgoing_bonds.get(&node_id))
+    }
+
+    /// Bonds coming into node, in insertion order
+    pub fn get_incoming_bonds(&self, node_id: Uuid) -> Vec<&Bond> {
+        self.bonds_by_adjacency(self.incoming_bonds.get(&node_id))
+    }
+
+    /// Bonds incident to node in given direction paired with id of node on their other end
+    pub fn get_adjacent_bonds(
+        &self,
+        node_id: Uuid,
+        direction: BondDirection,
+    ) -> Vec<(&Bond, Uuid)> {
+        let mut adjacent = Vec::new();
+        if direction != BondDirection::Ingoing {
+            adjacent.extend(
+                self.get_outgoing_bonds(node_id)
+                    .into_iter()
+                    .map(|x| (x, x.dst)),
+            );
+        }
+        if direction != BondDirection::Outgoing {
+            adjacent.extend(
+                self.get_incoming_bonds(node_id)
+                    .into_iter()
+                    .map(|x| (x, x.src)),
+            );
+        }
+        adjacent
+    }
+
+    fn bonds_by_adjacency(&self, bond_ids: Option<&Vec<Uuid>>) -> Vec<&Bond> {
+        match bond_ids {
+            None => Vec::new(),
+            Some(ids) => ids
+                .iter()
+                .filter_map(|x| self.bonds_id_index.get(x))
+                .map(|x| &self.bonds_collection[*x])
+                .collect(),
         }
     }
 
+    /// GETS BOND BY ID
+    pub fn get_bond_by_id(&self, bond_id: Uuid) -> Option<&Bond> {
+        self.bonds_id_index
+            .get(&bond_id)
+            .map(|x| &self.bonds_collection[*x])
+    }
+
     /// GETS NODES THAT EXIST IN UUID LIST
-    pub fn get_nodes_by_id_list(&self, uuid_list: Vec<Uuid>) -> Result<Vec<&Node>, ()> {
+    pub fn get_nodes_by_id_list(&self, uuid_list: Vec<Uuid>) -> Result<Vec<&Node>, GraphError> {
         let mut existing_node_refs = Vec::new();
         let mut existing_uuids_set = HashSet::<Uuid>::new();
 
@@ -304,18 +1008,29 @@ impl InMemoryGraph {
         Ok(existing_node_refs)
     }
 
-    /// GETS NODES THAT EXIST IN LABEL LIST
-    pub fn get_nodes_by_label_list(&self, label_list: Vec<String>) -> Result<Vec<&Node>, ()> {
+    /// GETS NODES THAT EXIST IN LABEL LIST AND HAVE ALL GIVEN PROPERTIES
+    pub fn get_nodes_by_label_list(
+        &self,
+        label_list: Vec<String>,
+        node_properties: Properties,
+    ) -> Result<Vec<&Node>, GraphError> {
         let mut existing_node_refs = Vec::new();
         let mut existing_uuids_set = HashSet::<Uuid>::new();
 
         // CHECK IF EXISTS => THEN ADD TO RETURN VECTOR
-        // CURRENTLY THERE IS NO INDEX ON LABELS
-        // NOW IT IS MIGHT BE TOO SLOW
+        // CANDIDATES COME FROM PROPERTY INDEX IF IT COVERS THE FILTER, LABEL INDEX OTHERWISE
         for i in 0..label_list.len() {
-            for j in 0..self.nodes_collection.len() {
+            let positions = match self.property_index_candidates(&label_list[i], &node_properties) {
+                Some(p) => p,
+                None => match self.labels_index.get(&label_list[i]) {
+                    None => continue,
+                    Some(p) => p.iter().copied().collect(),
+                },
+            };
+            for j in positions {
                 if self.nodes_collection[j].labels.contains(&label_list[i])
                     && !existing_uuids_set.contains(&self.nodes_collection[j].id)
+                    && properties_match(&self.nodes_collection[j].properties, &node_properties)
                 {
                     existing_uuids_set.insert(self.nodes_collection[j].id);
                     existing_node_refs.push(&self.nodes_collection[j]);
@@ -326,15 +1041,9 @@ impl InMemoryGraph {
         Ok(existing_node_refs)
     }
 
-    fn get_paths_between_ids(&self, start_id: u32, finish_id: u32) -> Result<Vec<Vec<u32>>, ()> {
-        todo!();
-        let paths = Vec::new();
-        Ok(paths)
-    }
-
-    /// Drops Whole Graph
+    /// Drops Whole Graph, releasing its collections and indexes
     pub fn delete_graph(self) {
-        todo!();
+        drop(self);
     }
 
     pub fn get_graph_nodes_number(&self) -> usize {
@@ -352,13 +1061,13 @@ impl InMemoryGraph {
 //  Main Graph action Methods impl
 impl Graph for InMemoryGraph {
     /// Creates Node, adding to nodes collection
-    fn create_node(&mut self, node: Node) -> Result<(), ()> {
+    fn create_node(&mut self, node: Node) -> Result<(), GraphError> {
         self.nodes_collection.push(node);
         Ok(())
     }
 
     /// Creates Bond, adding to bonds collection
-    fn create_bond(&mut self, bond: Bond) -> Result<(), ()> {
+    fn create_bond(&mut self, bond: Bond) -> Result<(), GraphError> {
         self.bonds_collection.push(bond);
         Ok(())
     }
@@ -366,21 +1075,478 @@ impl Graph for InMemoryGraph {
 
 impl Node {
     fn new(id: Uuid, labels: Vec<String>) -> Self {
-        Node { id, labels }
+        Node {
+            id,
+            labels,
+            properties: Properties::new(),
+        }
+    }
+}
+
+impl From<CreateNodeDTO> for Node {
+    fn from(dto: CreateNodeDTO) -> Self {
+        Node {
+            id: dto.id,
+            labels: dto.labels,
+            properties: dto.properties,
+        }
+    }
+}
+
+impl From<CreateBondDTO> for Bond {
+    fn from(dto: CreateBondDTO) -> Self {
+        Bond {
+            id: dto.id,
+            label: dto.label,
+            src: dto.src,
+            dst: dto.dst,
+            properties: dto.properties,
+        }
+    }
+}
+
+impl From<&Node> for ReturnNodeDTO {
+    fn from(node: &Node) -> Self {
+        ReturnNodeDTO {
+            id: node.id,
+            labels: node.labels.clone(),
+            properties: node.properties.clone(),
+            bonds: None,
+        }
+    }
+}
+
+impl From<&Bond> for ReturnBondDTO {
+    fn from(bond: &Bond) -> Self {
+        ReturnBondDTO {
+            id: bond.id,
+            label: bond.label.clone(),
+            src: bond.src,
+            dst: bond.dst,
+            properties: bond.properties.clone(),
+        }
+    }
+}
+
+/// Removes bond id from adjacency list of node, dropping list once it is empty
+fn remove_from_adjacency(adjacency: &mut BTreeMap<Uuid, Vec<Uuid>>, node_id: Uuid, bond_id: Uuid) {
+    let is_empty = match adjacency.get_mut(&node_id) {
+        None => return,
+        Some(ids) => {
+            ids.retain(|x| *x != bond_id);
+            ids.is_empty()
+        }
+    };
+    if is_empty {
+        adjacency.remove(&node_id);
+    }
+}
+
+/// Removes node position from label entry, dropping entry once it is empty
+fn remove_from_labels_index(
+    labels_index: &mut BTreeMap<String, BTreeSet<usize>>,
+    label: &str,
+    position: usize,
+) {
+    let is_empty = match labels_index.get_mut(label) {
+        None => return,
+        Some(positions) => {
+            positions.remove(&position);
+            positions.is_empty()
+        }
+    };
+    if is_empty {
+        labels_index.remove(label);
+    }
+}
+
+/// Adds node to every property index covering it
+fn add_to_property_indexes(indexes: &mut [PropertyIndex], node: &Node) {
+    for index in indexes.iter_mut() {
+        if let Some(key) = index.index_key(&node.labels, &node.properties) {
+            index.insert(key, node.id);
+        }
+    }
+}
+
+/// Removes node from every property index covering it
+fn remove_from_property_indexes(indexes: &mut [PropertyIndex], node: &Node) {
+    for index in indexes.iter_mut() {
+        if let Some(key) = index.index_key(&node.labels, &node.properties) {
+            index.remove(&key, &node.id);
+        }
+    }
+}
+
+/// Maps JSON bound to index bound, only scalar values are comparable
+fn map_bound(bound: Bound<Value>) -> Result<Bound<IndexValue>, GraphError> {
+    let not_scalar = || GraphError::InvalidQuery(String::from("range bound must be scalar"));
+    match bound {
+        Bound::Unbounded => Ok(Bound::Unbounded),
+        Bound::Included(v) => IndexValue::from_json(&v)
+            .map(Bound::Included)
+            .ok_or_else(not_scalar),
+        Bound::Excluded(v) => IndexValue::from_json(&v)
+            .map(Bound::Excluded)
+            .ok_or_else(not_scalar),
+    }
+}
+
+/// Same range check index does, for values of one type only
+fn is_in_range(value: &IndexValue, from: &Bound<IndexValue>, to: &Bound<IndexValue>) -> bool {
+    let after_from = match from {
+        Bound::Unbounded => true,
+        Bound::Included(f) => value >= f && value.is_same_type(f),
+        Bound::Excluded(f) => value > f && value.is_same_type(f),
+    };
+    let before_to = match to {
+        Bound::Unbounded => true,
+        Bound::Included(t) => value <= t && value.is_same_type(t),
+        Bound::Excluded(t) => value < t && value.is_same_type(t),
+    };
+    after_from && before_to
+}
+
+/// Graph name must not be blank or longer than MAX_GRAPH_NAME_LEN bytes
+fn validate_graph_name(name: &str) -> Result<(), GraphError> {
+    if name.trim().is_empty() || name.len() > MAX_GRAPH_NAME_LEN {
+        return Err(GraphError::InvalidGraphName);
+    }
+    Ok(())
+}
+
+/// Property keys must not be blank, null values are not stored - absent key means null
+fn validate_properties(properties: &Properties) -> Result<(), GraphError> {
+    for (key, value) in properties {
+        if key.trim().is_empty() || value.is_null() {
+            return Err(GraphError::InvalidProperty(key.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// True if graph_lock is registered under graph_name in locked registry
+fn is_registered(
+    graphs: &HashMap<String, Arc<RwLock<InMemoryGraph>>>,
+    graph_name: &str,
+    graph_lock: &Arc<RwLock<InMemoryGraph>>,
+) -> bool {
+    graphs
+        .get(graph_name)
+        .is_some_and(|x| Arc::ptr_eq(x, graph_lock))
+}
+
+/// True if every filter entry is present in properties with equal value
+pub fn properties_match(properties: &Properties, filter: &Properties) -> bool {
+    filter
+        .iter()
+        .all(|(key, value)| properties.get(key) == Some(value))
+}
+
+impl GraphCollectionFacade {
+    /// Creates facade over already loaded graphs
+    pub fn new(graphs: Vec<InMemoryGraph>, graph_storage: Option<Arc<GraphStorage>>) -> Self {
+        let registry = graphs
+            .into_iter()
+            .map(|x| (x.name.clone(), Arc::new(RwLock::new(x))))
+            .collect();
+        GraphCollectionFacade {
+            in_memory_graph_collection: RwLock::new(registry),
+            graph_storage,
+        }
+    }
+
+    /// Graph by name, registry lock is released before returning
+    pub fn get_graph(&self, graph_name: &str) -> Option<Arc<RwLock<InMemoryGraph>>> {
+        let graphs = self.in_memory_graph_collection.read().unwrap();
+        graphs.get(graph_name).cloned()
+    }
+
+    pub fn contains_graph(&self, graph_name: &str) -> bool {
+        let graphs = self.in_memory_graph_collection.read().unwrap();
+        graphs.contains_key(graph_name)
+    }
+
+    /// Adds new graph to collection and logs its creation
+    pub fn add_graph(&self, graph: InMemoryGraph) -> Result<(), GraphError> {
+        validate_graph_name(&graph.name)?;
+        let mut graphs = self.in_memory_graph_collection.write().unwrap();
+        if graphs.contains_key(&graph.name) {
+            return Err(GraphError::DuplicateGraphName(graph.name));
+        }
+        if let Some(storage) = &self.graph_storage {
+            storage.log_create_graph(&graph)?;
+        }
+        graphs.insert(graph.name.clone(), Arc::new(RwLock::new(graph)));
+        Ok(())
+    }
+
+    /// Removes graph by name together with its persisted files.
+    /// Waits for mutations already running on the graph to finish.
+    pub fn delete_graph(&self, graph_name: &str) -> Result<(), GraphError> {
+        let graph_lock = match self.get_graph(graph_name) {
+            None => return Err(GraphError::GraphNotFound(graph_name.to_string())),
+            Some(g) => g,
+        };
+        // registry is not locked while waiting for the graph, so other graphs stay reachable
+        let graph = graph_lock.write().unwrap();
+        let deleted_dir = {
+            let mut graphs = self.in_memory_graph_collection.write().unwrap();
+            if !is_registered(&graphs, graph_name, &graph_lock) {
+                return Err(GraphError::GraphNotFound(graph_name.to_string()));
+            }
+            // files are only moved aside here, their removal is left for later
+            let deleted_dir = match &self.graph_storage {
+                None => None,
+                Some(storage) => Some(storage.delete_graph(graph_name)?),
+            };
+            graphs.remove(graph_name);
+            deleted_dir
+        };
+        graph.change_feed.close();
+        drop(graph);
+
+        if let (Some(storage), Some(deleted_dir)) = (&self.graph_storage, deleted_dir) {
+            // graph is gone already, recover removes whatever is left over
+            if let Err(e) = storage.remove_deleted(&deleted_dir) {
+                eprintln!("removing files of graph {} failed: {}", graph_name, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Renames graph, failing if new name is blank or taken
+    pub fn rename_graph(&self, graph_name: &str, new_name: String) -> Result<(), GraphError> {
+        validate_graph_name(&new_name)?;
+        if self.contains_graph(&new_name) {
+            return Err(GraphError::DuplicateGraphName(new_name));
+        }
+        let graph_lock = match self.get_graph(graph_name) {
+            None => return Err(GraphError::GraphNotFound(graph_name.to_string())),
+            Some(g) => g,
+        };
+        // registry is not locked while waiting for the graph, so other graphs stay reachable
+        let mut graph = graph_lock.write().unwrap();
+        let mut graphs = self.in_memory_graph_collection.write().unwrap();
+        if !is_registered(&graphs, graph_name, &graph_lock) {
+            return Err(GraphError::GraphNotFound(graph_name.to_string()));
+        }
+        if graphs.contains_key(&new_name) {
+            return Err(GraphError::DuplicateGraphName(new_name));
+        }
+        if let Some(storage) = &self.graph_storage {
+            storage.rename_graph(graph_name, &new_name)?;
+        }
+        graph.name = new_name.clone();
+        graphs.remove(graph_name);
+        graphs.insert(new_name, graph_lock.clone());
+        Ok(())
+    }
+
+    /// Lists graphs with their sizes, ordered by name
+    pub fn list_graphs(&self) -> Vec<GraphInfoDTO> {
+        // graphs are read after the registry lock is released
+        let graphs: Vec<(String, Arc<RwLock<InMemoryGraph>>)> = self
+            .in_memory_graph_collection
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, graph)| (name.clone(), graph.clone()))
+            .collect();
+        let mut infos: Vec<GraphInfoDTO> = graphs
+            .into_iter()
+            .map(|(name, graph)| {
+                let graph = graph.read().unwrap();
+                GraphInfoDTO {
+                    name,
+                    nodes: graph.get_nodes_collection_len(),
+                    bonds: graph.get_bonds_collection_len(),
+                }
+            })
+            .collect();
+        infos.sort_by(|a, b| a.name.cmp(&b.name));
+        infos
+    }
+
+    /// True while graph_lock is registered under graph_name. Checked with graph write
+    /// locked, since graph deleted or renamed while waiting for the lock must not be
+    /// logged to under a name it no longer has.
+    pub(crate) fn is_registered(
+        &self,
+        graph_name: &str,
+        graph_lock: &Arc<RwLock<InMemoryGraph>>,
+    ) -> bool {
+        let graphs = self.in_memory_graph_collection.read().unwrap();
+        is_registered(&graphs, graph_name, graph_lock)
+    }
+
+    /// Change feed of graph by name
+    pub fn graph_feed(&self, graph_name: &str) -> Result<Arc<GraphFeed>, GraphError> {
+        match self.get_graph(graph_name) {
+            None => Err(GraphError::GraphNotFound(graph_name.to_string())),
+            Some(graph) => Ok(graph.read().unwrap().change_feed.clone()),
+        }
+    }
+
+    /// Adds Node to graph by name, returning id of stored node
+    pub fn add_node(&self, graph_name: &str, mut node: Node) -> Result<Uuid, GraphError> {
+        self.apply_logged(graph_name, |graph| {
+            graph.check_new_node(&mut node)?;
+            Ok((node.id, GraphWalRecord::AddNode(node)))
+        })
+    }
+
+    /// Adds Bond to graph by name, returning id of stored bond
+    pub fn add_bond(&self, graph_name: &str, mut bond: Bond) -> Result<Uuid, GraphError> {
+        self.apply_logged(graph_name, |graph| {
+            graph.check_new_bond(&mut bond)?;
+            Ok((bond.id, GraphWalRecord::AddBond(bond)))
+        })
+    }
+
+    /// Deletes Node from graph by name, `detach` deletes its bonds as well
+    pub fn delete_node(
+        &self,
+        graph_name: &str,
+        node_id: Uuid,
+        detach: bool,
+    ) -> Result<(), GraphError> {
+        self.apply_logged(graph_name, |graph| {
+            graph.check_node_deletable(node_id, detach)?;
+            Ok((
+                (),
+                GraphWalRecord::DeleteNode {
+                    id: node_id,
+                    detach,
+                },
+            ))
+        })
+    }
+
+    /// Deletes Bond from graph by name
+    pub fn delete_bond(&self, graph_name: &str, bond_id: Uuid) -> Result<(), GraphError> {
+        self.apply_logged(graph_name, |graph| {
+            if !graph.bonds_id_index.contains_key(&bond_id) {
+                return Err(GraphError::BondNotFound(bond_id));
+            }
+            Ok(((), GraphWalRecord::DeleteBond { id: bond_id }))
+        })
+    }
+
+    /// Replaces properties of Node in graph by name
+    pub fn update_node_properties(
+        &self,
+        graph_name: &str,
+        node_id: Uuid,
+        properties: Properties,
+    ) -> Result<(), GraphError> {
+        self.apply_logged(graph_name, |graph| {
+            graph.check_node_properties(node_id, &properties)?;
+            Ok((
+                (),
+                GraphWalRecord::UpdateNodeProperties {
+                    id: node_id,
+                    properties,
+                },
+            ))
+        })
+    }
+
+    /// Declares property index in graph by name
+    pub fn create_property_index(
+        &self,
+        graph_name: &str,
+        label: String,
+        property: String,
+    ) -> Result<(), GraphError> {
+        self.apply_logged(graph_name, |graph| {
+            graph.check_new_property_index(&label, &property)?;
+            Ok(((), GraphWalRecord::CreatePropertyIndex { label, property }))
+        })
+    }
+
+    /// Drops property index in graph by name
+    pub fn drop_property_index(
+        &self,
+        graph_name: &str,
+        label: String,
+        property: String,
+    ) -> Result<(), GraphError> {
+        self.apply_logged(graph_name, |graph| {
+            graph.check_property_index_exists(&label, &property)?;
+            Ok(((), GraphWalRecord::DropPropertyIndex { label, property }))
+        })
+    }
+
+    /// Writes full snapshot of graph and truncates its log right away, so the next
+    /// load does not replay it. Writers wait on graph read lock meanwhile.
+    pub fn snapshot_graph(&self, graph_name: &str) -> Result<(), GraphError> {
+        let graph_lock = match self.get_graph(graph_name) {
+            None => return Err(GraphError::GraphNotFound(graph_name.to_string())),
+            Some(g) => g,
+        };
+        let graph = graph_lock.read().unwrap();
+        if !self.is_registered(&graph.name, &graph_lock) {
+            return Err(GraphError::GraphNotFound(graph_name.to_string()));
+        }
+        match &self.graph_storage {
+            None => Ok(()),
+            Some(storage) => Ok(storage.snapshot(&graph)?),
+        }
+    }
+
+    /// Checks mutation against graph by name under its write lock and logs its record,
+    /// only then applying it and publishing its changes, so graph is left untouched if
+    /// logging fails. Only this graph is locked, other graphs stay available.
+    fn apply_logged<T>(
+        &self,
+        graph_name: &str,
+        prepare: impl FnOnce(&InMemoryGraph) -> Result<(T, GraphWalRecord), GraphError>,
+    ) -> Result<T, GraphError> {
+        let graph_lock = match self.get_graph(graph_name) {
+            None => return Err(GraphError::GraphNotFound(graph_name.to_string())),
+            Some(g) => g,
+        };
+        let mut graph = graph_lock.write().unwrap();
+        if !self.is_registered(&graph.name, &graph_lock) {
+            return Err(GraphError::GraphNotFound(graph_name.to_string()));
+        }
+        let (result, record) = prepare(&graph)?;
+        self.log(&graph.name, &record)?;
+        let changes = graph.apply_record(record)?;
+        graph.version += 1;
+        self.snapshot_if_due(&graph);
+        graph.change_feed.publish(changes);
+        Ok(result)
+    }
+
+    /// Writes mutation to graph write-ahead log, before it is applied to graph
+    pub(crate) fn log(&self, graph_name: &str, record: &GraphWalRecord) -> Result<(), GraphError> {
+        match &self.graph_storage {
+            None => Ok(()),
+            Some(storage) => Ok(storage.log(graph_name, record)?),
+        }
+    }
+
+    /// Snapshots graph once enough mutations were logged, called after logged mutation
+    /// is applied. Failure is only reported, logged mutations stay durable without it.
+    pub(crate) fn snapshot_if_due(&self, graph: &InMemoryGraph) {
+        if let Some(storage) = &self.graph_storage {
+            if let Err(e) = storage.snapshot_if_due(graph) {
+                eprintln!("snapshot of graph {} failed: {}", graph.name, e);
+            }
+        }
     }
 }
 
 pub fn validate_and_map_graph(
     dto: CreateGraphDTO,
     graph_data: &GraphCollectionFacade,
-) -> Result<InMemoryGraph, ()> {
-    let graphs = graph_data.in_memory_graph_collection.read().unwrap();
-
+) -> Result<InMemoryGraph, GraphError> {
+    validate_graph_name(&dto.name)?;
     // check if exactly name existst
-    for i in 0..graphs.len() {
-        if dto.name == graphs[i].name {
-            return Err(());
-        }
+    if graph_data.contains_graph(&dto.name) {
+        return Err(GraphError::DuplicateGraphName(dto.name));
     }
 
     let graph = InMemoryGraph::new_graph(dto.name);
@@ -1,14 +1,12 @@
 #[cfg(test)]
 mod in_memory_graph_tests {
     use crate::core_model;
-    use std::sync::Arc;
-    use std::sync::RwLock;
+    use serde_json::{json, Map};
+    use std::ops::Bound;
     use uuid::Uuid;
 
     fn initialize_graph_collection() -> core_model::GraphCollectionFacade {
-        core_model::GraphCollectionFacade {
-            in_memory_graph_collection: Arc::new(RwLock::new(Vec::new())),
-        }
+        core_model::GraphCollectionFacade::new(Vec::new(), None)
     }
 
     #[test]
@@ -30,12 +28,12 @@ mod in_memory_graph_tests {
             name: String::from("my_new_graph_name"),
         };
 
-        {
-            let graph_collection_lock = graph_collection_fac.in_memory_graph_collection.write();
-            let mut graph_collection = graph_collection_lock.unwrap();
-            graph_collection.push(core_model::InMemoryGraph::new_graph(String::from("some")));
-            graph_collection.push(core_model::InMemoryGraph::new_graph(String::from("some2")));
-        }
+        graph_collection_fac
+            .add_graph(core_model::InMemoryGraph::new_graph(String::from("some")))
+            .unwrap();
+        graph_collection_fac
+            .add_graph(core_model::InMemoryGraph::new_graph(String::from("some2")))
+            .unwrap();
 
         let result = core_model::validate_and_map_graph(dto, &graph_collection_fac);
         assert_eq!(true, result.is_ok());
@@ -50,14 +48,14 @@ mod in_memory_graph_tests {
             name: String::from("my_new_graph_name"),
         };
 
-        {
-            let graph_collection_lock = graph_collection_fac.in_memory_graph_collection.write();
-            let mut graph_collection = graph_collection_lock.unwrap();
-            graph_collection.push(core_model::InMemoryGraph::new_graph(String::from("some")));
-            graph_collection.push(core_model::InMemoryGraph::new_graph(String::from(
+        graph_collection_fac
+            .add_graph(core_model::InMemoryGraph::new_graph(String::from("some")))
+            .unwrap();
+        graph_collection_fac
+            .add_graph(core_model::InMemoryGraph::new_graph(String::from(
                 "my_new_graph_name",
-            )));
-        }
+            )))
+            .unwrap();
 
         let result = core_model::validate_and_map_graph(dto, &graph_collection_fac);
         assert_eq!(true, result.is_err());
@@ -70,6 +68,7 @@ mod in_memory_graph_tests {
         let node = core_model::Node {
             id: Uuid::default(),
             labels: vec![String::from("red")],
+            properties: Map::new(),
         };
         let adding_result = in_mem_graph.add_node(node);
 
@@ -88,15 +87,18 @@ mod in_memory_graph_tests {
         in_mem_graph.nodes_collection.push(core_model::Node {
             id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400001").unwrap(),
             labels: vec![String::from("blue")],
+            properties: Map::new(),
         });
         in_mem_graph.nodes_collection.push(core_model::Node {
             id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400003").unwrap(),
             labels: vec![String::from("green")],
+            properties: Map::new(),
         });
 
         let addong_node = core_model::Node {
             id: Uuid::default(),
             labels: vec![String::from("red")],
+            properties: Map::new(),
         };
         let adding_result = in_mem_graph.add_node(addong_node);
 
@@ -122,16 +124,19 @@ mod in_memory_graph_tests {
         let r1 = in_mem_graph.add_node(core_model::Node {
             id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400001").unwrap(),
             labels: vec![String::from("blue")],
+            properties: Map::new(),
         });
         let r2 = in_mem_graph.add_node(core_model::Node {
             id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400003").unwrap(),
             labels: vec![String::from("green")],
+            properties: Map::new(),
         });
 
         let checking_node_uuid = Uuid::parse_str("550e8400-e29b-41d4-a716-446655400004").unwrap();
         let adding_node = core_model::Node {
             id: checking_node_uuid,
             labels: vec![String::from("red")],
+            properties: Map::new(),
         };
         let adding_result = in_mem_graph.add_node(adding_node);
 
@@ -161,18 +166,22 @@ mod in_memory_graph_tests {
         let r1 = in_mem_graph.add_node(core_model::Node {
             id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400001").unwrap(),
             labels: vec![String::from("blue")],
+            properties: Map::new(),
         });
         let r2 = in_mem_graph.add_node(core_model::Node {
             id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400003").unwrap(),
             labels: vec![String::from("green")],
+            properties: Map::new(),
         });
         let r3 = in_mem_graph.add_node(core_model::Node {
             id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400300").unwrap(),
             labels: vec![String::from("green")],
+            properties: Map::new(),
         });
         let r4 = in_mem_graph.add_node(core_model::Node {
             id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400002").unwrap(),
             labels: vec![String::from("green")],
+            properties: Map::new(),
         });
 
         let node_vector_index = in_mem_graph
@@ -193,15 +202,18 @@ mod in_memory_graph_tests {
         let r1 = in_mem_graph.add_node(core_model::Node {
             id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400001").unwrap(),
             labels: vec![String::from("blue")],
+            properties: Map::new(),
         });
         let r2 = in_mem_graph.add_node(core_model::Node {
             id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400003").unwrap(),
             labels: vec![String::from("green")],
+            properties: Map::new(),
         });
 
         let adding_node = core_model::Node {
             id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400001").unwrap(),
             labels: vec![String::from("red")],
+            properties: Map::new(),
         };
         let adding_result = in_mem_graph.add_node(adding_node);
 
@@ -224,18 +236,21 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400001").unwrap(),
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400003").unwrap(),
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
 
         let adding_node = core_model::Node {
             id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400002").unwrap(),
             labels: vec![String::from("")],
+            properties: Map::new(),
         };
         let adding_result = in_mem_graph.add_node(adding_node);
 
@@ -257,18 +272,21 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400001").unwrap(),
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400003").unwrap(),
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
 
         let adding_node = core_model::Node {
             id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400002").unwrap(),
             labels: vec![String::from(" ")],
+            properties: Map::new(),
         };
         let adding_result = in_mem_graph.add_node(adding_node);
 
@@ -296,30 +314,35 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: uuid_1,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_2,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_3,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_4,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_5,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -329,6 +352,7 @@ mod in_memory_graph_tests {
                 src: uuid_2,
                 dst: uuid_4,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -337,6 +361,7 @@ mod in_memory_graph_tests {
                 src: uuid_3,
                 dst: uuid_2,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -345,6 +370,7 @@ mod in_memory_graph_tests {
                 src: uuid_1,
                 dst: uuid_5,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -353,6 +379,7 @@ mod in_memory_graph_tests {
             src: uuid_1,
             dst: uuid_2,
             id: Uuid::new_v4(),
+            properties: Map::new(),
         };
         let adding_result = in_mem_graph.add_bond(adding_bond);
 
@@ -373,30 +400,35 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: uuid_1,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_2,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_3,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_4,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_5,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -405,6 +437,7 @@ mod in_memory_graph_tests {
             src: Uuid::parse_str("550e8400-e29b-41d4-a716-446655400010").unwrap(),
             dst: uuid_2,
             id: Uuid::new_v4(),
+            properties: Map::new(),
         };
         let adding_result = in_mem_graph.add_bond(adding_bond);
 
@@ -426,30 +459,35 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: uuid_1,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_2,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_3,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_4,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_5,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -458,6 +496,7 @@ mod in_memory_graph_tests {
             src: uuid_1,
             dst: uuid_2,
             id: Uuid::new_v4(),
+            properties: Map::new(),
         };
         let adding_result = in_mem_graph.add_bond(adding_bond);
 
@@ -479,30 +518,35 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: uuid_1,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_2,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_3,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_4,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_5,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -512,6 +556,7 @@ mod in_memory_graph_tests {
                 src: uuid_2,
                 dst: uuid_4,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -520,6 +565,7 @@ mod in_memory_graph_tests {
                 src: uuid_3,
                 dst: uuid_2,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -528,6 +574,7 @@ mod in_memory_graph_tests {
                 src: uuid_1,
                 dst: uuid_5,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -536,6 +583,7 @@ mod in_memory_graph_tests {
                 uuid_2,
                 Vec::new(),
                 Vec::new(),
+                Map::new(),
                 core_model::BondDirection::Both,
             )
             .unwrap();
@@ -565,30 +613,35 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: uuid_1,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_2,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_3,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_4,
                 labels: vec![String::from("grey")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_5,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -598,6 +651,7 @@ mod in_memory_graph_tests {
                 src: uuid_2,
                 dst: uuid_4,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -606,6 +660,7 @@ mod in_memory_graph_tests {
                 src: uuid_3,
                 dst: uuid_2,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -614,6 +669,7 @@ mod in_memory_graph_tests {
                 src: uuid_1,
                 dst: uuid_5,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -622,6 +678,7 @@ mod in_memory_graph_tests {
                 uuid_2,
                 Vec::new(),
                 vec!["green".to_string()],
+                Map::new(),
                 core_model::BondDirection::Both,
             )
             .unwrap();
@@ -652,30 +709,35 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: uuid_1,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_2,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_3,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_4,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_5,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -685,6 +747,7 @@ mod in_memory_graph_tests {
                 src: uuid_2,
                 dst: uuid_4,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -693,6 +756,7 @@ mod in_memory_graph_tests {
                 src: uuid_3,
                 dst: uuid_2,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -701,6 +765,7 @@ mod in_memory_graph_tests {
                 src: uuid_1,
                 dst: uuid_5,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -709,6 +774,7 @@ mod in_memory_graph_tests {
                 uuid_2,
                 vec!["green-green".to_string()],
                 Vec::new(),
+                Map::new(),
                 core_model::BondDirection::Both,
             )
             .unwrap();
@@ -739,30 +805,35 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: uuid_1,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_2,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_3,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_4,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_5,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -771,6 +842,7 @@ mod in_memory_graph_tests {
                 uuid_2,
                 vec!["green-green".to_string()],
                 Vec::new(),
+                Map::new(),
                 core_model::BondDirection::Both,
             )
             .unwrap();
@@ -797,30 +869,35 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: uuid_1,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_2,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_3,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_4,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_5,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -828,6 +905,7 @@ mod in_memory_graph_tests {
             Uuid::parse_str("550e8400-e29b-41d4-a716-446655400006").unwrap(),
             vec!["green-green".to_string()],
             Vec::new(),
+            Map::new(),
             core_model::BondDirection::Both,
         );
 
@@ -848,30 +926,35 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: uuid_1,
                 labels: vec![String::from("blue1")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_2,
                 labels: vec![String::from("blue2")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_3,
                 labels: vec![String::from("blue3")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_4,
                 labels: vec![String::from("blue4")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_5,
                 labels: vec![String::from("blue5")],
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -881,6 +964,7 @@ mod in_memory_graph_tests {
                 src: uuid_2,
                 dst: uuid_4,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -889,6 +973,7 @@ mod in_memory_graph_tests {
                 src: uuid_3,
                 dst: uuid_2,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -899,6 +984,7 @@ mod in_memory_graph_tests {
                 src: uuid_1,
                 dst: uuid_5,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -907,6 +993,7 @@ mod in_memory_graph_tests {
                 src: uuid_1,
                 dst: uuid_1,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -915,6 +1002,7 @@ mod in_memory_graph_tests {
                 src: uuid_1,
                 dst: uuid_3,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -923,6 +1011,7 @@ mod in_memory_graph_tests {
                 src: uuid_1,
                 dst: uuid_4,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -931,6 +1020,7 @@ mod in_memory_graph_tests {
                 src: uuid_1,
                 dst: uuid_4,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -939,6 +1029,7 @@ mod in_memory_graph_tests {
                 src: uuid_1,
                 dst: uuid_2,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -947,6 +1038,7 @@ mod in_memory_graph_tests {
                 uuid_1,
                 Vec::new(),
                 Vec::new(),
+                Map::new(),
                 core_model::BondDirection::Both,
             )
             .unwrap();
@@ -972,30 +1064,35 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: uuid_1,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_2,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_3,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_4,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_5,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -1006,6 +1103,7 @@ mod in_memory_graph_tests {
                 src: uuid_1,
                 dst: uuid_5,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -1014,6 +1112,7 @@ mod in_memory_graph_tests {
                 src: uuid_1,
                 dst: uuid_3,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -1022,6 +1121,7 @@ mod in_memory_graph_tests {
                 src: uuid_1,
                 dst: uuid_4,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -1030,6 +1130,7 @@ mod in_memory_graph_tests {
                 src: uuid_1,
                 dst: uuid_2,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -1038,6 +1139,7 @@ mod in_memory_graph_tests {
                 uuid_1,
                 Vec::new(),
                 Vec::new(),
+                Map::new(),
                 core_model::BondDirection::Both,
             )
             .unwrap();
@@ -1062,30 +1164,35 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: uuid_1,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_2,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_3,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_4,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_5,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -1096,6 +1203,7 @@ mod in_memory_graph_tests {
                 src: uuid_2,
                 dst: uuid_1,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -1104,6 +1212,7 @@ mod in_memory_graph_tests {
                 src: uuid_3,
                 dst: uuid_1,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -1112,6 +1221,7 @@ mod in_memory_graph_tests {
                 src: uuid_4,
                 dst: uuid_1,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
@@ -1120,6 +1230,7 @@ mod in_memory_graph_tests {
                 src: uuid_5,
                 dst: uuid_1,
                 id: Uuid::new_v4(),
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -1128,6 +1239,7 @@ mod in_memory_graph_tests {
                 uuid_1,
                 Vec::new(),
                 Vec::new(),
+                Map::new(),
                 core_model::BondDirection::Both,
             )
             .unwrap();
@@ -1154,30 +1266,35 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: uuid_1,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_2,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_3,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_4,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_5,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -1203,30 +1320,35 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: uuid_1,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_2,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_3,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_4,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_5,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -1252,30 +1374,35 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: uuid_1,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_2,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_3,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_4,
                 labels: vec![String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_5,
                 labels: vec![String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -1299,30 +1426,35 @@ mod in_memory_graph_tests {
             .add_node(core_model::Node {
                 id: uuid_1,
                 labels: vec![String::from("one"), String::from("blue")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_2,
                 labels: vec![String::from("two"), String::from("green")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_3,
                 labels: vec![String::from("three"), String::from("yellow")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_4,
                 labels: vec![String::from("four"), String::from("brown")],
+                properties: Map::new(),
             })
             .unwrap();
         in_mem_graph
             .add_node(core_model::Node {
                 id: uuid_5,
                 labels: vec![String::from("five"), String::from("white")],
+                properties: Map::new(),
             })
             .unwrap();
 
@@ -1332,8 +1464,826 @@ mod in_memory_graph_tests {
             String::from("five"),
             String::from("white"),
         ];
-        let nodes_by_id_list = in_mem_graph.get_nodes_by_label_list(label_list);
+        let nodes_by_id_list = in_mem_graph.get_nodes_by_label_list(label_list, Map::new());
 
         assert_eq!(3, nodes_by_id_list.unwrap().len());
     }
+
+    fn properties(value: serde_json::Value) -> Map<String, serde_json::Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn add_node_with_properties_passed() {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+
+        let adding_result = in_mem_graph.add_node(core_model::Node {
+            id: Uuid::default(),
+            labels: vec![String::from("person")],
+            properties: properties(json!({"name": "Avtandil", "age": 33})),
+        });
+
+        assert!(adding_result.is_ok());
+        assert_eq!(
+            json!(33),
+            in_mem_graph.nodes_collection[0].properties["age"]
+        );
+    }
+
+    #[test]
+    fn add_node_blank_property_key_failed() {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+
+        let adding_result = in_mem_graph.add_node(core_model::Node {
+            id: Uuid::default(),
+            labels: vec![String::from("person")],
+            properties: properties(json!({" ": "Avtandil"})),
+        });
+
+        assert!(adding_result.is_err());
+        assert_eq!(0, in_mem_graph.get_nodes_collection_len());
+    }
+
+    #[test]
+    fn add_bond_null_property_failed() {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+
+        let uuid_1 = Uuid::parse_str("550e8400-e29b-41d4-a716-446655400001").unwrap();
+        let uuid_2 = Uuid::parse_str("550e8400-e29b-41d4-a716-446655400002").unwrap();
+
+        in_mem_graph
+            .add_node(core_model::Node {
+                id: uuid_1,
+                labels: vec![String::from("blue")],
+                properties: Map::new(),
+            })
+            .unwrap();
+        in_mem_graph
+            .add_node(core_model::Node {
+                id: uuid_2,
+                labels: vec![String::from("green")],
+                properties: Map::new(),
+            })
+            .unwrap();
+
+        let adding_result = in_mem_graph.add_bond(core_model::Bond {
+            label: String::from("blue-green"),
+            src: uuid_1,
+            dst: uuid_2,
+            id: Uuid::new_v4(),
+            properties: properties(json!({"since": null})),
+        });
+
+        assert!(adding_result.is_err());
+        assert_eq!(0, in_mem_graph.get_bonds_collection_len());
+    }
+
+    #[test]
+    fn get_connected_nodes_with_properties_passed() {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+
+        let uuid_1 = Uuid::parse_str("550e8400-e29b-41d4-a716-446655400001").unwrap();
+        let uuid_2 = Uuid::parse_str("550e8400-e29b-41d4-a716-446655400002").unwrap();
+        let uuid_3 = Uuid::parse_str("550e8400-e29b-41d4-a716-446655400003").unwrap();
+
+        in_mem_graph
+            .add_node(core_model::Node {
+                id: uuid_1,
+                labels: vec![String::from("person")],
+                properties: properties(json!({"city": "Tbilisi"})),
+            })
+            .unwrap();
+        in_mem_graph
+            .add_node(core_model::Node {
+                id: uuid_2,
+                labels: vec![String::from("person")],
+                properties: properties(json!({"city": "Tbilisi", "age": 20})),
+            })
+            .unwrap();
+        in_mem_graph
+            .add_node(core_model::Node {
+                id: uuid_3,
+                labels: vec![String::from("person")],
+                properties: properties(json!({"city": "Kutaisi"})),
+            })
+            .unwrap();
+
+        in_mem_graph
+            .add_bond(core_model::Bond {
+                label: String::from("knows"),
+                src: uuid_1,
+                dst: uuid_2,
+                id: Uuid::new_v4(),
+                properties: properties(json!({"since": 2010})),
+            })
+            .unwrap();
+        in_mem_graph
+            .add_bond(core_model::Bond {
+                label: String::from("knows"),
+                src: uuid_1,
+                dst: uuid_3,
+                id: Uuid::new_v4(),
+                properties: Map::new(),
+            })
+            .unwrap();
+
+        let connected_nodes_with_1 = in_mem_graph
+            .get_connected_nodes(
+                uuid_1,
+                Vec::new(),
+                Vec::new(),
+                properties(json!({"city": "Tbilisi"})),
+                core_model::BondDirection::Outgoing,
+            )
+            .unwrap();
+        let conn_nodes_ids_with_1: Vec<Uuid> =
+            connected_nodes_with_1.iter().map(|x| x.id).collect();
+
+        assert_eq!(vec![uuid_1, uuid_2], conn_nodes_ids_with_1);
+    }
+
+    #[test]
+    fn get_nodes_by_label_list_with_properties_passed() {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+
+        let uuid_1 = Uuid::parse_str("550e8400-e29b-41d4-a716-446655400001").unwrap();
+        let uuid_2 = Uuid::parse_str("550e8400-e29b-41d4-a716-446655400002").unwrap();
+        let uuid_3 = Uuid::parse_str("550e8400-e29b-41d4-a716-446655400003").unwrap();
+
+        in_mem_graph
+            .add_node(core_model::Node {
+                id: uuid_1,
+                labels: vec![String::from("person")],
+                properties: properties(json!({"age": 30, "active": true})),
+            })
+            .unwrap();
+        in_mem_graph
+            .add_node(core_model::Node {
+                id: uuid_2,
+                labels: vec![String::from("person")],
+                properties: properties(json!({"age": 30})),
+            })
+            .unwrap();
+        in_mem_graph
+            .add_node(core_model::Node {
+                id: uuid_3,
+                labels: vec![String::from("robot")],
+                properties: properties(json!({"age": 30, "active": true})),
+            })
+            .unwrap();
+
+        let nodes = in_mem_graph
+            .get_nodes_by_label_list(
+                vec![String::from("person")],
+                properties(json!({"age": 30, "active": true})),
+            )
+            .unwrap();
+
+        assert_eq!(1, nodes.len());
+        assert_eq!(uuid_1, nodes[0].id);
+    }
+
+    fn add_people(in_mem_graph: &mut core_model::InMemoryGraph) -> Vec<Uuid> {
+        let ages = vec![json!(25), json!(31), json!(40), json!("unknown"), json!(31)];
+        let mut ids = Vec::new();
+        for age in ages {
+            let id = Uuid::new_v4();
+            in_mem_graph
+                .add_node(core_model::Node {
+                    id,
+                    labels: vec![String::from("person")],
+                    properties: properties(json!({ "age": age })),
+                })
+                .unwrap();
+            ids.push(id);
+        }
+        in_mem_graph
+            .add_node(core_model::Node {
+                id: Uuid::new_v4(),
+                labels: vec![String::from("robot")],
+                properties: properties(json!({"age": 31})),
+            })
+            .unwrap();
+        ids
+    }
+
+    #[test]
+    fn create_property_index_passed() {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+        let ids = add_people(&mut in_mem_graph);
+
+        let creating_result =
+            in_mem_graph.create_property_index(String::from("person"), String::from("age"));
+        let nodes = in_mem_graph
+            .get_nodes_by_property_value("person", "age", json!(31))
+            .unwrap();
+        let mut node_ids: Vec<Uuid> = nodes.iter().map(|x| x.id).collect();
+        node_ids.sort();
+        let mut expected_ids = vec![ids[1], ids[4]];
+        expected_ids.sort();
+
+        assert!(creating_result.is_ok());
+        assert_eq!(1, in_mem_graph.get_property_indexes().len());
+        assert_eq!(expected_ids, node_ids);
+    }
+
+    #[test]
+    fn create_duplicate_property_index_failed() {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+
+        in_mem_graph
+            .create_property_index(String::from("person"), String::from("age"))
+            .unwrap();
+        let creating_result =
+            in_mem_graph.create_property_index(String::from("person"), String::from("age"));
+
+        assert!(creating_result.is_err());
+        assert_eq!(1, in_mem_graph.get_property_indexes().len());
+    }
+
+    #[test]
+    fn get_nodes_by_property_range_with_and_without_index_passed() {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+        let ids = add_people(&mut in_mem_graph);
+
+        let scanned: Vec<Uuid> = in_mem_graph
+            .get_nodes_by_property_range(
+                "person",
+                "age",
+                Bound::Excluded(json!(25)),
+                Bound::Unbounded,
+            )
+            .unwrap()
+            .iter()
+            .map(|x| x.id)
+            .collect();
+
+        in_mem_graph
+            .create_property_index(String::from("person"), String::from("age"))
+            .unwrap();
+        let indexed: Vec<Uuid> = in_mem_graph
+            .get_nodes_by_property_range(
+                "person",
+                "age",
+                Bound::Excluded(json!(25)),
+                Bound::Unbounded,
+            )
+            .unwrap()
+            .iter()
+            .map(|x| x.id)
+            .collect();
+
+        // "unknown" is a string and must not leak into numeric range
+        assert_eq!(3, indexed.len());
+        assert_eq!(ids[2], indexed[2]);
+        let mut scanned_sorted = scanned.clone();
+        scanned_sorted.sort();
+        let mut indexed_sorted = indexed.clone();
+        indexed_sorted.sort();
+        assert_eq!(scanned_sorted, indexed_sorted);
+    }
+
+    #[test]
+    fn property_index_follows_node_updates_passed() {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+        let ids = add_people(&mut in_mem_graph);
+        in_mem_graph
+            .create_property_index(String::from("person"), String::from("age"))
+            .unwrap();
+
+        in_mem_graph
+            .update_node_properties(ids[0], properties(json!({"age": 50})))
+            .unwrap();
+        let old_value_nodes = in_mem_graph
+            .get_nodes_by_property_value("person", "age", json!(25))
+            .unwrap();
+        let new_value_nodes = in_mem_graph
+            .get_nodes_by_property_value("person", "age", json!(50))
+            .unwrap();
+
+        assert_eq!(0, old_value_nodes.len());
+        assert_eq!(1, new_value_nodes.len());
+        assert_eq!(ids[0], new_value_nodes[0].id);
+    }
+
+    #[test]
+    fn get_nodes_by_label_list_uses_property_index_passed() {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+        let ids = add_people(&mut in_mem_graph);
+        in_mem_graph
+            .create_property_index(String::from("person"), String::from("age"))
+            .unwrap();
+
+        let nodes = in_mem_graph
+            .get_nodes_by_label_list(vec![String::from("person")], properties(json!({"age": 31})))
+            .unwrap();
+        let node_ids: Vec<Uuid> = nodes.iter().map(|x| x.id).collect();
+
+        assert_eq!(vec![ids[1], ids[4]], node_ids);
+    }
+
+    #[test]
+    fn drop_property_index_passed() {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+        add_people(&mut in_mem_graph);
+        in_mem_graph
+            .create_property_index(String::from("person"), String::from("age"))
+            .unwrap();
+
+        let dropping_result = in_mem_graph.drop_property_index("person", "age");
+        let second_dropping_result = in_mem_graph.drop_property_index("person", "age");
+        let nodes = in_mem_graph
+            .get_nodes_by_property_value("person", "age", json!(31))
+            .unwrap();
+
+        assert!(dropping_result.is_ok());
+        assert!(second_dropping_result.is_err());
+        assert_eq!(0, in_mem_graph.get_property_indexes().len());
+        assert_eq!(2, nodes.len());
+    }
+
+    #[test]
+    fn labels_index_follows_add_node_passed() {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+
+        in_mem_graph
+            .add_node(core_model::Node {
+                id: Uuid::new_v4(),
+                labels: vec![String::from("one"), String::from("blue")],
+                properties: Map::new(),
+            })
+            .unwrap();
+        in_mem_graph
+            .add_node(core_model::Node {
+                id: Uuid::new_v4(),
+                labels: vec![String::from("two"), String::from("blue")],
+                properties: Map::new(),
+            })
+            .unwrap();
+
+        let blue_positions: Vec<usize> =
+            in_mem_graph.labels_index["blue"].iter().copied().collect();
+        let one_positions: Vec<usize> = in_mem_graph.labels_index["one"].iter().copied().collect();
+        let missing = in_mem_graph
+            .get_nodes_by_label_list(vec![String::from("green")], Map::new())
+            .unwrap();
+
+        assert_eq!(vec![0, 1], blue_positions);
+        assert_eq!(vec![0], one_positions);
+        assert_eq!(0, missing.len());
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn get_nodes_by_label_list_million_nodes_benchmark() {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+        for i in 0..1_000_000 {
+            in_mem_graph
+                .add_node(core_model::Node {
+                    id: Uuid::new_v4(),
+                    labels: vec![format!("label_{}", i % 1000)],
+                    properties: Map::new(),
+                })
+                .unwrap();
+        }
+        let label_list = vec![String::from("label_7"), String::from("label_42")];
+
+        let scan_start = std::time::Instant::now();
+        let scanned: Vec<&core_model::Node> = in_mem_graph
+            .nodes_collection
+            .iter()
+            .filter(|x| label_list.iter().any(|l| x.labels.contains(l)))
+            .collect();
+        let scan_elapsed = scan_start.elapsed();
+
+        let index_start = std::time::Instant::now();
+        let indexed = in_mem_graph
+            .get_nodes_by_label_list(label_list.clone(), Map::new())
+            .unwrap();
+        let index_elapsed = index_start.elapsed();
+
+        println!(
+            "1M nodes, {} results: full scan {:?}, label index {:?}",
+            indexed.len(),
+            scan_elapsed,
+            index_elapsed
+        );
+        assert_eq!(2000, indexed.len());
+        assert_eq!(scanned.len(), indexed.len());
+        assert!(index_elapsed < scan_elapsed);
+    }
+
+    #[test]
+    fn adjacency_follows_add_bond_and_rebuild_passed() {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+
+        let uuid_1 = Uuid::parse_str("550e8400-e29b-41d4-a716-446655400001").unwrap();
+        let uuid_2 = Uuid::parse_str("550e8400-e29b-41d4-a716-446655400002").unwrap();
+        let uuid_3 = Uuid::parse_str("550e8400-e29b-41d4-a716-446655400003").unwrap();
+
+        for id in [uuid_1, uuid_2, uuid_3] {
+            in_mem_graph
+                .add_node(core_model::Node {
+                    id,
+                    labels: vec![String::from("blue")],
+                    properties: Map::new(),
+                })
+                .unwrap();
+        }
+        for (src, dst) in [(uuid_1, uuid_2), (uuid_1, uuid_3), (uuid_3, uuid_1)] {
+            in_mem_graph
+                .add_bond(core_model::Bond {
+                    label: String::from("blue-blue"),
+                    src,
+                    dst,
+                    id: Uuid::new_v4(),
+                    properties: Map::new(),
+                })
+                .unwrap();
+        }
+
+        let outgoing: Vec<Uuid> = in_mem_graph
+            .get_outgoing_bonds(uuid_1)
+            .iter()
+            .map(|x| x.dst)
+            .collect();
+        let incoming: Vec<Uuid> = in_mem_graph
+            .get_incoming_bonds(uuid_1)
+            .iter()
+            .map(|x| x.src)
+            .collect();
+        assert_eq!(vec![uuid_2, uuid_3], outgoing);
+        assert_eq!(vec![uuid_3], incoming);
+        assert_eq!(0, in_mem_graph.get_outgoing_bonds(uuid_2).len());
+
+        in_mem_graph.outgoing_bonds.clear();
+        in_mem_graph.incoming_bonds.clear();
+        in_mem_graph.rebuild_indexes();
+
+        assert_eq!(2, in_mem_graph.get_outgoing_bonds(uuid_1).len());
+        assert_eq!(1, in_mem_graph.get_incoming_bonds(uuid_1).len());
+    }
+
+    /// 1 -> 2 -> 3 -> 4, 1 -> 5 (red), 5 -> 4, 6 -> 1
+    fn build_chain_graph() -> (core_model::InMemoryGraph, Vec<Uuid>) {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+        let ids: Vec<Uuid> = (1..=6)
+            .map(|i| Uuid::parse_str(&format!("550e8400-e29b-41d4-a716-44665540000{}", i)).unwrap())
+            .collect();
+
+        for (i, id) in ids.iter().enumerate() {
+            let label = if i == 4 { "red" } else { "blue" };
+            in_mem_graph
+                .add_node(core_model::Node {
+                    id: *id,
+                    labels: vec![String::from(label)],
+                    properties: Map::new(),
+                })
+                .unwrap();
+        }
+        let bonds = vec![
+            (0, 1, "next"),
+            (1, 2, "next"),
+            (2, 3, "next"),
+            (0, 4, "side"),
+            (4, 3, "side"),
+            (5, 0, "next"),
+        ];
+        for (src, dst, label) in bonds {
+            in_mem_graph
+                .add_bond(core_model::Bond {
+                    label: String::from(label),
+                    src: ids[src],
+                    dst: ids[dst],
+                    id: Uuid::new_v4(),
+                    properties: Map::new(),
+                })
+                .unwrap();
+        }
+        (in_mem_graph, ids)
+    }
+
+    #[test]
+    fn get_connected_nodes_by_depth_passed() {
+        let (in_mem_graph, ids) = build_chain_graph();
+
+        let nodes = in_mem_graph
+            .get_connected_nodes_by_depth(
+                ids[0],
+                2,
+                Vec::new(),
+                Vec::new(),
+                Map::new(),
+                core_model::BondDirection::Outgoing,
+            )
+            .unwrap();
+        let nodes_with_depth: Vec<(Uuid, u32)> = nodes.iter().map(|(x, d)| (x.id, *d)).collect();
+
+        assert_eq!(
+            vec![
+                (ids[0], 0),
+                (ids[1], 1),
+                (ids[4], 1),
+                (ids[2], 2),
+                (ids[3], 2)
+            ],
+            nodes_with_depth
+        );
+    }
+
+    #[test]
+    fn get_connected_nodes_by_depth_with_filters_passed() {
+        let (in_mem_graph, ids) = build_chain_graph();
+
+        let by_bond_type = in_mem_graph
+            .get_connected_nodes_by_depth(
+                ids[0],
+                3,
+                vec![String::from("next")],
+                Vec::new(),
+                Map::new(),
+                core_model::BondDirection::Outgoing,
+            )
+            .unwrap();
+        let by_label = in_mem_graph
+            .get_connected_nodes_by_depth(
+                ids[0],
+                10,
+                Vec::new(),
+                vec![String::from("red")],
+                Map::new(),
+                core_model::BondDirection::Outgoing,
+            )
+            .unwrap();
+        let both_directions = in_mem_graph
+            .get_connected_nodes_by_depth(
+                ids[0],
+                1,
+                Vec::new(),
+                Vec::new(),
+                Map::new(),
+                core_model::BondDirection::Both,
+            )
+            .unwrap();
+
+        let by_bond_type_ids: Vec<Uuid> = by_bond_type.iter().map(|(x, _)| x.id).collect();
+        let by_label_ids: Vec<Uuid> = by_label.iter().map(|(x, _)| x.id).collect();
+        assert_eq!(vec![ids[0], ids[1], ids[2], ids[3]], by_bond_type_ids);
+        assert_eq!(vec![ids[0], ids[4]], by_label_ids);
+        assert_eq!(4, both_directions.len());
+        assert_eq!((ids[5], 1), (both_directions[3].0.id, both_directions[3].1));
+    }
+
+    #[test]
+    fn get_connected_nodes_by_depth_missing_node_failed() {
+        let (in_mem_graph, _) = build_chain_graph();
+
+        let result = in_mem_graph.get_connected_nodes_by_depth(
+            Uuid::new_v4(),
+            2,
+            Vec::new(),
+            Vec::new(),
+            Map::new(),
+            core_model::BondDirection::Both,
+        );
+
+        assert!(result.is_err());
+    }
+
+    /// Checks incrementally maintained indexes against ones rebuilt from scratch
+    fn assert_indexes_consistent(in_mem_graph: &core_model::InMemoryGraph) {
+        let json = serde_json::to_string(in_mem_graph).unwrap();
+        let mut rebuilt: core_model::InMemoryGraph = serde_json::from_str(&json).unwrap();
+        rebuilt.rebuild_indexes();
+
+        let sorted = |adjacency: &std::collections::BTreeMap<Uuid, Vec<Uuid>>| {
+            adjacency
+                .iter()
+                .map(|(k, v)| {
+                    let mut v = v.clone();
+                    v.sort();
+                    (*k, v)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rebuilt.nodes_id_index, in_mem_graph.nodes_id_index);
+        assert_eq!(rebuilt.bonds_id_index, in_mem_graph.bonds_id_index);
+        assert_eq!(rebuilt.labels_index, in_mem_graph.labels_index);
+        assert_eq!(
+            sorted(&rebuilt.outgoing_bonds),
+            sorted(&in_mem_graph.outgoing_bonds)
+        );
+        assert_eq!(
+            sorted(&rebuilt.incoming_bonds),
+            sorted(&in_mem_graph.incoming_bonds)
+        );
+    }
+
+    #[test]
+    fn delete_bond_passed() {
+        let (mut in_mem_graph, ids) = build_chain_graph();
+        let bond_id = in_mem_graph.get_outgoing_bonds(ids[0])[0].id;
+
+        let deleted = in_mem_graph.delete_bond(bond_id).unwrap();
+
+        assert_eq!(bond_id, deleted.id);
+        assert_eq!(5, in_mem_graph.get_bonds_collection_len());
+        assert!(!in_mem_graph.bonds_id_index.contains_key(&bond_id));
+        assert_eq!(1, in_mem_graph.get_outgoing_bonds(ids[0]).len());
+        assert_eq!(0, in_mem_graph.get_incoming_bonds(ids[1]).len());
+        assert_indexes_consistent(&in_mem_graph);
+        assert!(in_mem_graph.delete_bond(bond_id).is_err());
+    }
+
+    #[test]
+    fn delete_node_with_bonds_failed() {
+        let (mut in_mem_graph, ids) = build_chain_graph();
+
+        let deleting_result = in_mem_graph.delete_node(ids[1], false);
+
+        assert!(deleting_result.is_err());
+        assert_eq!(6, in_mem_graph.get_nodes_collection_len());
+        assert_eq!(6, in_mem_graph.get_bonds_collection_len());
+        assert!(in_mem_graph.delete_node(Uuid::new_v4(), true).is_err());
+    }
+
+    #[test]
+    fn delete_node_detach_passed() {
+        let (mut in_mem_graph, ids) = build_chain_graph();
+        in_mem_graph
+            .create_property_index(String::from("blue"), String::from("age"))
+            .unwrap();
+        in_mem_graph
+            .update_node_properties(ids[0], properties(json!({"age": 30})))
+            .unwrap();
+        // self loop is incident twice but deleted once
+        in_mem_graph
+            .add_bond(core_model::Bond {
+                label: String::from("self"),
+                src: ids[0],
+                dst: ids[0],
+                id: Uuid::new_v4(),
+                properties: Map::new(),
+            })
+            .unwrap();
+
+        let deleted = in_mem_graph.delete_node(ids[0], true).unwrap();
+
+        assert_eq!(ids[0], deleted.id);
+        assert_eq!(5, in_mem_graph.get_nodes_collection_len());
+        // only 2 -> 3, 3 -> 4 and 5 -> 4 are left
+        assert_eq!(3, in_mem_graph.get_bonds_collection_len());
+        assert_eq!(0, in_mem_graph.get_incoming_bonds(ids[1]).len());
+        assert_eq!(0, in_mem_graph.get_outgoing_bonds(ids[5]).len());
+        // last node was moved into slot of deleted one
+        assert_eq!(0, *in_mem_graph.nodes_id_index.get(&ids[5]).unwrap());
+        assert_eq!(
+            0,
+            in_mem_graph
+                .get_nodes_by_property_value("blue", "age", json!(30))
+                .unwrap()
+                .len()
+        );
+        let blue_nodes = in_mem_graph
+            .get_nodes_by_label_list(vec![String::from("blue")], Map::new())
+            .unwrap();
+        assert_eq!(4, blue_nodes.len());
+        assert!(blue_nodes.iter().all(|x| x.id != ids[0]));
+        assert_indexes_consistent(&in_mem_graph);
+    }
+
+    #[test]
+    fn delete_all_nodes_passed() {
+        let (mut in_mem_graph, ids) = build_chain_graph();
+
+        for id in ids.iter().rev() {
+            in_mem_graph.delete_node(*id, true).unwrap();
+            assert_indexes_consistent(&in_mem_graph);
+        }
+
+        assert_eq!(0, in_mem_graph.get_nodes_collection_len());
+        assert_eq!(0, in_mem_graph.get_bonds_collection_len());
+        assert!(in_mem_graph.labels_index.is_empty());
+        assert!(in_mem_graph.outgoing_bonds.is_empty());
+        assert!(in_mem_graph.incoming_bonds.is_empty());
+    }
+
+    #[test]
+    fn graph_locks_are_independent_passed() {
+        let graph_collection_fac = initialize_graph_collection();
+        for name in ["first", "second"] {
+            graph_collection_fac
+                .add_graph(core_model::InMemoryGraph::new_graph(String::from(name)))
+                .unwrap();
+        }
+
+        let first_graph = graph_collection_fac.get_graph("first").unwrap();
+        let _first_guard = first_graph.write().unwrap();
+        let adding_result = graph_collection_fac.add_node(
+            "second",
+            core_model::Node {
+                id: Uuid::default(),
+                labels: vec![String::from("blue")],
+                properties: Map::new(),
+            },
+        );
+
+        assert!(adding_result.is_ok());
+        assert!(graph_collection_fac.contains_graph("second"));
+        assert!(graph_collection_fac.get_graph("third").is_none());
+    }
+
+    #[test]
+    fn graph_errors_are_typed_passed() {
+        let (mut in_mem_graph, ids) = build_chain_graph();
+        let missing_id = Uuid::new_v4();
+
+        assert_eq!(
+            core_model::GraphError::DuplicateNodeId(ids[0]),
+            in_mem_graph
+                .add_node(core_model::Node {
+                    id: ids[0],
+                    labels: vec![String::from("blue")],
+                    properties: Map::new(),
+                })
+                .unwrap_err()
+        );
+        assert_eq!(
+            core_model::GraphError::InvalidLabel,
+            in_mem_graph
+                .add_node(core_model::Node {
+                    id: Uuid::default(),
+                    labels: vec![],
+                    properties: Map::new(),
+                })
+                .unwrap_err()
+        );
+        assert_eq!(
+            core_model::GraphError::DanglingBondEndpoint(missing_id),
+            in_mem_graph
+                .add_bond(core_model::Bond {
+                    label: String::from("next"),
+                    src: ids[0],
+                    dst: missing_id,
+                    id: Uuid::default(),
+                    properties: Map::new(),
+                })
+                .unwrap_err()
+        );
+        assert_eq!(
+            core_model::GraphError::InvalidProperty(String::from("size")),
+            in_mem_graph
+                .update_node_properties(ids[0], properties(json!({"size": null})))
+                .unwrap_err()
+        );
+        assert_eq!(
+            core_model::GraphError::NodeHasBonds(ids[0]),
+            in_mem_graph.delete_node(ids[0], false).unwrap_err()
+        );
+        assert_eq!(
+            core_model::GraphError::NodeNotFound(missing_id),
+            in_mem_graph.delete_node(missing_id, true).unwrap_err()
+        );
+        assert_eq!(
+            core_model::GraphError::PropertyIndexNotFound {
+                label: String::from("blue"),
+                property: String::from("size"),
+            },
+            in_mem_graph
+                .drop_property_index("blue", "size")
+                .unwrap_err()
+        );
+        assert_eq!(
+            "NODE_HAS_BONDS",
+            core_model::GraphError::NodeHasBonds(ids[0]).code()
+        );
+    }
+
+    #[test]
+    fn facade_errors_are_typed_passed() {
+        let graph_collection_fac = initialize_graph_collection();
+        graph_collection_fac
+            .add_graph(core_model::InMemoryGraph::new_graph(String::from("some")))
+            .unwrap();
+
+        assert_eq!(
+            core_model::GraphError::DuplicateGraphName(String::from("some")),
+            graph_collection_fac
+                .add_graph(core_model::InMemoryGraph::new_graph(String::from("some")))
+                .unwrap_err()
+        );
+        assert_eq!(
+            core_model::GraphError::InvalidGraphName,
+            graph_collection_fac
+                .add_graph(core_model::InMemoryGraph::new_graph(String::from(" ")))
+                .unwrap_err()
+        );
+        assert_eq!(
+            core_model::GraphError::GraphNotFound(String::from("other")),
+            graph_collection_fac
+                .delete_bond("other", Uuid::new_v4())
+                .unwrap_err()
+        );
+    }
 }
@@ -0,0 +1,446 @@
+use crate::core_model::{BondDirection, GraphCollectionFacade, GraphError, InMemoryGraph};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashSet, VecDeque};
+use uuid::Uuid;
+
+pub const DEFAULT_DAMPING_FACTOR: f64 = 0.85;
+pub const DEFAULT_PAGERANK_ITERATIONS: usize = 20;
+/// PageRank stops early once sum of rank changes of an iteration is below it
+pub const DEFAULT_PAGERANK_TOLERANCE: f64 = 1e-6;
+
+/// Algorithm and its parameters, `algorithm` names it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "snake_case")]
+pub enum Algorithm {
+    /// Share of total rank per node, ranks sum to 1
+    #[serde(rename = "pagerank")]
+    PageRank {
+        damping_factor: Option<f64>,
+        max_iterations: Option<usize>,
+        tolerance: Option<f64>,
+    },
+    /// Component number per node, bond direction ignored
+    WeaklyConnectedComponents,
+    /// Component number per node, nodes of component reach each other along bonds
+    StronglyConnectedComponents,
+    /// Number of bonds per node, both directions by default
+    DegreeCentrality { direction: Option<BondDirection> },
+    /// Number of shortest paths between other nodes going through node (Brandes)
+    BetweennessCentrality {
+        /// Ignore bond direction
+        #[serde(default)]
+        undirected: bool,
+        /// Divide by number of node pairs not involving node
+        #[serde(default)]
+        normalized: bool,
+    },
+    /// Number of triangles per node, bond direction ignored
+    TriangleCount,
+}
+
+/// Body of POST /graphs/{name}/algorithms
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AlgorithmDTO {
+    #[serde(flatten)]
+    pub algorithm: Algorithm,
+    /// Only bonds of these labels are followed, all if not set
+    pub bond_types: Option<Vec<String>>,
+    /// Stores result of every node in this property too
+    pub write_property: Option<String>,
+}
+
+/// Result of algorithm for one node
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct NodeValueDTO {
+    pub id: Uuid,
+    pub value: Value,
+}
+
+/// Results in node order of graph
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct AlgorithmResultDTO {
+    pub results: Vec<NodeValueDTO>,
+}
+
+/// Graph as adjacency lists over node positions in nodes_collection
+struct Projection {
+    outgoing: Vec<Vec<usize>>,
+    incoming: Vec<Vec<usize>>,
+}
+
+impl Projection {
+    fn new(graph: &InMemoryGraph, bond_types: Option<&[String]>) -> Self {
+        let n = graph.nodes_collection.len();
+        let mut outgoing = vec![Vec::new(); n];
+        let mut incoming = vec![Vec::new(); n];
+        for bond in &graph.bonds_collection {
+            if bond_types.is_some_and(|x| !x.contains(&bond.label)) {
+                continue;
+            }
+            let src = graph.nodes_id_index[&bond.src];
+            let dst = graph.nodes_id_index[&bond.dst];
+            outgoing[src].push(dst);
+            incoming[dst].push(src);
+        }
+        Projection { outgoing, incoming }
+    }
+
+    fn len(&self) -> usize {
+        self.outgoing.len()
+    }
+
+    /// Distinct neighbours in any direction, without node itself
+    fn undirected_neighbours(&self) -> Vec<Vec<usize>> {
+        (0..self.len())
+            .map(|i| {
+                let mut neighbours: Vec<usize> = self.outgoing[i]
+                    .iter()
+                    .chain(self.incoming[i].iter())
+                    .copied()
+                    .filter(|x| *x != i)
+                    .collect();
+                neighbours.sort_unstable();
+                neighbours.dedup();
+                neighbours
+            })
+            .collect()
+    }
+}
+
+impl InMemoryGraph {
+    /// Runs algorithm, returning value of every node in node order of graph
+    pub fn run_algorithm(
+        &self,
+        algorithm: &Algorithm,
+        bond_types: Option<&[String]>,
+    ) -> Result<Vec<Value>, GraphError> {
+        let projection = Projection::new(self, bond_types);
+        let values = match algorithm {
+            Algorithm::PageRank {
+                damping_factor,
+                max_iterations,
+                tolerance,
+            } => {
+                let damping_factor = damping_factor.unwrap_or(DEFAULT_DAMPING_FACTOR);
+                if !(0.0..1.0).contains(&damping_factor) {
+                    return Err(GraphError::InvalidQuery(String::from(
+                        "damping_factor must be in [0, 1)",
+                    )));
+                }
+                page_rank(
+                    &projection,
+                    damping_factor,
+                    max_iterations.unwrap_or(DEFAULT_PAGERANK_ITERATIONS),
+                    tolerance.unwrap_or(DEFAULT_PAGERANK_TOLERANCE),
+                )
+                .into_iter()
+                .map(Value::from)
+                .collect()
+            }
+            Algorithm::WeaklyConnectedComponents => weakly_connected_components(&projection)
+                .into_iter()
+                .map(Value::from)
+                .collect(),
+            Algorithm::StronglyConnectedComponents => strongly_connected_components(&projection)
+                .into_iter()
+                .map(Value::from)
+                .collect(),
+            Algorithm::DegreeCentrality { direction } => {
+                let direction = direction.unwrap_or(BondDirection::Both);
+                (0..projection.len())
+                    .map(|i| {
+                        let degree = match direction {
+                            BondDirection::Outgoing => projection.outgoing[i].len(),
+                            BondDirection::Ingoing => projection.incoming[i].len(),
+                            BondDirection::Both => {
+                                projection.outgoing[i].len() + projection.incoming[i].len()
+                            }
+                        };
+                        Value::from(degree)
+                    })
+                    .collect()
+            }
+            Algorithm::BetweennessCentrality {
+                undirected,
+                normalized,
+            } => betweenness_centrality(&projection, *undirected, *normalized)
+                .into_iter()
+                .map(Value::from)
+                .collect(),
+            Algorithm::TriangleCount => triangle_count(&projection)
+                .into_iter()
+                .map(Value::from)
+                .collect(),
+        };
+        Ok(values)
+    }
+}
+
+impl GraphCollectionFacade {
+    /// Runs algorithm over graph by name, with write_property set stores every
+    /// result in node properties as one transaction
+    pub fn run_algorithm(
+        &self,
+        graph_name: &str,
+        dto: &AlgorithmDTO,
+    ) -> Result<AlgorithmResultDTO, GraphError> {
+        let bond_types = dto.bond_types.as_deref();
+        let write_property = match &dto.write_property {
+            None => {
+                let graph_lock = match self.get_graph(graph_name) {
+                    None => return Err(GraphError::GraphNotFound(graph_name.to_string())),
+                    Some(g) => g,
+                };
+                let graph = graph_lock.read().unwrap();
+                let values = graph.run_algorithm(&dto.algorithm, bond_types)?;
+                return Ok(results(&graph, values));
+            }
+            Some(p) if p.trim().is_empty() => return Err(GraphError::InvalidProperty(p.clone())),
+            Some(p) => p,
+        };
+
+        self.run_transaction(graph_name, |transaction| {
+            let values = transaction
+                .graph()
+                .run_algorithm(&dto.algorithm, bond_types)?;
+            let result = results(transaction.graph(), values);
+            for x in &result.results {
+                let mut properties = transaction.graph().nodes_collection
+                    [transaction.graph().nodes_id_index[&x.id]]
+                    .properties
+                    .clone();
+                properties.insert(write_property.clone(), x.value.clone());
+                transaction.update_node_properties(x.id, properties)?;
+            }
+            Ok(result)
+        })
+    }
+}
+
+fn results(graph: &InMemoryGraph, values: Vec<Value>) -> AlgorithmResultDTO {
+    AlgorithmResultDTO {
+        results: graph
+            .nodes_collection
+            .iter()
+            .zip(values)
+            .map(|(node, value)| NodeValueDTO { id: node.id, value })
+            .collect(),
+    }
+}
+
+/// Power iteration, rank of nodes without outgoing bonds is spread over all nodes
+fn page_rank(
+    projection: &Projection,
+    damping_factor: f64,
+    max_iterations: usize,
+    tolerance: f64,
+) -> Vec<f64> {
+    let n = projection.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut ranks = vec![1.0 / n as f64; n];
+    for _ in 0..max_iterations {
+        let dangling: f64 = (0..n)
+            .filter(|i| projection.outgoing[*i].is_empty())
+            .map(|i| ranks[i])
+            .sum();
+        let base = (1.0 - damping_factor + damping_factor * dangling) / n as f64;
+        let mut next = vec![base; n];
+        for (src, targets) in projection.outgoing.iter().enumerate() {
+            let share = damping_factor * ranks[src] / targets.len() as f64;
+            for dst in targets {
+                next[*dst] += share;
+            }
+        }
+        let change: f64 = ranks.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+        ranks = next;
+        if change < tolerance {
+            break;
+        }
+    }
+    ranks
+}
+
+/// Component numbers, numbered in order of first node of component
+fn weakly_connected_components(projection: &Projection) -> Vec<usize> {
+    let n = projection.len();
+    let mut components = vec![usize::MAX; n];
+    let mut count = 0;
+    for start in 0..n {
+        if components[start] != usize::MAX {
+            continue;
+        }
+        components[start] = count;
+        let mut queue = VecDeque::from(vec![start]);
+        while let Some(curr) = queue.pop_front() {
+            for next in projection.outgoing[curr]
+                .iter()
+                .chain(projection.incoming[curr].iter())
+            {
+                if components[*next] == usize::MAX {
+                    components[*next] = count;
+                    queue.push_back(*next);
+                }
+            }
+        }
+        count += 1;
+    }
+    components
+}
+
+/// Tarjan without recursion, so long chains do not overflow the stack.
+/// Component numbers are renumbered in order of first node of component.
+fn strongly_connected_components(projection: &Projection) -> Vec<usize> {
+    let n = projection.len();
+    let mut index = vec![usize::MAX; n];
+    let mut low_link = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut found = vec![usize::MAX; n];
+    let mut found_count = 0;
+    let mut next_index = 0;
+
+    for start in 0..n {
+        if index[start] != usize::MAX {
+            continue;
+        }
+        // (node, position of next bond to visit)
+        let mut call_stack = vec![(start, 0)];
+        index[start] = next_index;
+        low_link[start] = next_index;
+        next_index += 1;
+        stack.push(start);
+        on_stack[start] = true;
+
+        while let Some((curr, position)) = call_stack.last_mut() {
+            let curr = *curr;
+            if let Some(next) = projection.outgoing[curr].get(*position).copied() {
+                *position += 1;
+                if index[next] == usize::MAX {
+                    index[next] = next_index;
+                    low_link[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    call_stack.push((next, 0));
+                } else if on_stack[next] {
+                    low_link[curr] = low_link[curr].min(index[next]);
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            if let Some((parent, _)) = call_stack.last() {
+                low_link[*parent] = low_link[*parent].min(low_link[curr]);
+            }
+            if low_link[curr] == index[curr] {
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    found[member] = found_count;
+                    if member == curr {
+                        break;
+                    }
+                }
+                found_count += 1;
+            }
+        }
+    }
+
+    let mut renumbered = vec![usize::MAX; found_count];
+    let mut count = 0;
+    found
+        .into_iter()
+        .map(|x| {
+            if renumbered[x] == usize::MAX {
+                renumbered[x] = count;
+                count += 1;
+            }
+            renumbered[x]
+        })
+        .collect()
+}
+
+/// Brandes algorithm over unweighted bonds, parallel bonds count as one
+fn betweenness_centrality(projection: &Projection, undirected: bool, normalized: bool) -> Vec<f64> {
+    let n = projection.len();
+    let neighbours: Vec<Vec<usize>> = if undirected {
+        projection.undirected_neighbours()
+    } else {
+        projection
+            .outgoing
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let mut targets: Vec<usize> = x.iter().copied().filter(|x| *x != i).collect();
+                targets.sort_unstable();
+                targets.dedup();
+                targets
+            })
+            .collect()
+    };
+
+    let mut centrality = vec![0.0; n];
+    for source in 0..n {
+        let mut order = Vec::with_capacity(n);
+        let mut predecessors = vec![Vec::new(); n];
+        let mut paths = vec![0.0; n];
+        let mut distance = vec![usize::MAX; n];
+        paths[source] = 1.0;
+        distance[source] = 0;
+        let mut queue = VecDeque::from(vec![source]);
+        while let Some(curr) = queue.pop_front() {
+            order.push(curr);
+            for next in &neighbours[curr] {
+                if distance[*next] == usize::MAX {
+                    distance[*next] = distance[curr] + 1;
+                    queue.push_back(*next);
+                }
+                if distance[*next] == distance[curr] + 1 {
+                    paths[*next] += paths[curr];
+                    predecessors[*next].push(curr);
+                }
+            }
+        }
+
+        let mut dependency = vec![0.0; n];
+        for curr in order.into_iter().rev() {
+            for prev in &predecessors[curr] {
+                dependency[*prev] += paths[*prev] / paths[curr] * (1.0 + dependency[curr]);
+            }
+            if curr != source {
+                centrality[curr] += dependency[curr];
+            }
+        }
+    }
+
+    // every undirected path was counted from both ends
+    let mut scale = if undirected { 0.5 } else { 1.0 };
+    if normalized && n > 2 {
+        scale *= if undirected { 2.0 } else { 1.0 } / ((n - 1) * (n - 2)) as f64;
+    }
+    centrality.into_iter().map(|x| x * scale).collect()
+}
+
+/// Counts every triangle once per corner, over sorted neighbour lists
+fn triangle_count(projection: &Projection) -> Vec<usize> {
+    let neighbours = projection.undirected_neighbours();
+    let sets: Vec<HashSet<usize>> = neighbours
+        .iter()
+        .map(|x| x.iter().copied().collect())
+        .collect();
+    let mut triangles = vec![0; neighbours.len()];
+    for (a, a_neighbours) in neighbours.iter().enumerate() {
+        for b in a_neighbours.iter().filter(|b| **b > a) {
+            for c in neighbours[*b].iter().filter(|c| **c > *b) {
+                if sets[a].contains(c) {
+                    triangles[a] += 1;
+                    triangles[*b] += 1;
+                    triangles[*c] += 1;
+                }
+            }
+        }
+    }
+    triangles
+}
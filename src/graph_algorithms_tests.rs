@@ -0,0 +1,255 @@
+#[cfg(test)]
+mod algorithm_tests {
+    use crate::core_model::{Bond, BondDirection, GraphCollectionFacade, InMemoryGraph, Node};
+    use crate::graph_algorithms::{Algorithm, AlgorithmDTO};
+    use serde_json::{json, Map, Value};
+    use uuid::Uuid;
+
+    fn add_node(graph: &mut InMemoryGraph) -> Uuid {
+        let id = Uuid::new_v4();
+        graph
+            .add_node(Node {
+                id,
+                labels: vec![String::from("Person")],
+                properties: Map::new(),
+            })
+            .unwrap();
+        id
+    }
+
+    fn add_bond(graph: &mut InMemoryGraph, label: &str, src: Uuid, dst: Uuid) {
+        graph
+            .add_bond(Bond {
+                id: Uuid::new_v4(),
+                label: String::from(label),
+                src,
+                dst,
+                properties: Map::new(),
+            })
+            .unwrap();
+    }
+
+    /// Graph of `n` nodes with KNOWS bonds between given node positions
+    fn initialize_graph(n: usize, bonds: &[(usize, usize)]) -> (InMemoryGraph, Vec<Uuid>) {
+        let mut graph = InMemoryGraph::new_graph(String::from("MyGraph"));
+        let ids: Vec<Uuid> = (0..n).map(|_| add_node(&mut graph)).collect();
+        for (src, dst) in bonds {
+            add_bond(&mut graph, "KNOWS", ids[*src], ids[*dst]);
+        }
+        (graph, ids)
+    }
+
+    fn as_f64(values: Vec<Value>) -> Vec<f64> {
+        values.iter().map(|x| x.as_f64().unwrap()).collect()
+    }
+
+    fn assert_close(expected: &[f64], actual: &[f64]) {
+        assert_eq!(expected.len(), actual.len());
+        for (e, a) in expected.iter().zip(actual) {
+            assert!(
+                (e - a).abs() < 1e-4,
+                "expected {:?}, got {:?}",
+                expected,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn page_rank_passed() {
+        // 0 and 1 point to 2, 2 points back to 0, 3 has no bonds
+        let (graph, _) = initialize_graph(4, &[(0, 2), (1, 2), (2, 0)]);
+        let algorithm = Algorithm::PageRank {
+            damping_factor: None,
+            max_iterations: Some(100),
+            tolerance: None,
+        };
+
+        let ranks = as_f64(graph.run_algorithm(&algorithm, None).unwrap());
+
+        assert!((ranks.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        assert!(ranks[2] > ranks[0]);
+        assert!(ranks[0] > ranks[1]);
+        assert_close(&[ranks[1]], &[ranks[3]]);
+    }
+
+    #[test]
+    fn page_rank_invalid_damping_failed() {
+        let (graph, _) = initialize_graph(2, &[(0, 1)]);
+        let algorithm = Algorithm::PageRank {
+            damping_factor: Some(1.5),
+            max_iterations: None,
+            tolerance: None,
+        };
+
+        assert!(graph.run_algorithm(&algorithm, None).is_err());
+    }
+
+    #[test]
+    fn connected_components_passed() {
+        // cycle 0 -> 1 -> 2 -> 0, 2 -> 3, separate 4 -> 5
+        let (graph, _) = initialize_graph(6, &[(0, 1), (1, 2), (2, 0), (2, 3), (4, 5)]);
+
+        let weak = graph
+            .run_algorithm(&Algorithm::WeaklyConnectedComponents, None)
+            .unwrap();
+        let strong = graph
+            .run_algorithm(&Algorithm::StronglyConnectedComponents, None)
+            .unwrap();
+
+        assert_eq!(
+            vec![json!(0), json!(0), json!(0), json!(0), json!(1), json!(1)],
+            weak
+        );
+        assert_eq!(
+            vec![json!(0), json!(0), json!(0), json!(1), json!(2), json!(3)],
+            strong
+        );
+    }
+
+    #[test]
+    fn strongly_connected_components_long_chain_passed() {
+        let n = 100_000;
+        let bonds: Vec<(usize, usize)> = (0..n).map(|x| (x, (x + 1) % n)).collect();
+        let (graph, _) = initialize_graph(n, &bonds);
+
+        let strong = graph
+            .run_algorithm(&Algorithm::StronglyConnectedComponents, None)
+            .unwrap();
+
+        assert!(strong.iter().all(|x| *x == json!(0)));
+    }
+
+    #[test]
+    fn degree_centrality_passed() {
+        let (graph, _) = initialize_graph(3, &[(0, 1), (0, 2), (1, 2)]);
+        let degree = |direction| {
+            graph
+                .run_algorithm(&Algorithm::DegreeCentrality { direction }, None)
+                .unwrap()
+        };
+
+        assert_eq!(vec![json!(2), json!(2), json!(2)], degree(None));
+        assert_eq!(
+            vec![json!(2), json!(1), json!(0)],
+            degree(Some(BondDirection::Outgoing))
+        );
+        assert_eq!(
+            vec![json!(0), json!(1), json!(2)],
+            degree(Some(BondDirection::Ingoing))
+        );
+    }
+
+    #[test]
+    fn betweenness_centrality_passed() {
+        // path 0 - 1 - 2 - 3
+        let (graph, _) = initialize_graph(4, &[(0, 1), (1, 2), (2, 3)]);
+        let betweenness = |undirected, normalized| {
+            as_f64(
+                graph
+                    .run_algorithm(
+                        &Algorithm::BetweennessCentrality {
+                            undirected,
+                            normalized,
+                        },
+                        None,
+                    )
+                    .unwrap(),
+            )
+        };
+
+        assert_close(&[0.0, 2.0, 2.0, 0.0], &betweenness(true, false));
+        assert_close(&[0.0, 2.0 / 3.0, 2.0 / 3.0, 0.0], &betweenness(true, true));
+        assert_close(&[0.0, 2.0, 2.0, 0.0], &betweenness(false, false));
+        assert_close(&[0.0, 1.0 / 3.0, 1.0 / 3.0, 0.0], &betweenness(false, true));
+    }
+
+    #[test]
+    fn triangle_count_passed() {
+        // triangles 0-1-2 and 1-2-3 with parallel and reversed bonds, 4 hangs off 3
+        let (graph, _) = initialize_graph(
+            5,
+            &[
+                (0, 1),
+                (1, 2),
+                (2, 0),
+                (0, 1),
+                (1, 0),
+                (2, 3),
+                (3, 1),
+                (3, 4),
+            ],
+        );
+
+        let triangles = graph
+            .run_algorithm(&Algorithm::TriangleCount, None)
+            .unwrap();
+
+        assert_eq!(
+            vec![json!(1), json!(2), json!(2), json!(1), json!(0)],
+            triangles
+        );
+    }
+
+    #[test]
+    fn algorithm_follows_bond_types_passed() {
+        let (mut graph, ids) = initialize_graph(3, &[(0, 1)]);
+        add_bond(&mut graph, "OWNS", ids[1], ids[2]);
+        let bond_types = vec![String::from("KNOWS")];
+
+        let components = graph
+            .run_algorithm(&Algorithm::WeaklyConnectedComponents, Some(&bond_types))
+            .unwrap();
+
+        assert_eq!(vec![json!(0), json!(0), json!(1)], components);
+    }
+
+    #[test]
+    fn algorithm_writes_property_passed() {
+        let (graph, ids) = initialize_graph(3, &[(0, 1)]);
+        let collection = GraphCollectionFacade::new(vec![graph], None);
+        collection
+            .update_node_properties(
+                "MyGraph",
+                ids[0],
+                json!({"name": "Ann"}).as_object().unwrap().clone(),
+            )
+            .unwrap();
+        let dto: AlgorithmDTO = serde_json::from_value(json!({
+            "algorithm": "weakly_connected_components",
+            "write_property": "component"
+        }))
+        .unwrap();
+
+        let result = collection.run_algorithm("MyGraph", &dto).unwrap();
+
+        assert_eq!(ids[2], result.results[2].id);
+        assert_eq!(json!(1), result.results[2].value);
+        let graph_lock = collection.get_graph("MyGraph").unwrap();
+        let graph = graph_lock.read().unwrap();
+        let ann = &graph.nodes_collection[0].properties;
+        assert_eq!(Some(&json!("Ann")), ann.get("name"));
+        assert_eq!(Some(&json!(0)), ann.get("component"));
+        assert_eq!(
+            Some(&json!(1)),
+            graph.nodes_collection[2].properties.get("component")
+        );
+    }
+
+    #[test]
+    fn algorithm_dto_from_json_passed() {
+        let dto: AlgorithmDTO = serde_json::from_value(json!({
+            "algorithm": "pagerank",
+            "damping_factor": 0.9,
+            "bond_types": ["KNOWS"]
+        }))
+        .unwrap();
+
+        match dto.algorithm {
+            Algorithm::PageRank { damping_factor, .. } => assert_eq!(Some(0.9), damping_factor),
+            other => panic!("unexpected algorithm {:?}", other),
+        }
+        assert_eq!(Some(vec![String::from("KNOWS")]), dto.bond_types);
+        assert_eq!(None, dto.write_property);
+    }
+}
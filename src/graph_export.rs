@@ -0,0 +1,463 @@
+use crate::core_model::{Bond, BondDirection, GraphError, InMemoryGraph, Node, Properties};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use uuid::Uuid;
+
+/// Hops from `from` node when export query does not set depth
+pub const DEFAULT_EXPORT_DEPTH: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    GraphMl,
+    Gexf,
+    /// Graphviz
+    Dot,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::GraphMl | ExportFormat::Gexf => "application/xml",
+            ExportFormat::Dot => "text/vnd.graphviz",
+        }
+    }
+
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            ExportFormat::GraphMl => "graphml",
+            ExportFormat::Gexf => "gexf",
+            ExportFormat::Dot => "dot",
+        }
+    }
+}
+
+/// Query string of GET /graphs/{name}/export.
+/// Without `from` the whole graph is exported, with it only nodes within `depth`
+/// hops of `from` (traversed like neighbours lookup) and bonds between them.
+#[derive(Debug, Deserialize)]
+pub struct ExportQueryDTO {
+    pub format: ExportFormat,
+    pub from: Option<Uuid>,
+    pub depth: Option<u32>,
+    pub direction: Option<BondDirection>,
+    /// Comma separated, only these bonds are followed and exported
+    pub bond_types: Option<String>,
+}
+
+/// Nodes and bonds of graph picked for export, bonds only between picked nodes
+pub struct GraphExport<'a> {
+    pub name: &'a str,
+    pub nodes: Vec<&'a Node>,
+    pub bonds: Vec<&'a Bond>,
+}
+
+impl InMemoryGraph {
+    /// Picks nodes and bonds described by export query
+    pub fn select_export(&self, query: &ExportQueryDTO) -> Result<GraphExport<'_>, GraphError> {
+        let bond_types: Vec<String> = query
+            .bond_types
+            .as_deref()
+            .unwrap_or("")
+            .split(',')
+            .map(|x| x.trim().to_string())
+            .filter(|x| !x.is_empty())
+            .collect();
+        match query.from {
+            None if query.depth.is_some() => Err(GraphError::InvalidQuery(String::from(
+                "depth requires from node",
+            ))),
+            None => Ok(self.export_graph(&bond_types)),
+            Some(from) => self.export_neighbourhood(
+                from,
+                query.depth.unwrap_or(DEFAULT_EXPORT_DEPTH),
+                bond_types,
+                query.direction.unwrap_or(BondDirection::Outgoing),
+            ),
+        }
+    }
+
+    /// Whole graph, `bond_types` limits bonds if not empty
+    pub fn export_graph(&self, bond_types: &[String]) -> GraphExport<'_> {
+        GraphExport {
+            name: &self.name,
+            nodes: self.nodes_collection.iter().collect(),
+            bonds: self
+                .bonds_collection
+                .iter()
+                .filter(|x| bond_types.is_empty() || bond_types.contains(&x.label))
+                .collect(),
+        }
+    }
+
+    /// Nodes reachable from `node_id` within `depth` hops and bonds between them
+    pub fn export_neighbourhood(
+        &self,
+        node_id: Uuid,
+        depth: u32,
+        bond_types: Vec<String>,
+        direction: BondDirection,
+    ) -> Result<GraphExport<'_>, GraphError> {
+        let nodes: Vec<&Node> = self
+            .get_connected_nodes_by_depth(
+                node_id,
+                depth,
+                bond_types.clone(),
+                Vec::new(),
+                Properties::new(),
+                direction,
+            )?
+            .into_iter()
+            .map(|(node, _)| node)
+            .collect();
+        let ids: HashSet<Uuid> = nodes.iter().map(|x| x.id).collect();
+        let bonds = self
+            .bonds_collection
+            .iter()
+            .filter(|x| ids.contains(&x.src) && ids.contains(&x.dst))
+            .filter(|x| bond_types.is_empty() || bond_types.contains(&x.label))
+            .collect();
+        Ok(GraphExport {
+            name: &self.name,
+            nodes,
+            bonds,
+        })
+    }
+}
+
+/// Type of property column, the narrowest one holding every value of the property.
+/// Arrays, objects and mixed values are exported as JSON text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AttributeType {
+    Boolean,
+    Long,
+    Double,
+    String,
+}
+
+impl AttributeType {
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::Bool(_) => AttributeType::Boolean,
+            Value::Number(n) if n.is_i64() => AttributeType::Long,
+            Value::Number(_) => AttributeType::Double,
+            _ => AttributeType::String,
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (AttributeType::Long, AttributeType::Double)
+            | (AttributeType::Double, AttributeType::Long) => AttributeType::Double,
+            _ => AttributeType::String,
+        }
+    }
+
+    /// Type name, the same in GraphML and GEXF
+    fn name(&self) -> &'static str {
+        match self {
+            AttributeType::Boolean => "boolean",
+            AttributeType::Long => "long",
+            AttributeType::Double => "double",
+            AttributeType::String => "string",
+        }
+    }
+}
+
+/// Property name -> its type, sorted so attribute ids are stable
+fn attribute_types<'a>(
+    properties: impl Iterator<Item = &'a Properties>,
+) -> BTreeMap<&'a str, AttributeType> {
+    let mut types = BTreeMap::<&str, AttributeType>::new();
+    for (key, value) in properties.flatten() {
+        let value_type = AttributeType::of(value);
+        types
+            .entry(key)
+            .and_modify(|x| *x = x.merge(value_type))
+            .or_insert(value_type);
+    }
+    types
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Escapes text for XML attribute or element, dropping control characters XML 1.0 forbids
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\r' => escaped.push_str("&#13;"),
+            '\t' => escaped.push_str("&#9;"),
+            c if c < ' ' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Quoted DOT id
+fn dot_quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => {}
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Labels of node as one value, `Person:Admin`
+fn joined_labels(node: &Node) -> String {
+    node.labels.join(":")
+}
+
+impl<'a> GraphExport<'a> {
+    pub fn export(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::GraphMl => self.to_graphml(),
+            ExportFormat::Gexf => self.to_gexf(),
+            ExportFormat::Dot => self.to_dot(),
+        }
+    }
+
+    /// GraphML, labels are `labels` node data and `label` edge data,
+    /// every property is data of key named after it
+    pub fn to_graphml(&self) -> String {
+        let node_types = attribute_types(self.nodes.iter().map(|x| &x.properties));
+        let bond_types = attribute_types(self.bonds.iter().map(|x| &x.properties));
+        let node_keys: BTreeMap<&str, String> = node_types
+            .keys()
+            .enumerate()
+            .map(|(i, x)| (*x, format!("n{}", i)))
+            .collect();
+        let bond_keys: BTreeMap<&str, String> = bond_types
+            .keys()
+            .enumerate()
+            .map(|(i, x)| (*x, format!("e{}", i)))
+            .collect();
+
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        out.push_str(
+            "  <key id=\"labels\" for=\"node\" attr.name=\"labels\" attr.type=\"string\"/>\n",
+        );
+        out.push_str(
+            "  <key id=\"label\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>\n",
+        );
+        for (name, attribute_type) in &node_types {
+            let _ = writeln!(
+                out,
+                "  <key id=\"{}\" for=\"node\" attr.name=\"{}\" attr.type=\"{}\"/>",
+                node_keys[name],
+                xml_escape(name),
+                attribute_type.name()
+            );
+        }
+        for (name, attribute_type) in &bond_types {
+            let _ = writeln!(
+                out,
+                "  <key id=\"{}\" for=\"edge\" attr.name=\"{}\" attr.type=\"{}\"/>",
+                bond_keys[name],
+                xml_escape(name),
+                attribute_type.name()
+            );
+        }
+        let _ = writeln!(
+            out,
+            "  <graph id=\"{}\" edgedefault=\"directed\">",
+            xml_escape(self.name)
+        );
+        for node in &self.nodes {
+            let _ = writeln!(out, "    <node id=\"{}\">", node.id);
+            let _ = writeln!(
+                out,
+                "      <data key=\"labels\">{}</data>",
+                xml_escape(&joined_labels(node))
+            );
+            for (key, value) in &node.properties {
+                let _ = writeln!(
+                    out,
+                    "      <data key=\"{}\">{}</data>",
+                    node_keys[key.as_str()],
+                    xml_escape(&value_text(value))
+                );
+            }
+            out.push_str("    </node>\n");
+        }
+        for bond in &self.bonds {
+            let _ = writeln!(
+                out,
+                "    <edge id=\"{}\" source=\"{}\" target=\"{}\">",
+                bond.id, bond.src, bond.dst
+            );
+            let _ = writeln!(
+                out,
+                "      <data key=\"label\">{}</data>",
+                xml_escape(&bond.label)
+            );
+            for (key, value) in &bond.properties {
+                let _ = writeln!(
+                    out,
+                    "      <data key=\"{}\">{}</data>",
+                    bond_keys[key.as_str()],
+                    xml_escape(&value_text(value))
+                );
+            }
+            out.push_str("    </edge>\n");
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    /// GEXF 1.3, labels are `label` of nodes and edges, properties are attvalues
+    pub fn to_gexf(&self) -> String {
+        let node_types = attribute_types(self.nodes.iter().map(|x| &x.properties));
+        let bond_types = attribute_types(self.bonds.iter().map(|x| &x.properties));
+        let node_ids: BTreeMap<&str, usize> = node_types
+            .keys()
+            .enumerate()
+            .map(|(i, x)| (*x, i))
+            .collect();
+        let bond_ids: BTreeMap<&str, usize> = bond_types
+            .keys()
+            .enumerate()
+            .map(|(i, x)| (*x, i))
+            .collect();
+
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n");
+        let _ = writeln!(
+            out,
+            "  <meta>\n    <description>{}</description>\n  </meta>",
+            xml_escape(self.name)
+        );
+        out.push_str("  <graph mode=\"static\" defaultedgetype=\"directed\">\n");
+        for (class, types) in [("node", &node_types), ("edge", &bond_types)] {
+            if types.is_empty() {
+                continue;
+            }
+            let _ = writeln!(out, "    <attributes class=\"{}\">", class);
+            for (i, (name, attribute_type)) in types.iter().enumerate() {
+                let _ = writeln!(
+                    out,
+                    "      <attribute id=\"{}\" title=\"{}\" type=\"{}\"/>",
+                    i,
+                    xml_escape(name),
+                    attribute_type.name()
+                );
+            }
+            out.push_str("    </attributes>\n");
+        }
+        out.push_str("    <nodes>\n");
+        for node in &self.nodes {
+            let _ = write!(
+                out,
+                "      <node id=\"{}\" label=\"{}\"",
+                node.id,
+                xml_escape(&joined_labels(node))
+            );
+            write_gexf_attvalues(&mut out, &node.properties, &node_ids, "node");
+        }
+        out.push_str("    </nodes>\n    <edges>\n");
+        for bond in &self.bonds {
+            let _ = write!(
+                out,
+                "      <edge id=\"{}\" source=\"{}\" target=\"{}\" label=\"{}\"",
+                bond.id,
+                bond.src,
+                bond.dst,
+                xml_escape(&bond.label)
+            );
+            write_gexf_attvalues(&mut out, &bond.properties, &bond_ids, "edge");
+        }
+        out.push_str("    </edges>\n  </graph>\n</gexf>\n");
+        out
+    }
+
+    /// Graphviz digraph, labels are `label` of nodes and edges,
+    /// properties are attributes named after them
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph {} {{", dot_quote(self.name));
+        for node in &self.nodes {
+            let _ = write!(
+                out,
+                "  {} [label={}",
+                dot_quote(&node.id.to_string()),
+                dot_quote(&joined_labels(node))
+            );
+            write_dot_attributes(&mut out, &node.properties);
+        }
+        for bond in &self.bonds {
+            let _ = write!(
+                out,
+                "  {} -> {} [id={}, label={}",
+                dot_quote(&bond.src.to_string()),
+                dot_quote(&bond.dst.to_string()),
+                dot_quote(&bond.id.to_string()),
+                dot_quote(&bond.label)
+            );
+            write_dot_attributes(&mut out, &bond.properties);
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Closes element opened by caller, with attvalues if it has properties
+fn write_gexf_attvalues(
+    out: &mut String,
+    properties: &Properties,
+    attribute_ids: &BTreeMap<&str, usize>,
+    element: &str,
+) {
+    if properties.is_empty() {
+        out.push_str("/>\n");
+        return;
+    }
+    out.push_str(">\n        <attvalues>\n");
+    for (key, value) in properties {
+        let _ = writeln!(
+            out,
+            "          <attvalue for=\"{}\" value=\"{}\"/>",
+            attribute_ids[key.as_str()],
+            xml_escape(&value_text(value))
+        );
+    }
+    let _ = writeln!(out, "        </attvalues>\n      </{}>", element);
+}
+
+/// Closes attribute list opened by caller
+fn write_dot_attributes(out: &mut String, properties: &Properties) {
+    for (key, value) in properties {
+        let _ = write!(
+            out,
+            ", {}={}",
+            dot_quote(key),
+            dot_quote(&value_text(value))
+        );
+    }
+    out.push_str("];\n");
+}
@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod export_tests {
+    use crate::core_model::{Bond, BondDirection, GraphError, InMemoryGraph, Node};
+    use crate::graph_export::{ExportFormat, ExportQueryDTO};
+    use serde_json::{json, Map, Value};
+    use uuid::Uuid;
+
+    fn add_node(graph: &mut InMemoryGraph, labels: &[&str], properties: Value) -> Uuid {
+        let id = Uuid::new_v4();
+        graph
+            .add_node(Node {
+                id,
+                labels: labels.iter().map(|x| x.to_string()).collect(),
+                properties: properties.as_object().unwrap().clone(),
+            })
+            .unwrap();
+        id
+    }
+
+    fn add_bond(graph: &mut InMemoryGraph, label: &str, src: Uuid, dst: Uuid) -> Uuid {
+        let id = Uuid::new_v4();
+        graph
+            .add_bond(Bond {
+                id,
+                label: String::from(label),
+                src,
+                dst,
+                properties: Map::new(),
+            })
+            .unwrap();
+        id
+    }
+
+    /// ann -KNOWS-> bob -KNOWS-> carl, ann -OWNS-> car
+    fn initialize_graph() -> (InMemoryGraph, Vec<Uuid>) {
+        let mut graph = InMemoryGraph::new_graph(String::from("My <Graph>"));
+        let ann = add_node(
+            &mut graph,
+            &["Person", "Admin"],
+            json!({"name": "Ann \"A\" & co", "age": 30, "tags": ["x"]}),
+        );
+        let bob = add_node(&mut graph, &["Person"], json!({"name": "Bob", "age": 1.5}));
+        let carl = add_node(&mut graph, &["Person"], json!({"name": "Carl"}));
+        let car = add_node(&mut graph, &["Car"], json!({}));
+        add_bond(&mut graph, "KNOWS", ann, bob);
+        add_bond(&mut graph, "KNOWS", bob, carl);
+        add_bond(&mut graph, "OWNS", ann, car);
+        (graph, vec![ann, bob, carl, car])
+    }
+
+    fn query(format: ExportFormat) -> ExportQueryDTO {
+        ExportQueryDTO {
+            format,
+            from: None,
+            depth: None,
+            direction: None,
+            bond_types: None,
+        }
+    }
+
+    #[test]
+    fn export_graphml_passed() {
+        let (graph, ids) = initialize_graph();
+
+        let graphml = graph.export_graph(&[]).to_graphml();
+
+        assert!(graphml.contains("<graph id=\"My &lt;Graph&gt;\" edgedefault=\"directed\">"));
+        // mixed int and float widens to double, array is JSON text
+        assert!(graphml.contains("attr.name=\"age\" attr.type=\"double\""));
+        assert!(graphml.contains("attr.name=\"tags\" attr.type=\"string\""));
+        assert!(graphml.contains(&format!("<node id=\"{}\">", ids[0])));
+        assert!(graphml.contains("<data key=\"labels\">Person:Admin</data>"));
+        assert!(graphml.contains(">Ann &quot;A&quot; &amp; co</data>"));
+        assert!(graphml.contains(">[&quot;x&quot;]</data>"));
+        assert!(graphml.contains(&format!("source=\"{}\" target=\"{}\"", ids[0], ids[1])));
+        assert!(graphml.contains("<data key=\"label\">OWNS</data>"));
+        assert_eq!(4, graphml.matches("<node ").count());
+        assert_eq!(3, graphml.matches("<edge ").count());
+    }
+
+    #[test]
+    fn export_gexf_passed() {
+        let (graph, ids) = initialize_graph();
+
+        let gexf = graph.export_graph(&[]).to_gexf();
+
+        assert!(gexf.contains("<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">"));
+        assert!(gexf.contains("<attributes class=\"node\">"));
+        // bonds have no properties
+        assert!(!gexf.contains("<attributes class=\"edge\">"));
+        assert!(gexf.contains(&format!("<node id=\"{}\" label=\"Person:Admin\">", ids[0])));
+        assert!(gexf.contains(&format!("<node id=\"{}\" label=\"Car\"/>", ids[3])));
+        assert!(gexf.contains("<attvalue for=\"0\" value=\"30\"/>"));
+        assert_eq!(3, gexf.matches("<edge ").count());
+    }
+
+    #[test]
+    fn export_dot_passed() {
+        let (graph, ids) = initialize_graph();
+
+        let dot = graph.export_graph(&[]).to_dot();
+
+        assert!(dot.starts_with("digraph \"My <Graph>\" {\n"));
+        assert!(dot.contains(&format!(
+            "\"{}\" [label=\"Person:Admin\", \"age\"=\"30\", \"name\"=\"Ann \\\"A\\\" & co\", \"tags\"=\"[\\\"x\\\"]\"];",
+            ids[0]
+        )));
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\" [id=", ids[1], ids[2])));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn export_neighbourhood_passed() {
+        let (graph, ids) = initialize_graph();
+        let mut dto = query(ExportFormat::Dot);
+        dto.from = Some(ids[1]);
+        dto.direction = Some(BondDirection::Both);
+
+        let export = graph.select_export(&dto).unwrap();
+
+        let mut node_ids: Vec<Uuid> = export.nodes.iter().map(|x| x.id).collect();
+        node_ids.sort();
+        let mut expected = vec![ids[0], ids[1], ids[2]];
+        expected.sort();
+        assert_eq!(expected, node_ids);
+        // OWNS leads out of neighbourhood
+        assert_eq!(2, export.bonds.len());
+    }
+
+    #[test]
+    fn export_filters_bond_types_passed() {
+        let (graph, ids) = initialize_graph();
+        let mut dto = query(ExportFormat::GraphMl);
+        dto.from = Some(ids[0]);
+        dto.depth = Some(5);
+        dto.bond_types = Some(String::from("KNOWS"));
+
+        let export = graph.select_export(&dto).unwrap();
+
+        assert_eq!(3, export.nodes.len());
+        assert!(export.bonds.iter().all(|x| x.label == "KNOWS"));
+        dto.from = None;
+        dto.depth = None;
+        assert_eq!(2, graph.select_export(&dto).unwrap().bonds.len());
+    }
+
+    #[test]
+    fn export_depth_without_from_failed() {
+        let (graph, _) = initialize_graph();
+        let mut dto = query(ExportFormat::Gexf);
+        dto.depth = Some(2);
+
+        assert!(matches!(
+            graph.select_export(&dto),
+            Err(GraphError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn export_format_from_query_string_passed() {
+        let dto: ExportQueryDTO = serde_json::from_value(json!({"format": "graphml"})).unwrap();
+
+        assert_eq!(ExportFormat::GraphMl, dto.format);
+        assert_eq!("application/xml", dto.format.content_type());
+        assert_eq!("dot", ExportFormat::Dot.file_extension());
+    }
+}
@@ -0,0 +1,238 @@
+use crate::core_model::{Bond, GraphError, Node};
+use crate::AppState;
+use actix_send_websocket::{Message, WebSocket};
+use actix_web::http::{HeaderName, HeaderValue};
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Number of latest changes kept for reconnecting clients to replay
+pub const GRAPH_FEED_CAPACITY: usize = 10_000;
+
+/// Header of feed upgrade responce, cursor of the latest change at subscribe
+pub const GRAPH_FEED_CURSOR_HEADER: &str = "x-graph-feed-cursor";
+
+/// Committed creation or deletion of node or bond, `event` names it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GraphChange {
+    NodeCreated {
+        node: Node,
+    },
+    NodeDeleted {
+        node: Node,
+    },
+    BondCreated {
+        bond: Bond,
+    },
+    /// Also sent for every bond deleted by detached node deletion, before the node
+    BondDeleted {
+        bond: Bond,
+    },
+}
+
+impl GraphChange {
+    /// Node matches by any of its labels, bond by its label
+    fn has_any_label(&self, labels: &[String]) -> bool {
+        match self {
+            GraphChange::NodeCreated { node } | GraphChange::NodeDeleted { node } => {
+                node.labels.iter().any(|x| labels.contains(x))
+            }
+            GraphChange::BondCreated { bond } | GraphChange::BondDeleted { bond } => {
+                labels.contains(&bond.label)
+            }
+        }
+    }
+}
+
+/// Message of graph change feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphChangeEventDTO {
+    /// Position in feed, grows by one with every change of the graph
+    pub cursor: u64,
+    #[serde(flatten)]
+    pub change: GraphChange,
+}
+
+/// Query of graph change feed websocket
+#[derive(Debug, Deserialize)]
+pub struct GraphFeedQueryDTO {
+    /// Cursor of the last event client has seen, retained events after it are replayed.
+    /// Without it only changes made after connecting are sent.
+    pub cursor: Option<u64>,
+    /// Comma separated, only changes of nodes or bonds having one of these labels are sent
+    pub labels: Option<String>,
+}
+
+/// Changes committed to one graph since it was loaded.
+///
+/// Facade publishes while still holding the graph write lock, so cursors follow
+/// commit order. Cursors start over when the server restarts.
+#[derive(Debug, Default)]
+pub struct GraphFeed {
+    state: Mutex<FeedState>,
+}
+
+#[derive(Debug, Default)]
+struct FeedState {
+    /// Cursor of the latest change, 0 before any
+    cursor: u64,
+    /// At most GRAPH_FEED_CAPACITY latest changes, oldest first
+    retained: VecDeque<GraphChangeEventDTO>,
+    next_subscription: u64,
+    subscriptions: Vec<FeedSubscription>,
+}
+
+#[derive(Debug)]
+struct FeedSubscription {
+    id: u64,
+    /// Empty passes every change
+    labels: Vec<String>,
+    sender: UnboundedSender<GraphChangeEventDTO>,
+}
+
+impl FeedSubscription {
+    /// False once receiver is gone
+    fn send(&self, event: &GraphChangeEventDTO) -> bool {
+        if !self.labels.is_empty() && !event.change.has_any_label(&self.labels) {
+            return true;
+        }
+        self.sender.send(event.clone()).is_ok()
+    }
+}
+
+impl GraphFeed {
+    /// Assigns cursors to changes of one commit, retains and sends them
+    pub fn publish(&self, changes: Vec<GraphChange>) {
+        if changes.is_empty() {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        for change in changes {
+            state.cursor += 1;
+            let event = GraphChangeEventDTO {
+                cursor: state.cursor,
+                change,
+            };
+            // subscriber whose receiver is gone is dropped on the way
+            state.subscriptions.retain(|x| x.send(&event));
+            if state.retained.len() == GRAPH_FEED_CAPACITY {
+                state.retained.pop_front();
+            }
+            state.retained.push_back(event);
+        }
+    }
+
+    /// Subscribes to changes after `after`, replaying retained ones first,
+    /// or to changes from now on without it. Fails with CursorExpired if some
+    /// change after `after` is no longer retained or `after` is ahead of feed.
+    /// Returns subscription id and cursor of the latest change, 0 before any.
+    pub fn subscribe(
+        &self,
+        after: Option<u64>,
+        labels: Vec<String>,
+    ) -> Result<(u64, u64, UnboundedReceiver<GraphChangeEventDTO>), GraphError> {
+        let mut state = self.state.lock().unwrap();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        state.next_subscription += 1;
+        let subscription = FeedSubscription {
+            id: state.next_subscription,
+            labels,
+            sender,
+        };
+
+        if let Some(after) = after {
+            let oldest = state.cursor + 1 - state.retained.len() as u64;
+            if after > state.cursor || after + 1 < oldest {
+                return Err(GraphError::CursorExpired(after));
+            }
+            // replayed under the same lock, so nothing is missed or sent twice
+            let skip = (after + 1 - oldest) as usize;
+            for event in state.retained.iter().skip(skip) {
+                subscription.send(event);
+            }
+        }
+
+        let id = subscription.id;
+        state.subscriptions.push(subscription);
+        Ok((id, state.cursor, receiver))
+    }
+
+    pub fn unsubscribe(&self, id: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.subscriptions.retain(|x| x.id != id);
+    }
+
+    /// Drops all subscriptions, which closes their receivers
+    pub fn close(&self) {
+        self.state.lock().unwrap().subscriptions.clear();
+    }
+}
+
+/// Streams GraphChangeEventDTO messages of graph, closed when graph is deleted.
+/// Upgrade responce carries cursor to resume from in GRAPH_FEED_CURSOR_HEADER.
+pub async fn graph_feed_ws(
+    data: web::Data<AppState>,
+    web::Path(graph_name): web::Path<String>,
+    query: web::Query<GraphFeedQueryDTO>,
+    ws: WebSocket,
+) -> Result<HttpResponse, GraphError> {
+    let labels: Vec<String> = query
+        .labels
+        .as_deref()
+        .unwrap_or("")
+        .split(',')
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .map(String::from)
+        .collect();
+    let feed = data.graph_collection.graph_feed(&graph_name)?;
+    // subscribed before upgrade, so expired cursor is answered with plain error responce
+    let (subscription, cursor, mut changes) = feed.subscribe(query.cursor, labels)?;
+
+    // stream is the async iterator of incoming client websocket messages.
+    // res is the response we return to client.
+    // tx is a sender to push new websocket message to client response.
+    let (mut stream, mut res, tx) = ws.into_parts();
+    // client connecting without cursor learns where to resume from
+    res.headers_mut().insert(
+        HeaderName::from_static(GRAPH_FEED_CURSOR_HEADER),
+        HeaderValue::from(cursor),
+    );
+
+    let changes_tx = tx.clone();
+    actix_web::rt::spawn(async move {
+        while let Some(event) = changes.recv().await {
+            let text = serde_json::to_string(&event).expect("err serializing");
+            if changes_tx.text(text).is_err() {
+                return;
+            }
+        }
+        // subscription ended by graph deletion or client leaving
+        let _ = changes_tx.close(None);
+    });
+
+    actix_web::rt::spawn(async move {
+        while let Some(Ok(msg)) = stream.next().await {
+            let result = match msg {
+                Message::Ping(bytes) => tx.pong(&bytes),
+                Message::Close(reason) => {
+                    let _ = tx.close(reason);
+                    // force end the stream when we have a close message.
+                    break;
+                }
+                // feed is one way, other types of message would be ignored
+                _ => Ok(()),
+            };
+            if result.is_err() {
+                // end the stream when the response is gone.
+                break;
+            }
+        }
+        feed.unsubscribe(subscription);
+    });
+
+    Ok(res)
+}
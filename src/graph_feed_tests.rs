@@ -0,0 +1,246 @@
+#[cfg(test)]
+mod change_feed_tests {
+    use crate::core_model::{Bond, GraphCollectionFacade, GraphError, InMemoryGraph, Node};
+    use crate::graph_feed::{GraphChange, GraphChangeEventDTO, GRAPH_FEED_CAPACITY};
+    use serde_json::{json, Map};
+    use std::future::Future;
+    use tokio::sync::mpsc::UnboundedReceiver;
+    use uuid::Uuid;
+
+    fn block_on<F: Future + 'static>(fut: F) -> F::Output {
+        actix_web::rt::System::new("graph_feed_tests").block_on(fut)
+    }
+
+    fn initialize_collection() -> GraphCollectionFacade {
+        GraphCollectionFacade::new(
+            vec![InMemoryGraph::new_graph(String::from("MyGraph"))],
+            None,
+        )
+    }
+
+    fn node(label: &str) -> Node {
+        Node {
+            id: Uuid::new_v4(),
+            labels: vec![String::from(label)],
+            properties: Map::new(),
+        }
+    }
+
+    fn bond(label: &str, src: Uuid, dst: Uuid) -> Bond {
+        Bond {
+            id: Uuid::new_v4(),
+            label: String::from(label),
+            src,
+            dst,
+            properties: Map::new(),
+        }
+    }
+
+    /// Cursor, event name and id of node or bond of every received change
+    fn drain(changes: &mut UnboundedReceiver<GraphChangeEventDTO>) -> Vec<(u64, String, Uuid)> {
+        let mut drained = Vec::new();
+        while let Ok(event) = changes.try_recv() {
+            let id = match &event.change {
+                GraphChange::NodeCreated { node } | GraphChange::NodeDeleted { node } => node.id,
+                GraphChange::BondCreated { bond } | GraphChange::BondDeleted { bond } => bond.id,
+            };
+            let name = serde_json::to_value(&event).unwrap()["event"]
+                .as_str()
+                .unwrap()
+                .to_string();
+            drained.push((event.cursor, name, id));
+        }
+        drained
+    }
+
+    fn entry(cursor: u64, event: &str, id: Uuid) -> (u64, String, Uuid) {
+        (cursor, String::from(event), id)
+    }
+
+    #[test]
+    fn facade_changes_published_passed() {
+        let collection = initialize_collection();
+        let feed = collection.graph_feed("MyGraph").unwrap();
+        let (_, _, mut changes) = feed.subscribe(None, Vec::new()).unwrap();
+
+        let ann = collection.add_node("MyGraph", node("Person")).unwrap();
+        let bob = collection.add_node("MyGraph", node("Person")).unwrap();
+        let knows = collection
+            .add_bond("MyGraph", bond("KNOWS", ann, bob))
+            .unwrap();
+        collection
+            .update_node_properties(
+                "MyGraph",
+                ann,
+                json!({"name": "Ann"}).as_object().unwrap().clone(),
+            )
+            .unwrap();
+        // failed mutation publishes nothing
+        assert!(collection.delete_node("MyGraph", ann, false).is_err());
+        collection.delete_node("MyGraph", ann, true).unwrap();
+
+        assert_eq!(
+            vec![
+                entry(1, "node_created", ann),
+                entry(2, "node_created", bob),
+                entry(3, "bond_created", knows),
+                entry(4, "bond_deleted", knows),
+                entry(5, "node_deleted", ann),
+            ],
+            drain(&mut changes)
+        );
+        let (_, cursor, _) = feed.subscribe(None, Vec::new()).unwrap();
+        assert_eq!(5, cursor);
+    }
+
+    #[test]
+    fn transaction_changes_published_on_commit_passed() {
+        let collection = initialize_collection();
+        let feed = collection.graph_feed("MyGraph").unwrap();
+        let (_, _, mut changes) = feed.subscribe(None, Vec::new()).unwrap();
+
+        let mut rolled_back = collection.begin_transaction("MyGraph").unwrap();
+        collection
+            .continue_transaction(&mut rolled_back, |tx| tx.add_node(node("Person")))
+            .unwrap();
+        drop(rolled_back);
+        let mut transaction = collection.begin_transaction("MyGraph").unwrap();
+        let (ann, city, lives) = collection
+            .continue_transaction(&mut transaction, |tx| {
+                let ann = tx.add_node(node("Person"))?;
+                let city = tx.add_node(node("City"))?;
+                let lives = tx.add_bond(bond("LIVES_IN", ann, city))?;
+                tx.delete_bond(lives)?;
+                Ok((ann, city, lives))
+            })
+            .unwrap();
+        assert!(drain(&mut changes).is_empty());
+
+        collection.commit_transaction(transaction).unwrap();
+        let run = collection
+            .run_transaction("MyGraph", |tx| tx.add_node(node("Person")))
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                entry(1, "node_created", ann),
+                entry(2, "node_created", city),
+                entry(3, "bond_created", lives),
+                entry(4, "bond_deleted", lives),
+                entry(5, "node_created", run),
+            ],
+            drain(&mut changes)
+        );
+    }
+
+    #[test]
+    fn label_filter_passed() {
+        let collection = initialize_collection();
+        let feed = collection.graph_feed("MyGraph").unwrap();
+        let labels = vec![String::from("City"), String::from("LIVES_IN")];
+        let (_, _, mut changes) = feed.subscribe(None, labels).unwrap();
+
+        let ann = collection.add_node("MyGraph", node("Person")).unwrap();
+        let city = collection.add_node("MyGraph", node("City")).unwrap();
+        collection
+            .add_bond("MyGraph", bond("KNOWS", ann, ann))
+            .unwrap();
+        let lives = collection
+            .add_bond("MyGraph", bond("LIVES_IN", ann, city))
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                entry(2, "node_created", city),
+                entry(4, "bond_created", lives)
+            ],
+            drain(&mut changes)
+        );
+    }
+
+    #[test]
+    fn resume_from_cursor_passed() {
+        let collection = initialize_collection();
+        let feed = collection.graph_feed("MyGraph").unwrap();
+        let ids: Vec<Uuid> = (0..3)
+            .map(|_| collection.add_node("MyGraph", node("Person")).unwrap())
+            .collect();
+
+        let (_, _, mut resumed) = feed.subscribe(Some(1), Vec::new()).unwrap();
+        let (_, _, mut from_start) = feed.subscribe(Some(0), Vec::new()).unwrap();
+        let (_, _, mut up_to_date) = feed.subscribe(Some(3), Vec::new()).unwrap();
+        let last = collection.add_node("MyGraph", node("Person")).unwrap();
+
+        assert_eq!(
+            vec![
+                entry(2, "node_created", ids[1]),
+                entry(3, "node_created", ids[2]),
+                entry(4, "node_created", last),
+            ],
+            drain(&mut resumed)
+        );
+        assert_eq!(4, drain(&mut from_start).len());
+        assert_eq!(vec![entry(4, "node_created", last)], drain(&mut up_to_date));
+    }
+
+    #[test]
+    fn expired_cursor_failed() {
+        let collection = initialize_collection();
+        let feed = collection.graph_feed("MyGraph").unwrap();
+        let changes: Vec<GraphChange> = (0..GRAPH_FEED_CAPACITY + 2)
+            .map(|_| GraphChange::NodeCreated {
+                node: node("Person"),
+            })
+            .collect();
+        feed.publish(changes);
+        let (_, latest, _) = feed.subscribe(None, Vec::new()).unwrap();
+
+        assert_eq!(
+            GraphError::CursorExpired(1),
+            feed.subscribe(Some(1), Vec::new()).unwrap_err()
+        );
+        assert_eq!(
+            GraphError::CursorExpired(latest + 1),
+            feed.subscribe(Some(latest + 1), Vec::new()).unwrap_err()
+        );
+        let (_, _, mut oldest) = feed.subscribe(Some(2), Vec::new()).unwrap();
+        let replayed = drain(&mut oldest);
+        assert_eq!(GRAPH_FEED_CAPACITY, replayed.len());
+        assert_eq!(3, replayed[0].0);
+    }
+
+    #[test]
+    fn deleted_graph_closes_feed_passed() {
+        block_on(async {
+            let collection = initialize_collection();
+            let feed = collection.graph_feed("MyGraph").unwrap();
+            let (_, _, mut changes) = feed.subscribe(None, Vec::new()).unwrap();
+
+            collection.delete_graph("MyGraph").unwrap();
+
+            assert!(changes.recv().await.is_none());
+            assert_eq!(
+                Some(GraphError::GraphNotFound(String::from("MyGraph"))),
+                collection.graph_feed("MyGraph").err()
+            );
+        });
+    }
+
+    #[test]
+    fn change_event_json_passed() {
+        let ann = node("Person");
+        let event = GraphChangeEventDTO {
+            cursor: 7,
+            change: GraphChange::NodeDeleted { node: ann.clone() },
+        };
+
+        assert_eq!(
+            json!({
+                "cursor": 7,
+                "event": "node_deleted",
+                "node": {"id": ann.id, "labels": ["Person"], "properties": {}}
+            }),
+            serde_json::to_value(&event).unwrap()
+        );
+    }
+}
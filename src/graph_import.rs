@@ -0,0 +1,556 @@
+use crate::core_model::{Bond, GraphCollectionFacade, GraphError, InMemoryGraph, Node, Properties};
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Max size of POST /graphs/{name}/import body
+pub const IMPORT_BODY_LIMIT: usize = 256 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    /// Header row, then one node or bond per row
+    Csv,
+    /// One JSON document per line:
+    /// nodes `{"id": .., "labels": [..], "properties": {..}}`,
+    /// bonds `{"id": .., "label": .., "src": .., "dst": .., "properties": {..}}`
+    Jsonl,
+}
+
+/// CSV columns with special meaning, every other column is a property.
+/// Property header may carry type as `name:type`, type is one of
+/// string (default), int, float, bool or json. Empty cells are left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvMapping {
+    /// External id of node or bond, optional column
+    pub id: String,
+    /// Node labels joined by label_separator
+    pub labels: String,
+    pub label_separator: String,
+    /// Bond label
+    pub label: String,
+    /// External ids of bond endpoints
+    pub src: String,
+    pub dst: String,
+}
+
+impl Default for CsvMapping {
+    fn default() -> Self {
+        CsvMapping {
+            id: String::from("id"),
+            labels: String::from("labels"),
+            label_separator: String::from(";"),
+            label: String::from("label"),
+            src: String::from("src"),
+            dst: String::from("dst"),
+        }
+    }
+}
+
+/// Body of POST /graphs/{name}/import, graph is created if missing.
+///
+/// External ids are file-local names of nodes and bonds: a UUID is kept as node id,
+/// anything else gets a generated one. Bond endpoints are resolved against imported
+/// nodes first, then as UUIDs of nodes already in the graph.
+#[derive(Serialize, Deserialize)]
+pub struct ImportDTO {
+    pub format: ImportFormat,
+    #[serde(default)]
+    pub nodes: String,
+    #[serde(default)]
+    pub bonds: String,
+    #[serde(default)]
+    pub mapping: CsvMapping,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFile {
+    Nodes,
+    Bonds,
+}
+
+/// Row skipped by import, `line` is 1-based line of file the row starts at
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImportRowErrorDTO {
+    pub file: ImportFile,
+    pub line: usize,
+    pub code: String,
+    pub message: String,
+}
+
+/// Numbers of imported nodes and bonds plus every skipped row
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportReportDTO {
+    pub nodes: usize,
+    pub bonds: usize,
+    pub errors: Vec<ImportRowErrorDTO>,
+}
+
+struct NodeRow {
+    external_id: Option<String>,
+    labels: Vec<String>,
+    properties: Properties,
+}
+
+struct BondRow {
+    external_id: Option<String>,
+    label: String,
+    src: String,
+    dst: String,
+    properties: Properties,
+}
+
+/// Parsed row or the reason it is skipped, with its line
+type ParsedRows<T> = Vec<(usize, Result<T, GraphError>)>;
+
+#[derive(Deserialize)]
+struct JsonlNode {
+    #[serde(default)]
+    id: Option<Value>,
+    labels: Vec<String>,
+    #[serde(default)]
+    properties: Properties,
+}
+
+#[derive(Deserialize)]
+struct JsonlBond {
+    #[serde(default)]
+    id: Option<Value>,
+    label: String,
+    src: Value,
+    dst: Value,
+    #[serde(default)]
+    properties: Properties,
+}
+
+impl GraphCollectionFacade {
+    /// Loads node and bond files into graph by name, creating the graph if missing.
+    /// Rows that fail to parse or validate are reported and skipped, the rest is
+    /// applied as one transaction. Malformed CSV header fails the whole import.
+    pub fn import_graph(
+        &self,
+        graph_name: &str,
+        dto: &ImportDTO,
+    ) -> Result<ImportReportDTO, GraphError> {
+        let (node_rows, bond_rows) = match dto.format {
+            ImportFormat::Csv => (
+                parse_csv_nodes(&dto.nodes, &dto.mapping)?,
+                parse_csv_bonds(&dto.bonds, &dto.mapping)?,
+            ),
+            ImportFormat::Jsonl => (parse_jsonl_nodes(&dto.nodes), parse_jsonl_bonds(&dto.bonds)),
+        };
+
+        if !self.contains_graph(graph_name) {
+            match self.add_graph(InMemoryGraph::new_graph(graph_name.to_string())) {
+                // created by concurrent request in between
+                Ok(_) | Err(GraphError::DuplicateGraphName(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        self.run_transaction(graph_name, |transaction| {
+            let mut report = ImportReportDTO::default();
+            let mut external_ids = HashMap::<String, Uuid>::new();
+
+            for (line, row) in node_rows {
+                let added = row.and_then(|row| {
+                    let id = match row.external_id {
+                        None => Uuid::default(),
+                        Some(external_id) => *external_ids
+                            .entry(external_id)
+                            .or_insert_with_key(|x| external_uuid(x)),
+                    };
+                    transaction.add_node(Node {
+                        id,
+                        labels: row.labels,
+                        properties: row.properties,
+                    })
+                });
+                match added {
+                    Ok(_) => report.nodes += 1,
+                    Err(e) => report.errors.push(row_error(ImportFile::Nodes, line, &e)),
+                }
+            }
+
+            for (line, row) in bond_rows {
+                let added = row.and_then(|row| {
+                    let bond = Bond {
+                        id: row
+                            .external_id
+                            .map(|x| external_uuid(&x))
+                            .unwrap_or_default(),
+                        label: row.label,
+                        src: resolve_endpoint(&external_ids, &row.src)?,
+                        dst: resolve_endpoint(&external_ids, &row.dst)?,
+                        properties: row.properties,
+                    };
+                    transaction.add_bond(bond)
+                });
+                match added {
+                    Ok(_) => report.bonds += 1,
+                    Err(e) => report.errors.push(row_error(ImportFile::Bonds, line, &e)),
+                }
+            }
+
+            Ok(report)
+        })
+    }
+}
+
+/// UUID external id is kept, any other gets a generated id
+fn external_uuid(external_id: &str) -> Uuid {
+    Uuid::parse_str(external_id).unwrap_or_else(|_| Uuid::new_v4())
+}
+
+fn resolve_endpoint(
+    external_ids: &HashMap<String, Uuid>,
+    external_id: &str,
+) -> Result<Uuid, GraphError> {
+    if let Some(id) = external_ids.get(external_id) {
+        return Ok(*id);
+    }
+    Uuid::parse_str(external_id).map_err(|_| {
+        GraphError::InvalidImport(format!(
+            "bond endpoint \"{}\" is neither imported node nor node id",
+            external_id
+        ))
+    })
+}
+
+fn row_error(file: ImportFile, line: usize, e: &GraphError) -> ImportRowErrorDTO {
+    ImportRowErrorDTO {
+        file,
+        line,
+        code: e.code().to_string(),
+        message: e.to_string(),
+    }
+}
+
+fn parse_jsonl_nodes(text: &str) -> ParsedRows<NodeRow> {
+    parse_jsonl(text, |x: JsonlNode| {
+        Ok(NodeRow {
+            external_id: x.id.as_ref().map(external_id_text).transpose()?,
+            labels: x.labels,
+            properties: x.properties,
+        })
+    })
+}
+
+fn parse_jsonl_bonds(text: &str) -> ParsedRows<BondRow> {
+    parse_jsonl(text, |x: JsonlBond| {
+        Ok(BondRow {
+            external_id: x.id.as_ref().map(external_id_text).transpose()?,
+            label: x.label,
+            src: external_id_text(&x.src)?,
+            dst: external_id_text(&x.dst)?,
+            properties: x.properties,
+        })
+    })
+}
+
+/// Every non-blank line is one row
+fn parse_jsonl<D: serde::de::DeserializeOwned, T>(
+    text: &str,
+    map: impl Fn(D) -> Result<T, GraphError>,
+) -> ParsedRows<T> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, x)| !x.trim().is_empty())
+        .map(|(i, x)| {
+            let row = serde_json::from_str(x)
+                .map_err(|e| GraphError::InvalidImport(e.to_string()))
+                .and_then(&map);
+            (i + 1, row)
+        })
+        .collect()
+}
+
+/// External ids may be JSON strings or numbers
+fn external_id_text(value: &Value) -> Result<String, GraphError> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        _ => Err(GraphError::InvalidImport(String::from(
+            "id must be string or number",
+        ))),
+    }
+}
+
+fn parse_csv_nodes(text: &str, mapping: &CsvMapping) -> Result<ParsedRows<NodeRow>, GraphError> {
+    let table = match CsvTable::parse(text, ImportFile::Nodes)? {
+        None => return Ok(Vec::new()),
+        Some(t) => t,
+    };
+    let id = table.optional_column(&mapping.id);
+    let labels = table.required_column(&mapping.labels)?;
+    let properties = table.property_columns(&[Some(labels), id])?;
+
+    Ok(table.map_rows(|cells| {
+        Ok(NodeRow {
+            external_id: id.and_then(|x| non_empty(&cells[x])),
+            labels: cells[labels]
+                .split(mapping.label_separator.as_str())
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty())
+                .collect(),
+            properties: read_properties(&properties, cells)?,
+        })
+    }))
+}
+
+fn parse_csv_bonds(text: &str, mapping: &CsvMapping) -> Result<ParsedRows<BondRow>, GraphError> {
+    let table = match CsvTable::parse(text, ImportFile::Bonds)? {
+        None => return Ok(Vec::new()),
+        Some(t) => t,
+    };
+    let id = table.optional_column(&mapping.id);
+    let label = table.required_column(&mapping.label)?;
+    let src = table.required_column(&mapping.src)?;
+    let dst = table.required_column(&mapping.dst)?;
+    let properties = table.property_columns(&[Some(label), Some(src), Some(dst), id])?;
+
+    Ok(table.map_rows(|cells| {
+        Ok(BondRow {
+            external_id: id.and_then(|x| non_empty(&cells[x])),
+            label: cells[label].clone(),
+            src: cells[src].clone(),
+            dst: cells[dst].clone(),
+            properties: read_properties(&properties, cells)?,
+        })
+    }))
+}
+
+fn non_empty(cell: &str) -> Option<String> {
+    if cell.is_empty() {
+        None
+    } else {
+        Some(cell.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PropertyType {
+    String,
+    Int,
+    Float,
+    Bool,
+    Json,
+}
+
+/// Property column: position, property name and type of its values
+struct PropertyColumn {
+    position: usize,
+    name: String,
+    property_type: PropertyType,
+}
+
+fn read_properties(columns: &[PropertyColumn], cells: &[String]) -> Result<Properties, GraphError> {
+    let mut properties = Properties::new();
+    for column in columns {
+        let cell = &cells[column.position];
+        if cell.is_empty() {
+            continue;
+        }
+        let invalid = || {
+            GraphError::InvalidImport(format!(
+                "value \"{}\" of {} is not {:?}",
+                cell, column.name, column.property_type
+            ))
+        };
+        let value = match column.property_type {
+            PropertyType::String => Value::String(cell.clone()),
+            PropertyType::Int => Value::from(cell.trim().parse::<i64>().map_err(|_| invalid())?),
+            PropertyType::Float => cell
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(Number::from_f64)
+                .map(Value::Number)
+                .ok_or_else(invalid)?,
+            PropertyType::Bool => match cell.trim() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                _ => return Err(invalid()),
+            },
+            PropertyType::Json => serde_json::from_str(cell).map_err(|_| invalid())?,
+        };
+        properties.insert(column.name.clone(), value);
+    }
+    Ok(properties)
+}
+
+/// CSV file split into header and records (RFC 4180: quoted fields may hold
+/// separators, doubled quotes and line breaks)
+struct CsvTable {
+    file: ImportFile,
+    header: Vec<String>,
+    /// (line record starts at, its cells or why it is malformed)
+    records: Vec<(usize, Result<Vec<String>, String>)>,
+}
+
+impl CsvTable {
+    /// None for file without any record, not even header
+    fn parse(text: &str, file: ImportFile) -> Result<Option<Self>, GraphError> {
+        let mut records = parse_csv(text).into_iter();
+        let header = match records.next() {
+            None => return Ok(None),
+            Some((_, Ok(h))) => h,
+            Some((_, Err(message))) => {
+                return Err(GraphError::InvalidImport(format!(
+                    "{:?} header: {}",
+                    file, message
+                )))
+            }
+        };
+        Ok(Some(CsvTable {
+            file,
+            header,
+            records: records.collect(),
+        }))
+    }
+
+    fn optional_column(&self, name: &str) -> Option<usize> {
+        self.header.iter().position(|x| x == name)
+    }
+
+    fn required_column(&self, name: &str) -> Result<usize, GraphError> {
+        self.optional_column(name).ok_or_else(|| {
+            GraphError::InvalidImport(format!("{:?} header has no {} column", self.file, name))
+        })
+    }
+
+    /// Every column not in `special` is property, header is `name` or `name:type`
+    fn property_columns(
+        &self,
+        special: &[Option<usize>],
+    ) -> Result<Vec<PropertyColumn>, GraphError> {
+        let mut columns = Vec::new();
+        for (position, header) in self.header.iter().enumerate() {
+            if special.contains(&Some(position)) {
+                continue;
+            }
+            let (name, property_type) = match header.rsplit_once(':') {
+                None => (header.as_str(), PropertyType::String),
+                Some((name, type_name)) => {
+                    let property_type = match type_name {
+                        "string" => PropertyType::String,
+                        "int" => PropertyType::Int,
+                        "float" => PropertyType::Float,
+                        "bool" => PropertyType::Bool,
+                        "json" => PropertyType::Json,
+                        _ => {
+                            return Err(GraphError::InvalidImport(format!(
+                                "{:?} header: unknown type of column {}",
+                                self.file, header
+                            )))
+                        }
+                    };
+                    (name, property_type)
+                }
+            };
+            columns.push(PropertyColumn {
+                position,
+                name: name.to_string(),
+                property_type,
+            });
+        }
+        Ok(columns)
+    }
+
+    /// Maps records having as many cells as header
+    fn map_rows<T>(&self, map: impl Fn(&[String]) -> Result<T, GraphError>) -> ParsedRows<T> {
+        self.records
+            .iter()
+            .map(|(line, record)| {
+                let row = match record {
+                    Err(message) => Err(GraphError::InvalidImport(message.clone())),
+                    Ok(cells) if cells.len() != self.header.len() => {
+                        Err(GraphError::InvalidImport(format!(
+                            "row has {} cells, header has {}",
+                            cells.len(),
+                            self.header.len()
+                        )))
+                    }
+                    Ok(cells) => map(cells),
+                };
+                (*line, row)
+            })
+            .collect()
+    }
+}
+
+/// Splits CSV text into records of cells, skipping blank lines.
+/// Unterminated quote makes the rest of the text one malformed record.
+fn parse_csv(text: &str) -> Vec<(usize, Result<Vec<String>, String>)> {
+    let mut records = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+
+    while chars.peek().is_some() {
+        let start_line = line;
+        let mut cells = Vec::new();
+        let mut cell = String::new();
+        let mut in_quotes = false;
+        let mut was_quoted = false;
+        let mut error = None;
+
+        loop {
+            let c = match chars.next() {
+                None => {
+                    if in_quotes {
+                        error = Some(String::from("unterminated quoted field"));
+                    }
+                    break;
+                }
+                Some(c) => c,
+            };
+            if in_quotes {
+                match c {
+                    '"' if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        cell.push('"');
+                    }
+                    '"' => in_quotes = false,
+                    '\n' => {
+                        line += 1;
+                        cell.push(c);
+                    }
+                    _ => cell.push(c),
+                }
+                continue;
+            }
+            match c {
+                '"' if cell.is_empty() && !was_quoted => {
+                    in_quotes = true;
+                    was_quoted = true;
+                }
+                ',' => {
+                    cells.push(std::mem::take(&mut cell));
+                    was_quoted = false;
+                }
+                '\r' if chars.peek() == Some(&'\n') => {}
+                '\n' => {
+                    line += 1;
+                    break;
+                }
+                _ => {
+                    if was_quoted && error.is_none() {
+                        error = Some(format!("text after closing quote at line {}", line));
+                    }
+                    cell.push(c);
+                }
+            }
+        }
+        cells.push(cell);
+
+        let is_blank = cells.len() == 1 && cells[0].is_empty() && !was_quoted;
+        if is_blank && error.is_none() {
+            continue;
+        }
+        records.push((start_line, error.map_or(Ok(cells), Err)));
+    }
+    records
+}
@@ -0,0 +1,239 @@
+#[cfg(test)]
+mod import_tests {
+    use crate::core_model::{GraphCollectionFacade, GraphError, InMemoryGraph, Node};
+    use crate::graph_import::{CsvMapping, ImportDTO, ImportFile, ImportFormat};
+    use crate::graph_storage::GraphStorage;
+    use serde_json::{json, Map};
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    fn import(format: ImportFormat, nodes: &str, bonds: &str) -> ImportDTO {
+        ImportDTO {
+            format,
+            nodes: String::from(nodes),
+            bonds: String::from(bonds),
+            mapping: CsvMapping::default(),
+        }
+    }
+
+    fn node_by_name<'a>(graph: &'a InMemoryGraph, name: &str) -> &'a Node {
+        graph
+            .nodes_collection
+            .iter()
+            .find(|x| x.properties.get("name") == Some(&json!(name)))
+            .unwrap()
+    }
+
+    #[test]
+    fn import_csv_passed() {
+        let collection = GraphCollectionFacade::new(Vec::new(), None);
+        let nodes = "id,labels,name,age:int,score:float,admin:bool,tags:json\r\n\
+                     a,Person;Admin,\"Smith, John\",42,1.5,true,\"[\"\"x\"\"]\"\r\n\
+                     b,Person,\"multi\nline\",,,,\r\n";
+        let bonds = "label,src,dst,since:int\nKNOWS,a,b,2010\n";
+
+        let report = collection
+            .import_graph("MyGraph", &import(ImportFormat::Csv, nodes, bonds))
+            .unwrap();
+
+        assert_eq!((2, 1, 0), (report.nodes, report.bonds, report.errors.len()));
+        let graph_lock = collection.get_graph("MyGraph").unwrap();
+        let graph = graph_lock.read().unwrap();
+        let john = node_by_name(&graph, "Smith, John");
+        assert_eq!(vec!["Person", "Admin"], john.labels);
+        assert_eq!(Some(&json!(42)), john.properties.get("age"));
+        assert_eq!(Some(&json!(1.5)), john.properties.get("score"));
+        assert_eq!(Some(&json!(true)), john.properties.get("admin"));
+        assert_eq!(Some(&json!(["x"])), john.properties.get("tags"));
+        // empty cells are left out
+        let multiline = node_by_name(&graph, "multi\nline");
+        assert_eq!(1, multiline.properties.len());
+        let bond = &graph.bonds_collection[0];
+        assert_eq!((john.id, multiline.id), (bond.src, bond.dst));
+        assert_eq!(Some(&json!(2010)), bond.properties.get("since"));
+    }
+
+    #[test]
+    fn import_csv_with_mapping_passed() {
+        let collection = GraphCollectionFacade::new(Vec::new(), None);
+        let mut dto = import(
+            ImportFormat::Csv,
+            "key,kind\n1,Person|Admin\n2,Person\n",
+            "type,from,to\nKNOWS,1,2\n",
+        );
+        dto.mapping = CsvMapping {
+            id: String::from("key"),
+            labels: String::from("kind"),
+            label_separator: String::from("|"),
+            label: String::from("type"),
+            src: String::from("from"),
+            dst: String::from("to"),
+        };
+
+        let report = collection.import_graph("MyGraph", &dto).unwrap();
+
+        assert_eq!((2, 1), (report.nodes, report.bonds));
+        let graph_lock = collection.get_graph("MyGraph").unwrap();
+        let graph = graph_lock.read().unwrap();
+        assert_eq!(vec!["Person", "Admin"], graph.nodes_collection[0].labels);
+        // mapped columns do not become properties
+        assert!(graph.nodes_collection[0].properties.is_empty());
+    }
+
+    #[test]
+    fn import_jsonl_passed() {
+        let collection = GraphCollectionFacade::new(Vec::new(), None);
+        let nodes = r#"{"id": 1, "labels": ["Person"], "properties": {"name": "Ann"}}
+
+{"id": "b", "labels": ["Person"], "properties": {"name": "Bob"}}
+"#;
+        let bonds = r#"{"label": "KNOWS", "src": 1, "dst": "b", "properties": {"since": 2010}}"#;
+
+        let report = collection
+            .import_graph("MyGraph", &import(ImportFormat::Jsonl, nodes, bonds))
+            .unwrap();
+
+        assert_eq!((2, 1, 0), (report.nodes, report.bonds, report.errors.len()));
+        let graph_lock = collection.get_graph("MyGraph").unwrap();
+        let graph = graph_lock.read().unwrap();
+        let bond = &graph.bonds_collection[0];
+        assert_eq!(node_by_name(&graph, "Ann").id, bond.src);
+        assert_eq!(node_by_name(&graph, "Bob").id, bond.dst);
+    }
+
+    #[test]
+    fn import_resolves_node_ids_passed() {
+        let collection = GraphCollectionFacade::new(Vec::new(), None);
+        collection
+            .add_graph(InMemoryGraph::new_graph(String::from("MyGraph")))
+            .unwrap();
+        let existing_id = collection
+            .add_node(
+                "MyGraph",
+                Node {
+                    id: Uuid::default(),
+                    labels: vec![String::from("Person")],
+                    properties: Map::new(),
+                },
+            )
+            .unwrap();
+        let kept_id = Uuid::new_v4();
+        let nodes = format!("id,labels\n{},Person\n", kept_id);
+        let bonds = format!("label,src,dst\nKNOWS,{},{}\n", kept_id, existing_id);
+
+        let report = collection
+            .import_graph("MyGraph", &import(ImportFormat::Csv, &nodes, &bonds))
+            .unwrap();
+
+        assert_eq!((1, 1, 0), (report.nodes, report.bonds, report.errors.len()));
+        let graph_lock = collection.get_graph("MyGraph").unwrap();
+        let graph = graph_lock.read().unwrap();
+        assert!(graph.nodes_id_index.contains_key(&kept_id));
+        assert_eq!(kept_id, graph.bonds_collection[0].src);
+        assert_eq!(existing_id, graph.bonds_collection[0].dst);
+    }
+
+    #[test]
+    fn import_reports_row_errors_passed() {
+        let collection = GraphCollectionFacade::new(Vec::new(), None);
+        let nodes = "id,labels,age:int\n\
+                     a,Person,1\n\
+                     b,,2\n\
+                     c,Person,old\n\
+                     a,Person,3\n\
+                     d,Person\n\
+                     e,Person,5\n";
+        let bonds = "label,src,dst\nKNOWS,a,e\nKNOWS,a,missing\n,a,e\nKNOWS,a,b\n";
+
+        let report = collection
+            .import_graph("MyGraph", &import(ImportFormat::Csv, nodes, bonds))
+            .unwrap();
+
+        assert_eq!((2, 1), (report.nodes, report.bonds));
+        let errors: Vec<(ImportFile, usize, &str)> = report
+            .errors
+            .iter()
+            .map(|x| (x.file, x.line, x.code.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (ImportFile::Nodes, 3, "INVALID_LABEL"),
+                (ImportFile::Nodes, 4, "INVALID_IMPORT"),
+                (ImportFile::Nodes, 5, "DUPLICATE_NODE_ID"),
+                (ImportFile::Nodes, 6, "INVALID_IMPORT"),
+                (ImportFile::Bonds, 3, "INVALID_IMPORT"),
+                (ImportFile::Bonds, 4, "INVALID_LABEL"),
+                (ImportFile::Bonds, 5, "DANGLING_BOND_ENDPOINT"),
+            ],
+            errors
+        );
+        let graph_lock = collection.get_graph("MyGraph").unwrap();
+        let graph = graph_lock.read().unwrap();
+        assert_eq!(2, graph.get_nodes_collection_len());
+        assert_eq!(1, graph.get_bonds_collection_len());
+    }
+
+    #[test]
+    fn import_jsonl_reports_row_errors_passed() {
+        let collection = GraphCollectionFacade::new(Vec::new(), None);
+        let nodes = "{\"id\": 1, \"labels\": [\"Person\"]}\nnot json\n{\"id\": [1], \"labels\": [\"Person\"]}\n";
+
+        let report = collection
+            .import_graph("MyGraph", &import(ImportFormat::Jsonl, nodes, ""))
+            .unwrap();
+
+        assert_eq!(1, report.nodes);
+        let lines: Vec<usize> = report.errors.iter().map(|x| x.line).collect();
+        assert_eq!(vec![2, 3], lines);
+    }
+
+    #[test]
+    fn import_csv_missing_column_failed() {
+        let collection = GraphCollectionFacade::new(Vec::new(), None);
+
+        let missing_column = collection.import_graph(
+            "MyGraph",
+            &import(ImportFormat::Csv, "id,name\n1,Ann\n", ""),
+        );
+        let unknown_type = collection.import_graph(
+            "MyGraph",
+            &import(ImportFormat::Csv, "labels,age:date\nPerson,1\n", ""),
+        );
+
+        assert!(matches!(missing_column, Err(GraphError::InvalidImport(_))));
+        assert!(matches!(unknown_type, Err(GraphError::InvalidImport(_))));
+        assert!(!collection.contains_graph("MyGraph"));
+    }
+
+    #[test]
+    fn import_recovered_from_log_passed() {
+        let graphs_dir =
+            std::env::temp_dir().join(format!("avtandb_import_test_{}", Uuid::new_v4()));
+        let open_collection = || {
+            let storage = GraphStorage::new(graphs_dir.clone()).unwrap();
+            let graphs = storage.recover().unwrap();
+            GraphCollectionFacade::new(graphs, Some(Arc::new(storage)))
+        };
+        {
+            let collection = open_collection();
+            collection
+                .import_graph(
+                    "MyGraph",
+                    &import(
+                        ImportFormat::Csv,
+                        "id,labels\na,Person\nb,Person\n",
+                        "label,src,dst\nKNOWS,a,b\n",
+                    ),
+                )
+                .unwrap();
+        }
+
+        let collection = open_collection();
+
+        let graph_lock = collection.get_graph("MyGraph").unwrap();
+        let graph = graph_lock.read().unwrap();
+        assert_eq!(2, graph.get_nodes_collection_len());
+        assert_eq!(1, graph.get_bonds_collection_len());
+        let _ = std::fs::remove_dir_all(&graphs_dir);
+    }
+}
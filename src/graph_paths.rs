@@ -0,0 +1,346 @@
+use crate::core_model::{BondDirection, GraphError, InMemoryGraph};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use uuid::Uuid;
+
+/// Max number of bonds in path when caller does not limit it
+pub const DEFAULT_MAX_PATH_LENGTH: usize = 10;
+/// Largest max_length caller may ask for, the number of simple paths grows exponentially with it
+pub const MAX_PATH_LENGTH: usize = 50;
+/// Max number of returned paths when caller does not limit it
+pub const DEFAULT_MAX_PATHS: usize = 100;
+
+/// Path as sequence of node ids and bond ids between them, bonds.len() == nodes.len() - 1
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct GraphPath {
+    pub nodes: Vec<Uuid>,
+    pub bonds: Vec<Uuid>,
+    /// Sum of bond weights, set only by weighted search
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathMode {
+    /// Fewest bonds, BFS
+    Shortest,
+    /// Smallest sum of numeric bond property, Dijkstra
+    Weighted,
+    /// All simple paths up to max_length
+    All,
+}
+
+/// Query string of GET /graphs/{name}/paths
+#[derive(Debug, Deserialize)]
+pub struct PathQueryDTO {
+    pub from: Uuid,
+    pub to: Uuid,
+    pub mode: Option<PathMode>,
+    pub weight_property: Option<String>,
+    pub max_length: Option<usize>,
+    pub max_results: Option<usize>,
+    pub direction: Option<BondDirection>,
+}
+
+/// Dijkstra queue entry, ordered so BinaryHeap pops the smallest distance first
+struct WeightedEntry {
+    distance: f64,
+    node_id: Uuid,
+}
+
+impl Ord for WeightedEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
+
+impl PartialOrd for WeightedEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for WeightedEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for WeightedEntry {}
+
+impl InMemoryGraph {
+    /// Runs path query described by DTO
+    pub fn find_paths(&self, query: &PathQueryDTO) -> Result<Vec<GraphPath>, GraphError> {
+        let direction = query.direction.unwrap_or(BondDirection::Outgoing);
+        let found = match query.mode.unwrap_or(PathMode::Shortest) {
+            PathMode::Shortest => self.get_shortest_path(query.from, query.to, direction)?,
+            PathMode::Weighted => {
+                let weight_property = match &query.weight_property {
+                    None => {
+                        return Err(GraphError::InvalidQuery(String::from(
+                            "weighted mode requires weight_property",
+                        )))
+                    }
+                    Some(w) => w,
+                };
+                self.get_weighted_shortest_path(query.from, query.to, weight_property, direction)?
+            }
+            PathMode::All => {
+                return self.get_paths_between_ids(
+                    query.from,
+                    query.to,
+                    query.max_length.unwrap_or(DEFAULT_MAX_PATH_LENGTH),
+                    query.max_results.unwrap_or(DEFAULT_MAX_PATHS),
+                    direction,
+                )
+            }
+        };
+        Ok(found.into_iter().collect())
+    }
+
+    /// Path with fewest bonds, None if finish is unreachable
+    pub fn get_shortest_path(
+        &self,
+        start_id: Uuid,
+        finish_id: Uuid,
+        direction: BondDirection,
+    ) -> Result<Option<GraphPath>, GraphError> {
+        self.check_path_ends(start_id, finish_id)?;
+
+        // node id -> (previous node id, bond id) it was reached by
+        let mut came_from = HashMap::<Uuid, (Uuid, Uuid)>::new();
+        let mut visited = HashSet::<Uuid>::new();
+        visited.insert(start_id);
+        let mut queue = VecDeque::new();
+        queue.push_back(start_id);
+
+        while let Some(curr_id) = queue.pop_front() {
+            if curr_id == finish_id {
+                return Ok(Some(build_path(&came_from, start_id, finish_id, None)));
+            }
+            for (bond, next_id) in self.get_adjacent_bonds(curr_id, direction) {
+                if visited.insert(next_id) {
+                    came_from.insert(next_id, (curr_id, bond.id));
+                    queue.push_back(next_id);
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Path with smallest sum of `weight_property` over its bonds (Dijkstra).
+    /// Bonds without non-negative numeric weight are not traversable.
+    pub fn get_weighted_shortest_path(
+        &self,
+        start_id: Uuid,
+        finish_id: Uuid,
+        weight_property: &str,
+        direction: BondDirection,
+    ) -> Result<Option<GraphPath>, GraphError> {
+        self.check_path_ends(start_id, finish_id)?;
+
+        let mut came_from = HashMap::<Uuid, (Uuid, Uuid)>::new();
+        let mut distances = HashMap::<Uuid, f64>::new();
+        let mut settled = HashSet::<Uuid>::new();
+        let mut heap = BinaryHeap::new();
+        distances.insert(start_id, 0.0);
+        heap.push(WeightedEntry {
+            distance: 0.0,
+            node_id: start_id,
+        });
+
+        while let Some(WeightedEntry { distance, node_id }) = heap.pop() {
+            if !settled.insert(node_id) {
+                continue;
+            }
+            if node_id == finish_id {
+                return Ok(Some(build_path(
+                    &came_from,
+                    start_id,
+                    finish_id,
+                    Some(distance),
+                )));
+            }
+
+            for (bond, next_id) in self.get_adjacent_bonds(node_id, direction) {
+                let weight = match bond
+                    .properties
+                    .get(weight_property)
+                    .and_then(|x| x.as_f64())
+                {
+                    Some(w) if w >= 0.0 => w,
+                    _ => continue,
+                };
+                let next_distance = distance + weight;
+                let is_shorter = match distances.get(&next_id) {
+                    None => true,
+                    Some(d) => next_distance < *d,
+                };
+                if is_shorter && !settled.contains(&next_id) {
+                    distances.insert(next_id, next_distance);
+                    came_from.insert(next_id, (node_id, bond.id));
+                    heap.push(WeightedEntry {
+                        distance: next_distance,
+                        node_id: next_id,
+                    });
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// GETS ALL SIMPLE PATHS (NO REPEATED NODES) WITH AT MOST max_length BONDS,
+    /// STOPPING AFTER max_results PATHS
+    pub fn get_paths_between_ids(
+        &self,
+        start_id: Uuid,
+        finish_id: Uuid,
+        max_length: usize,
+        max_results: usize,
+        direction: BondDirection,
+    ) -> Result<Vec<GraphPath>, GraphError> {
+        if max_length > MAX_PATH_LENGTH {
+            return Err(GraphError::InvalidQuery(format!(
+                "max_length must not exceed {}",
+                MAX_PATH_LENGTH
+            )));
+        }
+        self.check_path_ends(start_id, finish_id)?;
+
+        let mut paths = Vec::new();
+        if start_id == finish_id {
+            paths.push(GraphPath {
+                nodes: vec![start_id],
+                bonds: Vec::new(),
+                weight: None,
+            });
+            return Ok(paths);
+        }
+
+        let mut search = SimplePathsSearch {
+            graph: self,
+            finish_id,
+            max_length,
+            max_results,
+            direction,
+            path_nodes: vec![start_id],
+            path_bonds: Vec::new(),
+            on_path: HashSet::new(),
+            paths,
+        };
+        search.on_path.insert(start_id);
+        search.run();
+        Ok(search.paths)
+    }
+
+    fn check_path_ends(&self, start_id: Uuid, finish_id: Uuid) -> Result<(), GraphError> {
+        for node_id in [start_id, finish_id] {
+            if !self.nodes_id_index.contains_key(&node_id) {
+                return Err(GraphError::NodeNotFound(node_id));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Walks predecessor links back from finish to start
+fn build_path(
+    came_from: &HashMap<Uuid, (Uuid, Uuid)>,
+    start_id: Uuid,
+    finish_id: Uuid,
+    weight: Option<f64>,
+) -> GraphPath {
+    let mut nodes = vec![finish_id];
+    let mut bonds = Vec::new();
+    let mut curr_id = finish_id;
+    while curr_id != start_id {
+        let (prev_id, bond_id) = came_from[&curr_id];
+        bonds.push(bond_id);
+        nodes.push(prev_id);
+        curr_id = prev_id;
+    }
+    nodes.reverse();
+    bonds.reverse();
+    GraphPath {
+        nodes,
+        bonds,
+        weight,
+    }
+}
+
+/// DFS state of get_paths_between_ids
+struct SimplePathsSearch<'a> {
+    graph: &'a InMemoryGraph,
+    finish_id: Uuid,
+    max_length: usize,
+    max_results: usize,
+    direction: BondDirection,
+    path_nodes: Vec<Uuid>,
+    path_bonds: Vec<Uuid>,
+    on_path: HashSet<Uuid>,
+    paths: Vec<GraphPath>,
+}
+
+impl<'a> SimplePathsSearch<'a> {
+    /// Depth first walk with explicit stack, holding one frame of not yet walked
+    /// (bond id, next node id) pairs for every node on path
+    fn run(&mut self) {
+        if self.max_length == 0 {
+            return;
+        }
+        let mut frames = vec![self.adjacent(self.path_nodes[0])];
+        while let Some(frame) = frames.last_mut() {
+            if self.paths.len() >= self.max_results {
+                return;
+            }
+            let (bond_id, next_id) = match frame.pop() {
+                None => {
+                    // every bond of last node is walked, step back unless it is start
+                    frames.pop();
+                    if !frames.is_empty() {
+                        if let Some(node_id) = self.path_nodes.pop() {
+                            self.on_path.remove(&node_id);
+                        }
+                        self.path_bonds.pop();
+                    }
+                    continue;
+                }
+                Some(x) => x,
+            };
+            if self.on_path.contains(&next_id) {
+                continue;
+            }
+
+            if next_id == self.finish_id {
+                let mut nodes = self.path_nodes.clone();
+                nodes.push(next_id);
+                let mut bonds = self.path_bonds.clone();
+                bonds.push(bond_id);
+                self.paths.push(GraphPath {
+                    nodes,
+                    bonds,
+                    weight: None,
+                });
+            } else if self.path_bonds.len() + 1 < self.max_length {
+                self.path_nodes.push(next_id);
+                self.path_bonds.push(bond_id);
+                self.on_path.insert(next_id);
+                frames.push(self.adjacent(next_id));
+            }
+        }
+    }
+
+    /// Adjacent pairs in reverse, so popping them walks bonds in adjacency order
+    fn adjacent(&self, node_id: Uuid) -> Vec<(Uuid, Uuid)> {
+        self.graph
+            .get_adjacent_bonds(node_id, self.direction)
+            .into_iter()
+            .rev()
+            .map(|(bond, next_id)| (bond.id, next_id))
+            .collect()
+    }
+}
@@ -0,0 +1,205 @@
+#[cfg(test)]
+mod path_finding_tests {
+    use crate::core_model;
+    use crate::core_model::{BondDirection, GraphError};
+    use crate::graph_paths::MAX_PATH_LENGTH;
+    use serde_json::{json, Map};
+    use uuid::Uuid;
+
+    /// Diamond with a long cheap detour:
+    /// 0 -> 1 -> 3 (weights 5, 5), 0 -> 2 -> 3 (weights 1, 20), 0 -> 4 -> 5 -> 3 (1, 1, 1)
+    fn build_weighted_graph() -> (core_model::InMemoryGraph, Vec<Uuid>) {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+        let ids: Vec<Uuid> = (0..6).map(|_| Uuid::new_v4()).collect();
+        for id in ids.iter() {
+            in_mem_graph
+                .add_node(core_model::Node {
+                    id: *id,
+                    labels: vec![String::from("city")],
+                    properties: Map::new(),
+                })
+                .unwrap();
+        }
+        let bonds = vec![
+            (0, 1, 5),
+            (1, 3, 5),
+            (0, 2, 1),
+            (2, 3, 20),
+            (0, 4, 1),
+            (4, 5, 1),
+            (5, 3, 1),
+        ];
+        for (src, dst, weight) in bonds {
+            in_mem_graph
+                .add_bond(core_model::Bond {
+                    id: Uuid::default(),
+                    label: String::from("road"),
+                    src: ids[src],
+                    dst: ids[dst],
+                    properties: json!({ "km": weight }).as_object().unwrap().clone(),
+                })
+                .unwrap();
+        }
+        (in_mem_graph, ids)
+    }
+
+    #[test]
+    fn get_shortest_path_passed() {
+        let (in_mem_graph, ids) = build_weighted_graph();
+
+        let path = in_mem_graph
+            .get_shortest_path(ids[0], ids[3], BondDirection::Outgoing)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(vec![ids[0], ids[1], ids[3]], path.nodes);
+        assert_eq!(2, path.bonds.len());
+        assert_eq!(None, path.weight);
+    }
+
+    #[test]
+    fn get_shortest_path_unreachable_passed() {
+        let (in_mem_graph, ids) = build_weighted_graph();
+
+        let outgoing = in_mem_graph
+            .get_shortest_path(ids[3], ids[0], BondDirection::Outgoing)
+            .unwrap();
+        let ingoing = in_mem_graph
+            .get_shortest_path(ids[3], ids[0], BondDirection::Ingoing)
+            .unwrap();
+
+        assert!(outgoing.is_none());
+        assert_eq!(3, ingoing.unwrap().nodes.len());
+    }
+
+    #[test]
+    fn get_shortest_path_missing_node_failed() {
+        let (in_mem_graph, ids) = build_weighted_graph();
+
+        let result = in_mem_graph.get_shortest_path(ids[0], Uuid::new_v4(), BondDirection::Both);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn get_weighted_shortest_path_passed() {
+        let (in_mem_graph, ids) = build_weighted_graph();
+
+        let path = in_mem_graph
+            .get_weighted_shortest_path(ids[0], ids[3], "km", BondDirection::Outgoing)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(vec![ids[0], ids[4], ids[5], ids[3]], path.nodes);
+        assert_eq!(Some(3.0), path.weight);
+        let first_bond = &in_mem_graph.bonds_collection
+            [*in_mem_graph.bonds_id_index.get(&path.bonds[0]).unwrap()];
+        assert_eq!(ids[4], first_bond.dst);
+    }
+
+    #[test]
+    fn get_paths_between_ids_passed() {
+        let (in_mem_graph, ids) = build_weighted_graph();
+
+        let all_paths = in_mem_graph
+            .get_paths_between_ids(ids[0], ids[3], 10, 100, BondDirection::Outgoing)
+            .unwrap();
+        let short_paths = in_mem_graph
+            .get_paths_between_ids(ids[0], ids[3], 2, 100, BondDirection::Outgoing)
+            .unwrap();
+        let capped_paths = in_mem_graph
+            .get_paths_between_ids(ids[0], ids[3], 10, 1, BondDirection::Outgoing)
+            .unwrap();
+
+        assert_eq!(3, all_paths.len());
+        assert_eq!(2, short_paths.len());
+        assert_eq!(1, capped_paths.len());
+        for path in all_paths {
+            assert_eq!(path.nodes.len(), path.bonds.len() + 1);
+            assert_eq!(ids[0], path.nodes[0]);
+            assert_eq!(ids[3], path.nodes[path.nodes.len() - 1]);
+        }
+    }
+
+    #[test]
+    fn get_paths_between_ids_both_directions_are_simple_passed() {
+        let (in_mem_graph, ids) = build_weighted_graph();
+
+        let paths = in_mem_graph
+            .get_paths_between_ids(ids[1], ids[2], 10, 100, BondDirection::Both)
+            .unwrap();
+
+        // 1-0-2, 1-3-2, 1-0-4-5-3-2, 1-3-5-4-0-2
+        assert_eq!(4, paths.len());
+        for path in paths {
+            let mut unique = path.nodes.clone();
+            unique.sort();
+            unique.dedup();
+            assert_eq!(path.nodes.len(), unique.len());
+        }
+    }
+
+    #[test]
+    fn get_paths_between_ids_long_chain_passed() {
+        let mut in_mem_graph = core_model::InMemoryGraph::new_graph("MyGraph".to_string());
+        let ids: Vec<Uuid> = (0..=MAX_PATH_LENGTH).map(|_| Uuid::new_v4()).collect();
+        for id in ids.iter() {
+            in_mem_graph
+                .add_node(core_model::Node {
+                    id: *id,
+                    labels: vec!["Stop".to_string()],
+                    properties: Map::new(),
+                })
+                .unwrap();
+        }
+        for pair in ids.windows(2) {
+            in_mem_graph
+                .add_bond(core_model::Bond {
+                    id: Uuid::new_v4(),
+                    label: "NEXT".to_string(),
+                    src: pair[0],
+                    dst: pair[1],
+                    properties: Map::new(),
+                })
+                .unwrap();
+        }
+
+        let paths = in_mem_graph
+            .get_paths_between_ids(
+                ids[0],
+                ids[MAX_PATH_LENGTH],
+                MAX_PATH_LENGTH,
+                100,
+                BondDirection::Outgoing,
+            )
+            .unwrap();
+        let too_short = in_mem_graph
+            .get_paths_between_ids(
+                ids[0],
+                ids[MAX_PATH_LENGTH],
+                MAX_PATH_LENGTH - 1,
+                100,
+                BondDirection::Outgoing,
+            )
+            .unwrap();
+
+        assert_eq!(1, paths.len());
+        assert_eq!(MAX_PATH_LENGTH, paths[0].bonds.len());
+        assert!(too_short.is_empty());
+    }
+
+    #[test]
+    fn get_paths_between_ids_too_long_failed() {
+        let (in_mem_graph, ids) = build_weighted_graph();
+
+        let result = in_mem_graph.get_paths_between_ids(
+            ids[0],
+            ids[3],
+            MAX_PATH_LENGTH + 1,
+            100,
+            BondDirection::Outgoing,
+        );
+
+        assert!(matches!(result, Err(GraphError::InvalidQuery(_))));
+    }
+}
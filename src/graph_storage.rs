@@ -0,0 +1,410 @@
+use crate::core_model::{Bond, InMemoryGraph, Node, Properties};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Write-ahead log file inside graph directory
+const WAL_FILE_NAME: &str = "wal.log";
+/// Last full snapshot of the graph
+const SNAPSHOT_FILE_NAME: &str = "snapshot.json";
+/// Snapshot is written here first and then renamed over the old one
+const SNAPSHOT_TMP_FILE_NAME: &str = "snapshot.json.tmp";
+/// Number of logged mutations after which graph is snapshotted and its log truncated
+const SNAPSHOT_EVERY_RECORDS: usize = 1000;
+/// Graph directory is renamed with this suffix before removal, so crash mid-removal
+/// never leaves half of a graph to be recovered
+const DELETED_DIR_SUFFIX: &str = ".deleted";
+
+/// Single mutation of a graph, one JSON document per log line
+#[derive(Debug, Serialize, Deserialize)]
+pub enum GraphWalRecord {
+    CreateGraph {
+        name: String,
+    },
+    AddNode(Node),
+    AddBond(Bond),
+    UpdateNodeProperties {
+        id: Uuid,
+        properties: Properties,
+    },
+    CreatePropertyIndex {
+        label: String,
+        property: String,
+    },
+    DropPropertyIndex {
+        label: String,
+        property: String,
+    },
+    DeleteNode {
+        id: Uuid,
+        detach: bool,
+    },
+    DeleteBond {
+        id: Uuid,
+    },
+    /// Committed transaction, written as one line so it is replayed whole or not at all
+    Transaction(Vec<GraphWalRecord>),
+}
+
+/// Persists every graph as `<graphs_dir>/<hex name>/{snapshot.json, wal.log}`
+///
+/// Mutations are appended and fsynced to the graph log while the caller still holds
+/// the graph write lock, so log order always matches the in-memory apply order.
+/// Every log has its own lock, so graphs are logged without contending.
+pub struct GraphStorage {
+    graphs_dir: PathBuf,
+    wals: Mutex<HashMap<String, Arc<Mutex<GraphWal>>>>,
+}
+
+/// Open log of a single graph
+struct GraphWal {
+    file: File,
+    records_since_snapshot: usize,
+}
+
+impl GraphStorage {
+    /// ctor, creates graphs directory if needed
+    pub fn new(graphs_dir: PathBuf) -> io::Result<Self> {
+        fs::create_dir_all(&graphs_dir)?;
+        Ok(GraphStorage {
+            graphs_dir,
+            wals: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Loads every persisted graph: snapshot first, then replay of its log
+    pub fn recover(&self) -> io::Result<Vec<InMemoryGraph>> {
+        let mut graphs = Vec::new();
+        let mut wals = self.wals.lock().unwrap();
+
+        for entry in fs::read_dir(&self.graphs_dir)? {
+            let graph_dir = entry?.path();
+            if !graph_dir.is_dir() {
+                continue;
+            }
+            let dir_name = graph_dir
+                .file_name()
+                .and_then(|x| x.to_str())
+                .unwrap_or_default()
+                .to_string();
+            if dir_name.ends_with(DELETED_DIR_SUFFIX) {
+                fs::remove_dir_all(&graph_dir)?;
+                continue;
+            }
+            let (mut graph, wal) = match GraphStorage::recover_graph(&graph_dir)? {
+                None => continue,
+                Some(r) => r,
+            };
+            // Directory name is authoritative, renames only move the directory
+            if let Some(name) = decode_graph_dir_name(&dir_name) {
+                graph.name = name;
+            }
+            wals.insert(graph.name.clone(), Arc::new(Mutex::new(wal)));
+            graphs.push(graph);
+        }
+
+        Ok(graphs)
+    }
+
+    /// Creates graph directory and logs graph creation
+    pub fn log_create_graph(&self, graph: &InMemoryGraph) -> io::Result<()> {
+        let graph_dir = self.graph_dir(&graph.name);
+        fs::create_dir_all(&graph_dir)?;
+        sync_dir(&self.graphs_dir)?;
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(graph_dir.join(WAL_FILE_NAME))?;
+        let mut wal = GraphWal {
+            file,
+            records_since_snapshot: 0,
+        };
+        wal.append(&GraphWalRecord::CreateGraph {
+            name: graph.name.clone(),
+        })?;
+        sync_dir(&graph_dir)?;
+
+        self.wals
+            .lock()
+            .unwrap()
+            .insert(graph.name.clone(), Arc::new(Mutex::new(wal)));
+        Ok(())
+    }
+
+    /// Moves persisted files of graph aside, so recover no longer loads it, and returns
+    /// their new directory for remove_deleted. No lock is held during file system calls.
+    pub fn delete_graph(&self, graph_name: &str) -> io::Result<PathBuf> {
+        let wal = self.wals.lock().unwrap().remove(graph_name);
+        let graph_dir = self.graph_dir(graph_name);
+        // unique, graph of the same name may be deleted again before removal finishes
+        let mut deleted_dir = graph_dir.clone().into_os_string();
+        deleted_dir.push(format!(
+            ".{}{}",
+            Uuid::new_v4().to_simple(),
+            DELETED_DIR_SUFFIX
+        ));
+
+        if let Err(e) = fs::rename(&graph_dir, &deleted_dir) {
+            if let Some(wal) = wal {
+                self.wals
+                    .lock()
+                    .unwrap()
+                    .insert(graph_name.to_string(), wal);
+            }
+            return Err(e);
+        }
+        sync_dir(&self.graphs_dir)?;
+        Ok(PathBuf::from(deleted_dir))
+    }
+
+    /// Removes directory graph files were moved to by delete_graph
+    pub fn remove_deleted(&self, deleted_dir: &Path) -> io::Result<()> {
+        fs::remove_dir_all(deleted_dir)?;
+        sync_dir(&self.graphs_dir)
+    }
+
+    /// Moves persisted files of graph under new name, single directory rename keeps it atomic
+    pub fn rename_graph(&self, old_name: &str, new_name: &str) -> io::Result<()> {
+        let mut wals = self.wals.lock().unwrap();
+        let new_dir = self.graph_dir(new_name);
+        if new_dir.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "graph directory already exists",
+            ));
+        }
+
+        fs::rename(self.graph_dir(old_name), &new_dir)?;
+        sync_dir(&self.graphs_dir)?;
+        // Open log handle keeps pointing to the moved file
+        if let Some(wal) = wals.remove(old_name) {
+            wals.insert(new_name.to_string(), wal);
+        }
+        Ok(())
+    }
+
+    /// Logs mutation of graph by name, before it is applied
+    pub fn log(&self, graph_name: &str, record: &GraphWalRecord) -> io::Result<()> {
+        let wal = self.get_wal(graph_name)?;
+        let mut wal = wal.lock().unwrap();
+        wal.append(record)
+    }
+
+    /// Writes full snapshot of graph and truncates its log once enough mutations were
+    /// logged, so it has to be called after logged mutation is applied to graph
+    pub fn snapshot_if_due(&self, graph: &InMemoryGraph) -> io::Result<()> {
+        let wal = self.get_wal(&graph.name)?;
+        let mut wal = wal.lock().unwrap();
+        if wal.records_since_snapshot >= SNAPSHOT_EVERY_RECORDS {
+            self.write_snapshot(graph, &mut wal)?;
+        }
+        Ok(())
+    }
+
+    /// Writes full snapshot of graph and truncates its log
+    pub fn snapshot(&self, graph: &InMemoryGraph) -> io::Result<()> {
+        let wal = self.get_wal(&graph.name)?;
+        let mut wal = wal.lock().unwrap();
+        self.write_snapshot(graph, &mut wal)
+    }
+
+    /// Log of graph by name, map lock is released before returning
+    fn get_wal(&self, graph_name: &str) -> io::Result<Arc<Mutex<GraphWal>>> {
+        match self.wals.lock().unwrap().get(graph_name) {
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "graph log not open",
+            )),
+            Some(wal) => Ok(wal.clone()),
+        }
+    }
+
+    fn write_snapshot(&self, graph: &InMemoryGraph, wal: &mut GraphWal) -> io::Result<()> {
+        let graph_dir = self.graph_dir(&graph.name);
+        let tmp_path = graph_dir.join(SNAPSHOT_TMP_FILE_NAME);
+
+        {
+            let tmp_file = File::create(&tmp_path)?;
+            let mut writer = BufWriter::new(&tmp_file);
+            let json = serde_json::to_string(graph)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            writer.write_all(json.as_bytes())?;
+            writer.flush()?;
+            drop(writer);
+            tmp_file.sync_all()?;
+        }
+        fs::rename(&tmp_path, graph_dir.join(SNAPSHOT_FILE_NAME))?;
+        sync_dir(&graph_dir)?;
+
+        // Everything logged so far is in the snapshot now.
+        // Crash before truncation only means some records get replayed twice,
+        // which replay ignores as duplicate ids.
+        wal.file.set_len(0)?;
+        wal.file.sync_all()?;
+        wal.records_since_snapshot = 0;
+        Ok(())
+    }
+
+    /// Restores single graph from its directory, None if directory holds no graph
+    fn recover_graph(graph_dir: &Path) -> io::Result<Option<(InMemoryGraph, GraphWal)>> {
+        let tmp_path = graph_dir.join(SNAPSHOT_TMP_FILE_NAME);
+        if tmp_path.exists() {
+            fs::remove_file(&tmp_path)?;
+        }
+
+        let mut graph = match fs::read_to_string(graph_dir.join(SNAPSHOT_FILE_NAME)) {
+            Ok(json) => {
+                let mut g: InMemoryGraph = serde_json::from_str(&json)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                g.rebuild_indexes();
+                Some(g)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(graph_dir.join(WAL_FILE_NAME))?;
+        let records = read_wal(&mut file)?;
+        let records_since_snapshot = records.len();
+
+        for record in records {
+            replay_record(&mut graph, record);
+        }
+
+        Ok(graph.map(|g| {
+            (
+                g,
+                GraphWal {
+                    file,
+                    records_since_snapshot,
+                },
+            )
+        }))
+    }
+
+    /// Graph names are arbitrary strings, so directory name is their hex encoding
+    fn graph_dir(&self, graph_name: &str) -> PathBuf {
+        let dir_name: String = graph_name.bytes().map(|b| format!("{:02x}", b)).collect();
+        self.graphs_dir.join(dir_name)
+    }
+}
+
+impl GraphWal {
+    /// Appends record and fsyncs it. Record that is not fully written and synced is
+    /// cut off again, so it is not replayed and later records are not lost behind it.
+    fn append(&mut self, record: &GraphWalRecord) -> io::Result<()> {
+        let mut line = serde_json::to_string(record)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        line.push('\n');
+        let len_before = self.file.metadata()?.len();
+        let appended = self
+            .file
+            .write_all(line.as_bytes())
+            .and_then(|_| self.file.sync_data());
+        if let Err(e) = appended {
+            let _ = self.file.set_len(len_before);
+            return Err(e);
+        }
+        self.records_since_snapshot += 1;
+        Ok(())
+    }
+}
+
+/// Applies logged mutation during recovery
+fn replay_record(graph: &mut Option<InMemoryGraph>, record: GraphWalRecord) {
+    if let GraphWalRecord::CreateGraph { name } = record {
+        if graph.is_none() {
+            *graph = Some(InMemoryGraph::new_graph(name));
+        }
+        return;
+    }
+    let g = match graph.as_mut() {
+        None => return,
+        Some(g) => g,
+    };
+    match record {
+        GraphWalRecord::CreateGraph { .. } => {}
+        // Records already contained in snapshot fail as duplicates and are skipped
+        GraphWalRecord::AddNode(node) => {
+            let _ = g.add_node(node);
+        }
+        GraphWalRecord::AddBond(bond) => {
+            let _ = g.add_bond(bond);
+        }
+        GraphWalRecord::UpdateNodeProperties { id, properties } => {
+            let _ = g.update_node_properties(id, properties);
+        }
+        GraphWalRecord::CreatePropertyIndex { label, property } => {
+            let _ = g.create_property_index(label, property);
+        }
+        GraphWalRecord::DropPropertyIndex { label, property } => {
+            let _ = g.drop_property_index(&label, &property);
+        }
+        // Deletes already contained in snapshot fail as missing ids and are skipped
+        GraphWalRecord::DeleteNode { id, detach } => {
+            let _ = g.delete_node(id, detach);
+        }
+        GraphWalRecord::DeleteBond { id } => {
+            let _ = g.delete_bond(id);
+        }
+        GraphWalRecord::Transaction(records) => {
+            for record in records {
+                replay_record(graph, record);
+            }
+        }
+    }
+}
+
+/// Reads all complete records, cutting off torn tail left by a crash mid-write
+fn read_wal(file: &mut File) -> io::Result<Vec<GraphWalRecord>> {
+    let mut records = Vec::new();
+    let mut valid_len: u64 = 0;
+
+    file.seek(SeekFrom::Start(0))?;
+    let mut reader = BufReader::new(&*file);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line)?;
+        if read == 0 || !line.ends_with('\n') {
+            break;
+        }
+        match serde_json::from_str(line.trim_end()) {
+            Ok(r) => records.push(r),
+            Err(_) => break,
+        }
+        valid_len += read as u64;
+    }
+
+    if valid_len < file.metadata()?.len() {
+        file.set_len(valid_len)?;
+        file.sync_all()?;
+    }
+    Ok(records)
+}
+
+/// Reverse of GraphStorage::graph_dir naming, None for foreign directories
+fn decode_graph_dir_name(dir_name: &str) -> Option<String> {
+    if !dir_name.len().is_multiple_of(2) {
+        return None;
+    }
+    let bytes = (0..dir_name.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(dir_name.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Makes directory entries (created or renamed files) durable
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    File::open(dir)?.sync_all()
+}
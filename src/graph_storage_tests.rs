@@ -0,0 +1,411 @@
+#[cfg(test)]
+mod persistence_tests {
+    use crate::core_model;
+    use crate::graph_storage::GraphStorage;
+    use serde_json::{json, Map};
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    fn temp_graphs_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("avtandb_graph_test_{}", Uuid::new_v4()))
+    }
+
+    fn open_collection(graphs_dir: &Path) -> core_model::GraphCollectionFacade {
+        let storage = GraphStorage::new(graphs_dir.to_path_buf()).unwrap();
+        let graphs = storage.recover().unwrap();
+        core_model::GraphCollectionFacade::new(graphs, Some(Arc::new(storage)))
+    }
+
+    fn blue_node() -> core_model::Node {
+        core_model::Node {
+            id: Uuid::default(),
+            labels: vec![String::from("blue")],
+            properties: Map::new(),
+        }
+    }
+
+    fn fill_graph(collection: &core_model::GraphCollectionFacade) -> (Uuid, Uuid, Uuid) {
+        collection
+            .add_graph(core_model::InMemoryGraph::new_graph(String::from(
+                "MyGraph",
+            )))
+            .unwrap();
+        fill_graph_nodes(collection)
+    }
+
+    /// Adds blue and green node bonded together to existing "MyGraph"
+    fn fill_graph_nodes(collection: &core_model::GraphCollectionFacade) -> (Uuid, Uuid, Uuid) {
+        let uuid_1 = collection.add_node("MyGraph", blue_node()).unwrap();
+        let uuid_2 = collection
+            .add_node(
+                "MyGraph",
+                core_model::Node {
+                    id: Uuid::default(),
+                    labels: vec![String::from("green")],
+                    properties: Map::new(),
+                },
+            )
+            .unwrap();
+        let bond_id = collection
+            .add_bond(
+                "MyGraph",
+                core_model::Bond {
+                    id: Uuid::default(),
+                    label: String::from("blue-green"),
+                    src: uuid_1,
+                    dst: uuid_2,
+                    properties: Map::new(),
+                },
+            )
+            .unwrap();
+        (uuid_1, uuid_2, bond_id)
+    }
+
+    fn assert_graph_restored(
+        collection: &core_model::GraphCollectionFacade,
+        uuid_1: Uuid,
+        uuid_2: Uuid,
+        bond_id: Uuid,
+    ) {
+        assert_eq!(1, collection.list_graphs().len());
+        let graph_lock = collection.get_graph("MyGraph").unwrap();
+        let graph = graph_lock.read().unwrap();
+        assert_eq!("MyGraph", graph.name);
+        assert_eq!(2, graph.get_nodes_collection_len());
+        assert_eq!(1, graph.get_bonds_collection_len());
+        assert_eq!(0, *graph.nodes_id_index.get(&uuid_1).unwrap());
+        assert_eq!(1, *graph.nodes_id_index.get(&uuid_2).unwrap());
+        assert_eq!(0, *graph.bonds_id_index.get(&bond_id).unwrap());
+    }
+
+    #[test]
+    fn recover_from_log_passed() {
+        let graphs_dir = temp_graphs_dir();
+        let (uuid_1, uuid_2, bond_id) = fill_graph(&open_collection(&graphs_dir));
+
+        let collection = open_collection(&graphs_dir);
+
+        assert_graph_restored(&collection, uuid_1, uuid_2, bond_id);
+        let _ = std::fs::remove_dir_all(graphs_dir);
+    }
+
+    #[test]
+    fn recover_from_snapshot_and_log_passed() {
+        let graphs_dir = temp_graphs_dir();
+        let (uuid_1, uuid_2, bond_id) = {
+            let collection = open_collection(&graphs_dir);
+            collection
+                .add_graph(core_model::InMemoryGraph::new_graph(String::from(
+                    "MyGraph",
+                )))
+                .unwrap();
+            let uuid_1 = collection
+                .add_node(
+                    "MyGraph",
+                    core_model::Node {
+                        id: Uuid::default(),
+                        labels: vec![String::from("blue")],
+                        properties: Map::new(),
+                    },
+                )
+                .unwrap();
+            collection.snapshot_graph("MyGraph").unwrap();
+            assert_eq!(
+                core_model::GraphError::GraphNotFound(String::from("Nope")),
+                collection.snapshot_graph("Nope").unwrap_err()
+            );
+            let uuid_2 = collection
+                .add_node(
+                    "MyGraph",
+                    core_model::Node {
+                        id: Uuid::default(),
+                        labels: vec![String::from("green")],
+                        properties: Map::new(),
+                    },
+                )
+                .unwrap();
+            let bond_id = collection
+                .add_bond(
+                    "MyGraph",
+                    core_model::Bond {
+                        id: Uuid::default(),
+                        label: String::from("blue-green"),
+                        src: uuid_1,
+                        dst: uuid_2,
+                        properties: Map::new(),
+                    },
+                )
+                .unwrap();
+            (uuid_1, uuid_2, bond_id)
+        };
+
+        let collection = open_collection(&graphs_dir);
+
+        assert_graph_restored(&collection, uuid_1, uuid_2, bond_id);
+        let _ = std::fs::remove_dir_all(graphs_dir);
+    }
+
+    #[test]
+    fn recover_with_torn_log_tail_passed() {
+        let graphs_dir = temp_graphs_dir();
+        let (uuid_1, uuid_2, bond_id) = fill_graph(&open_collection(&graphs_dir));
+
+        // simulate crash in the middle of appending a record
+        let graph_dir = std::fs::read_dir(&graphs_dir)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let mut wal_file = OpenOptions::new()
+            .append(true)
+            .open(graph_dir.join("wal.log"))
+            .unwrap();
+        wal_file.write_all(b"{\"AddNode\":{\"id\":\"550e").unwrap();
+        drop(wal_file);
+
+        let collection = open_collection(&graphs_dir);
+
+        assert_graph_restored(&collection, uuid_1, uuid_2, bond_id);
+        let _ = std::fs::remove_dir_all(graphs_dir);
+    }
+
+    #[test]
+    fn add_graph_with_existing_name_failed() {
+        let graphs_dir = temp_graphs_dir();
+        let collection = open_collection(&graphs_dir);
+        fill_graph(&collection);
+
+        let adding_result = collection.add_graph(core_model::InMemoryGraph::new_graph(
+            String::from("MyGraph"),
+        ));
+
+        assert!(adding_result.is_err());
+        let _ = std::fs::remove_dir_all(graphs_dir);
+    }
+
+    #[test]
+    fn recover_property_indexes_passed() {
+        let graphs_dir = temp_graphs_dir();
+        let (uuid_1, _, _) = {
+            let collection = open_collection(&graphs_dir);
+            let ids = fill_graph(&collection);
+            collection
+                .create_property_index("MyGraph", String::from("blue"), String::from("size"))
+                .unwrap();
+            collection
+                .update_node_properties(
+                    "MyGraph",
+                    ids.0,
+                    json!({"size": 3}).as_object().unwrap().clone(),
+                )
+                .unwrap();
+            ids
+        };
+
+        let collection = open_collection(&graphs_dir);
+        let graph_lock = collection.get_graph("MyGraph").unwrap();
+        let graph = graph_lock.read().unwrap();
+        let nodes = graph
+            .get_nodes_by_property_value("blue", "size", json!(3))
+            .unwrap();
+
+        assert_eq!(1, graph.get_property_indexes().len());
+        assert_eq!(1, nodes.len());
+        assert_eq!(uuid_1, nodes[0].id);
+        let _ = std::fs::remove_dir_all(&graphs_dir);
+    }
+
+    #[test]
+    fn recover_deletions_passed() {
+        let graphs_dir = temp_graphs_dir();
+        let (uuid_1, uuid_2) = {
+            let collection = open_collection(&graphs_dir);
+            let (uuid_1, uuid_2, bond_id) = fill_graph(&collection);
+            assert!(collection.delete_node("MyGraph", uuid_1, false).is_err());
+            collection.delete_bond("MyGraph", bond_id).unwrap();
+            collection.delete_node("MyGraph", uuid_1, false).unwrap();
+            (uuid_1, uuid_2)
+        };
+
+        let collection = open_collection(&graphs_dir);
+        let graph_lock = collection.get_graph("MyGraph").unwrap();
+        let graph = graph_lock.read().unwrap();
+
+        assert_eq!(1, graph.get_nodes_collection_len());
+        assert_eq!(0, graph.get_bonds_collection_len());
+        assert!(!graph.nodes_id_index.contains_key(&uuid_1));
+        assert_eq!(0, *graph.nodes_id_index.get(&uuid_2).unwrap());
+        let _ = std::fs::remove_dir_all(&graphs_dir);
+    }
+
+    #[test]
+    fn delete_graph_removes_files_passed() {
+        let graphs_dir = temp_graphs_dir();
+        {
+            let collection = open_collection(&graphs_dir);
+            fill_graph(&collection);
+            collection.delete_graph("MyGraph").unwrap();
+            assert!(collection.delete_graph("MyGraph").is_err());
+        }
+
+        let collection = open_collection(&graphs_dir);
+
+        assert_eq!(0, collection.list_graphs().len());
+        assert_eq!(0, std::fs::read_dir(&graphs_dir).unwrap().count());
+        let _ = std::fs::remove_dir_all(&graphs_dir);
+    }
+
+    #[test]
+    fn delete_graph_waits_with_registry_unlocked_passed() {
+        let graphs_dir = temp_graphs_dir();
+        let collection = Arc::new(open_collection(&graphs_dir));
+        fill_graph(&collection);
+        let graph_lock = collection.get_graph("MyGraph").unwrap();
+        let reading = graph_lock.read().unwrap();
+
+        let deleting = {
+            let collection = collection.clone();
+            std::thread::spawn(move || collection.delete_graph("MyGraph"))
+        };
+        std::thread::sleep(Duration::from_millis(50));
+        // delete waits for the reader, other graphs stay reachable meanwhile
+        assert!(collection.in_memory_graph_collection.try_write().is_ok());
+        drop(reading);
+        deleting.join().unwrap().unwrap();
+        fill_graph(&collection);
+        drop(collection);
+
+        let collection = open_collection(&graphs_dir);
+
+        let graphs = collection.list_graphs();
+        assert_eq!(1, graphs.len());
+        assert_eq!((2, 1), (graphs[0].nodes, graphs[0].bonds));
+        let _ = std::fs::remove_dir_all(&graphs_dir);
+    }
+
+    #[test]
+    fn rename_graph_recovered_under_new_name_passed() {
+        let graphs_dir = temp_graphs_dir();
+        let (uuid_1, uuid_2, bond_id) = {
+            let collection = open_collection(&graphs_dir);
+            collection
+                .add_graph(core_model::InMemoryGraph::new_graph(String::from(
+                    "OldGraph",
+                )))
+                .unwrap();
+            collection
+                .rename_graph("OldGraph", String::from("MyGraph"))
+                .unwrap();
+            // mutations after rename land in moved log
+            let ids = fill_graph_nodes(&collection);
+            assert!(collection.add_node("OldGraph", blue_node()).is_err());
+            ids
+        };
+
+        let collection = open_collection(&graphs_dir);
+
+        assert_graph_restored(&collection, uuid_1, uuid_2, bond_id);
+        let _ = std::fs::remove_dir_all(&graphs_dir);
+    }
+
+    #[test]
+    fn long_graph_name_failed() {
+        let graphs_dir = temp_graphs_dir();
+        let collection = open_collection(&graphs_dir);
+        let longest = "g".repeat(core_model::MAX_GRAPH_NAME_LEN);
+        let too_long = "g".repeat(core_model::MAX_GRAPH_NAME_LEN + 1);
+
+        assert_eq!(
+            Err(core_model::GraphError::InvalidGraphName),
+            collection.add_graph(core_model::InMemoryGraph::new_graph(too_long.clone()))
+        );
+        collection
+            .add_graph(core_model::InMemoryGraph::new_graph(longest.clone()))
+            .unwrap();
+        assert_eq!(
+            Err(core_model::GraphError::InvalidGraphName),
+            collection.rename_graph(&longest, too_long)
+        );
+
+        let collection = open_collection(&graphs_dir);
+        assert!(collection.contains_graph(&longest));
+        let _ = std::fs::remove_dir_all(&graphs_dir);
+    }
+
+    #[test]
+    fn rename_graph_to_existing_name_failed() {
+        let graphs_dir = temp_graphs_dir();
+        let collection = open_collection(&graphs_dir);
+        fill_graph(&collection);
+        collection
+            .add_graph(core_model::InMemoryGraph::new_graph(String::from(
+                "OtherGraph",
+            )))
+            .unwrap();
+
+        let renaming_result = collection.rename_graph("OtherGraph", String::from("MyGraph"));
+
+        assert!(renaming_result.is_err());
+        assert!(collection
+            .rename_graph("OtherGraph", String::from(" "))
+            .is_err());
+        assert!(collection
+            .rename_graph("NoGraph", String::from("New"))
+            .is_err());
+        assert_eq!(
+            vec![
+                core_model::GraphInfoDTO {
+                    name: String::from("MyGraph"),
+                    nodes: 2,
+                    bonds: 1,
+                },
+                core_model::GraphInfoDTO {
+                    name: String::from("OtherGraph"),
+                    nodes: 0,
+                    bonds: 0,
+                },
+            ],
+            collection.list_graphs()
+        );
+        let _ = std::fs::remove_dir_all(&graphs_dir);
+    }
+
+    #[test]
+    fn failed_log_leaves_graph_unchanged_failed() {
+        let graphs_dir = temp_graphs_dir();
+        let storage = GraphStorage::new(graphs_dir.clone()).unwrap();
+        // graph never logged its creation, so storage has no log to append to
+        let collection = core_model::GraphCollectionFacade::new(
+            vec![core_model::InMemoryGraph::new_graph(String::from(
+                "MyGraph",
+            ))],
+            Some(Arc::new(storage)),
+        );
+        let feed = collection.graph_feed("MyGraph").unwrap();
+
+        assert!(matches!(
+            collection.add_node("MyGraph", blue_node()),
+            Err(core_model::GraphError::Storage(_))
+        ));
+        assert!(collection
+            .run_transaction("MyGraph", |tx| tx.add_node(blue_node()))
+            .is_err());
+        let mut transaction = collection.begin_transaction("MyGraph").unwrap();
+        collection
+            .continue_transaction(&mut transaction, |tx| tx.add_node(blue_node()))
+            .unwrap();
+        assert!(collection.commit_transaction(transaction).is_err());
+
+        let graph = collection.get_graph("MyGraph").unwrap();
+        let graph = graph.read().unwrap();
+        assert_eq!(0, graph.nodes_collection.len());
+        assert_eq!(0, graph.version);
+        assert_eq!(0, feed.subscribe(None, Vec::new()).unwrap().1);
+        let _ = std::fs::remove_dir_all(&graphs_dir);
+    }
+}
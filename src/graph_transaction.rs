@@ -0,0 +1,486 @@
+use crate::core_model::{
+    Bond, CreateBondDTO, CreateNodeDTO, GraphCollectionFacade, GraphError, InMemoryGraph, Node,
+    Properties,
+};
+use crate::graph_feed::GraphChange;
+use crate::graph_storage::GraphWalRecord;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Transactions opened over HTTP and not used for this long are rolled back
+pub const TRANSACTION_TIMEOUT: Duration = Duration::from_secs(60);
+/// Transactions open over HTTP at once, each one holds its operations in memory
+pub const MAX_OPEN_TRANSACTIONS: usize = 64;
+
+/// Single mutation of transaction, `op` names it
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum TransactionOperation {
+    AddNode(CreateNodeDTO),
+    AddBond(CreateBondDTO),
+    UpdateNodeProperties {
+        id: Uuid,
+        properties: Properties,
+    },
+    DeleteNode {
+        id: Uuid,
+        #[serde(default)]
+        detach: bool,
+    },
+    DeleteBond {
+        id: Uuid,
+    },
+}
+
+/// Id of transaction opened over HTTP
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransactionDTO {
+    pub id: Uuid,
+}
+
+/// Ids of nodes and bonds touched by operations, in operation order
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransactionOperationsResultDTO {
+    pub ids: Vec<Uuid>,
+}
+
+/// Batch of mutations over one graph, applied all together on commit or not at all.
+///
+/// Transaction keeps only its own operations, the graph is never copied: continuing
+/// it applies them to the graph under its write lock and an undo log takes them back
+/// before the lock is released. Anything committed to the graph after begin fails the
+/// transaction with TransactionConflict, so it sees the graph as of begin plus its own
+/// writes and nothing committed later (snapshot isolation). Rollback is dropping the
+/// transaction.
+pub struct GraphTransaction {
+    graph_name: String,
+    graph_lock: Arc<RwLock<InMemoryGraph>>,
+    base_version: u64,
+    /// Applied operations, logged as single record on commit
+    records: Vec<GraphWalRecord>,
+}
+
+/// Operations of transaction applied to write locked graph, with undo log
+/// to take them back
+pub struct TransactionScope<'a> {
+    graph: &'a mut InMemoryGraph,
+    records: Vec<GraphWalRecord>,
+    /// Node and bond changes of operations, published on commit
+    changes: Vec<GraphChange>,
+    undo_log: Vec<UndoStep>,
+}
+
+/// Reverts one applied operation
+enum UndoStep {
+    DeleteNode(Uuid),
+    DeleteBond(Uuid),
+    RestoreProperties(Uuid, Properties),
+    /// Node deleted together with its bonds
+    RestoreNode(Node, Vec<Bond>),
+    RestoreBond(Bond),
+}
+
+impl GraphTransaction {
+    fn new(graph_lock: Arc<RwLock<InMemoryGraph>>, graph: &InMemoryGraph) -> Self {
+        GraphTransaction {
+            graph_name: graph.name.clone(),
+            graph_lock,
+            base_version: graph.version,
+            records: Vec::new(),
+        }
+    }
+}
+
+impl<'a> TransactionScope<'a> {
+    fn new(graph: &'a mut InMemoryGraph) -> Self {
+        TransactionScope {
+            graph,
+            records: Vec::new(),
+            changes: Vec::new(),
+            undo_log: Vec::new(),
+        }
+    }
+
+    /// Graph as seen by transaction
+    pub fn graph(&self) -> &InMemoryGraph {
+        self.graph
+    }
+
+    /// Adds Node, returning id of stored node
+    pub fn add_node(&mut self, node: Node) -> Result<Uuid, GraphError> {
+        self.graph.add_node(node)?;
+        let stored_node =
+            self.graph.nodes_collection[self.graph.nodes_collection.len() - 1].clone();
+        let id = stored_node.id;
+        self.undo_log.push(UndoStep::DeleteNode(id));
+        self.changes.push(GraphChange::NodeCreated {
+            node: stored_node.clone(),
+        });
+        self.records.push(GraphWalRecord::AddNode(stored_node));
+        Ok(id)
+    }
+
+    /// Adds Bond, returning id of stored bond
+    pub fn add_bond(&mut self, bond: Bond) -> Result<Uuid, GraphError> {
+        self.graph.add_bond(bond)?;
+        let stored_bond =
+            self.graph.bonds_collection[self.graph.bonds_collection.len() - 1].clone();
+        let id = stored_bond.id;
+        self.undo_log.push(UndoStep::DeleteBond(id));
+        self.changes.push(GraphChange::BondCreated {
+            bond: stored_bond.clone(),
+        });
+        self.records.push(GraphWalRecord::AddBond(stored_bond));
+        Ok(id)
+    }
+
+    /// Replaces properties of Node
+    pub fn update_node_properties(
+        &mut self,
+        node_id: Uuid,
+        properties: Properties,
+    ) -> Result<(), GraphError> {
+        self.graph.check_node_properties(node_id, &properties)?;
+        let old_properties = self.graph.nodes_collection[self.graph.nodes_id_index[&node_id]]
+            .properties
+            .clone();
+        self.graph
+            .update_node_properties(node_id, properties.clone())?;
+        self.undo_log
+            .push(UndoStep::RestoreProperties(node_id, old_properties));
+        self.records.push(GraphWalRecord::UpdateNodeProperties {
+            id: node_id,
+            properties,
+        });
+        Ok(())
+    }
+
+    /// Deletes Node, `detach` deletes its bonds as well
+    pub fn delete_node(&mut self, node_id: Uuid, detach: bool) -> Result<(), GraphError> {
+        let (node, bonds) = self.graph.delete_node_with_bonds(node_id, detach)?;
+        self.changes.extend(
+            bonds
+                .iter()
+                .map(|bond| GraphChange::BondDeleted { bond: bond.clone() }),
+        );
+        self.changes
+            .push(GraphChange::NodeDeleted { node: node.clone() });
+        self.undo_log.push(UndoStep::RestoreNode(node, bonds));
+        self.records.push(GraphWalRecord::DeleteNode {
+            id: node_id,
+            detach,
+        });
+        Ok(())
+    }
+
+    /// Deletes Bond
+    pub fn delete_bond(&mut self, bond_id: Uuid) -> Result<(), GraphError> {
+        let bond = self.graph.delete_bond(bond_id)?;
+        self.changes
+            .push(GraphChange::BondDeleted { bond: bond.clone() });
+        self.undo_log.push(UndoStep::RestoreBond(bond));
+        self.records
+            .push(GraphWalRecord::DeleteBond { id: bond_id });
+        Ok(())
+    }
+
+    /// Applies operation, returning id of node or bond it touched.
+    /// Failed operation leaves transaction unchanged.
+    pub fn apply(&mut self, operation: TransactionOperation) -> Result<Uuid, GraphError> {
+        match operation {
+            TransactionOperation::AddNode(dto) => self.add_node(Node::from(dto)),
+            TransactionOperation::AddBond(dto) => self.add_bond(Bond::from(dto)),
+            TransactionOperation::UpdateNodeProperties { id, properties } => {
+                self.update_node_properties(id, properties).map(|_| id)
+            }
+            TransactionOperation::DeleteNode { id, detach } => {
+                self.delete_node(id, detach).map(|_| id)
+            }
+            TransactionOperation::DeleteBond { id } => self.delete_bond(id).map(|_| id),
+        }
+    }
+
+    /// Applies operation recorded earlier by transaction
+    fn replay(&mut self, record: GraphWalRecord) -> Result<(), GraphError> {
+        match record {
+            GraphWalRecord::AddNode(node) => self.add_node(node).map(|_| ()),
+            GraphWalRecord::AddBond(bond) => self.add_bond(bond).map(|_| ()),
+            GraphWalRecord::UpdateNodeProperties { id, properties } => {
+                self.update_node_properties(id, properties)
+            }
+            GraphWalRecord::DeleteNode { id, detach } => self.delete_node(id, detach),
+            GraphWalRecord::DeleteBond { id } => self.delete_bond(id),
+            // transactions record node and bond operations only
+            _ => Ok(()),
+        }
+    }
+
+    /// Takes all operations back in reverse order, returning their records
+    fn rollback(mut self) -> Vec<GraphWalRecord> {
+        while let Some(step) = self.undo_log.pop() {
+            let undone = match step {
+                UndoStep::DeleteNode(id) => self.graph.delete_node(id, false).map(|_| ()),
+                UndoStep::DeleteBond(id) => self.graph.delete_bond(id).map(|_| ()),
+                UndoStep::RestoreProperties(id, properties) => {
+                    self.graph.update_node_properties(id, properties)
+                }
+                UndoStep::RestoreNode(node, bonds) => {
+                    let restored = self.graph.add_node(node);
+                    bonds
+                        .into_iter()
+                        .fold(restored, |x, bond| x.and(self.graph.add_bond(bond)))
+                }
+                UndoStep::RestoreBond(bond) => self.graph.add_bond(bond),
+            };
+            // every step reverts operation applied right before it
+            undone.expect("undo of applied transaction operation");
+        }
+        self.records
+    }
+}
+
+impl GraphCollectionFacade {
+    /// Begins transaction over graph by name
+    pub fn begin_transaction(&self, graph_name: &str) -> Result<GraphTransaction, GraphError> {
+        let graph_lock = match self.get_graph(graph_name) {
+            None => return Err(GraphError::GraphNotFound(graph_name.to_string())),
+            Some(g) => g,
+        };
+        let graph = graph_lock.read().unwrap();
+        Ok(GraphTransaction::new(graph_lock.clone(), &graph))
+    }
+
+    /// Runs `operations` inside transaction, reads see its earlier operations.
+    /// Operations that succeeded stay in transaction even if `operations` fails.
+    pub fn continue_transaction<T>(
+        &self,
+        transaction: &mut GraphTransaction,
+        operations: impl FnOnce(&mut TransactionScope) -> Result<T, GraphError>,
+    ) -> Result<T, GraphError> {
+        let mut graph = transaction.graph_lock.write().unwrap();
+        self.check_transaction_graph(transaction, &graph)?;
+
+        let mut scope = TransactionScope::new(&mut graph);
+        let mut replayed = Ok(());
+        // replayed operations record themselves in scope again
+        for record in std::mem::take(&mut transaction.records) {
+            replayed = scope.replay(record);
+            if replayed.is_err() {
+                break;
+            }
+        }
+        let result = replayed.and_then(|_| operations(&mut scope));
+        transaction.records = scope.rollback();
+        result
+    }
+
+    /// Makes all operations of transaction visible at once and logs them as one record
+    pub fn commit_transaction(&self, transaction: GraphTransaction) -> Result<(), GraphError> {
+        let mut graph = transaction.graph_lock.write().unwrap();
+        self.check_transaction_graph(&transaction, &graph)?;
+        if transaction.records.is_empty() {
+            return Ok(());
+        }
+
+        // operations were applied to this very version before, so they apply again
+        // after logging and graph is left as it was if logging fails
+        let record = GraphWalRecord::Transaction(transaction.records);
+        self.log(&graph.name, &record)?;
+        let changes = graph.apply_record(record)?;
+        graph.version += 1;
+        self.snapshot_if_due(&graph);
+        graph.change_feed.publish(changes);
+        Ok(())
+    }
+
+    /// Runs `operations` as transaction holding graph write lock the whole time,
+    /// so it never conflicts. Nothing is applied if `operations` fails.
+    pub fn run_transaction<T>(
+        &self,
+        graph_name: &str,
+        operations: impl FnOnce(&mut TransactionScope) -> Result<T, GraphError>,
+    ) -> Result<T, GraphError> {
+        let graph_lock = match self.get_graph(graph_name) {
+            None => return Err(GraphError::GraphNotFound(graph_name.to_string())),
+            Some(g) => g,
+        };
+        let mut graph = graph_lock.write().unwrap();
+        // deleted or renamed while waiting for the lock
+        if !self.is_registered(&graph.name, &graph_lock) {
+            return Err(GraphError::GraphNotFound(graph_name.to_string()));
+        }
+        let mut scope = TransactionScope::new(&mut graph);
+        let result = match operations(&mut scope) {
+            Ok(x) => x,
+            Err(e) => {
+                scope.rollback();
+                return Err(e);
+            }
+        };
+        if scope.records.is_empty() {
+            return Ok(result);
+        }
+
+        // nobody sees operations before the write lock is released,
+        // so they are taken back if logging fails
+        let record = GraphWalRecord::Transaction(std::mem::take(&mut scope.records));
+        if let Err(e) = self.log(&scope.graph.name, &record) {
+            scope.rollback();
+            return Err(e);
+        }
+        let changes = std::mem::take(&mut scope.changes);
+        drop(scope);
+        graph.version += 1;
+        self.snapshot_if_due(&graph);
+        graph.change_feed.publish(changes);
+        Ok(result)
+    }
+
+    /// Fails if graph of transaction was deleted, or renamed and its name taken by another
+    /// one, or if anything was committed to it after begin. Called with graph write locked.
+    fn check_transaction_graph(
+        &self,
+        transaction: &GraphTransaction,
+        graph: &InMemoryGraph,
+    ) -> Result<(), GraphError> {
+        let graph_name = transaction.graph_name.clone();
+        if !self.is_registered(&graph_name, &transaction.graph_lock) {
+            return Err(match self.contains_graph(&graph_name) {
+                true => GraphError::TransactionConflict(graph_name),
+                false => GraphError::GraphNotFound(graph_name),
+            });
+        }
+        if graph.version != transaction.base_version {
+            return Err(GraphError::TransactionConflict(graph_name));
+        }
+        Ok(())
+    }
+}
+
+/// Transactions opened over HTTP, kept between requests by id
+pub struct TransactionRegistry {
+    transactions: Mutex<HashMap<Uuid, RegisteredTransaction>>,
+    /// Transaction unused for this long is rolled back
+    timeout: Duration,
+    max_open: usize,
+}
+
+struct RegisteredTransaction {
+    graph_name: String,
+    open: Arc<Mutex<OpenTransaction>>,
+}
+
+struct OpenTransaction {
+    /// Taken out on commit or rollback
+    transaction: Option<GraphTransaction>,
+    last_used: Instant,
+}
+
+impl Default for TransactionRegistry {
+    fn default() -> Self {
+        TransactionRegistry::new(TRANSACTION_TIMEOUT, MAX_OPEN_TRANSACTIONS)
+    }
+}
+
+impl TransactionRegistry {
+    /// ctor
+    pub fn new(timeout: Duration, max_open: usize) -> Self {
+        TransactionRegistry {
+            transactions: Mutex::new(HashMap::new()),
+            timeout,
+            max_open,
+        }
+    }
+
+    /// Begins transaction over graph and registers it, rolling back expired ones.
+    /// Fails with TooManyTransactions while max_open transactions are still open.
+    pub fn begin(
+        &self,
+        graph_collection: &GraphCollectionFacade,
+        graph_name: &str,
+    ) -> Result<Uuid, GraphError> {
+        let mut transactions = self.transactions.lock().unwrap();
+        transactions.retain(|_, x| !self.is_expired(x));
+        if transactions.len() >= self.max_open {
+            return Err(GraphError::TooManyTransactions(self.max_open));
+        }
+        let transaction = graph_collection.begin_transaction(graph_name)?;
+        let id = Uuid::new_v4();
+        transactions.insert(
+            id,
+            RegisteredTransaction {
+                graph_name: graph_name.to_string(),
+                open: Arc::new(Mutex::new(OpenTransaction {
+                    transaction: Some(transaction),
+                    last_used: Instant::now(),
+                })),
+            },
+        );
+        Ok(id)
+    }
+
+    /// Runs handler inside open transaction of graph
+    pub fn with_transaction<T>(
+        &self,
+        graph_collection: &GraphCollectionFacade,
+        graph_name: &str,
+        id: Uuid,
+        handler: impl FnOnce(&mut TransactionScope) -> Result<T, GraphError>,
+    ) -> Result<T, GraphError> {
+        let open = self.get(graph_name, id)?;
+        let mut open = open.lock().unwrap();
+        open.last_used = Instant::now();
+        match open.transaction.as_mut() {
+            None => Err(GraphError::TransactionNotFound(id)),
+            Some(transaction) => graph_collection.continue_transaction(transaction, handler),
+        }
+    }
+
+    /// Unregisters transaction and commits it
+    pub fn commit(
+        &self,
+        graph_collection: &GraphCollectionFacade,
+        graph_name: &str,
+        id: Uuid,
+    ) -> Result<(), GraphError> {
+        let transaction = self.remove(graph_name, id)?;
+        graph_collection.commit_transaction(transaction)
+    }
+
+    /// Unregisters transaction, dropping its operations
+    pub fn rollback(&self, graph_name: &str, id: Uuid) -> Result<(), GraphError> {
+        self.remove(graph_name, id).map(|_| ())
+    }
+
+    /// Open transaction by id, expired one is rolled back and not found
+    fn get(&self, graph_name: &str, id: Uuid) -> Result<Arc<Mutex<OpenTransaction>>, GraphError> {
+        let mut transactions = self.transactions.lock().unwrap();
+        match transactions.get(&id) {
+            Some(x) if self.is_expired(x) => {
+                transactions.remove(&id);
+                Err(GraphError::TransactionNotFound(id))
+            }
+            Some(x) if x.graph_name == graph_name => Ok(x.open.clone()),
+            _ => Err(GraphError::TransactionNotFound(id)),
+        }
+    }
+
+    fn is_expired(&self, transaction: &RegisteredTransaction) -> bool {
+        match transaction.open.try_lock() {
+            Ok(open) => open.last_used.elapsed() >= self.timeout,
+            // in use right now
+            Err(_) => false,
+        }
+    }
+
+    /// Takes transaction out of registry, waiting for request still using it
+    fn remove(&self, graph_name: &str, id: Uuid) -> Result<GraphTransaction, GraphError> {
+        let open = self.get(graph_name, id)?;
+        self.transactions.lock().unwrap().remove(&id);
+        let transaction = open.lock().unwrap().transaction.take();
+        transaction.ok_or(GraphError::TransactionNotFound(id))
+    }
+}
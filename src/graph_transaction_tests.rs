@@ -0,0 +1,334 @@
+#[cfg(test)]
+mod transaction_tests {
+    use crate::core_model::{Bond, GraphCollectionFacade, GraphError, InMemoryGraph, Node};
+    use crate::graph_storage::GraphStorage;
+    use crate::graph_transaction::{TransactionOperation, TransactionRegistry};
+    use serde_json::{json, Map};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    fn initialize_graph_collection() -> GraphCollectionFacade {
+        let collection = GraphCollectionFacade::new(Vec::new(), None);
+        collection
+            .add_graph(InMemoryGraph::new_graph(String::from("MyGraph")))
+            .unwrap();
+        collection
+    }
+
+    fn node(label: &str) -> Node {
+        Node {
+            id: Uuid::default(),
+            labels: vec![String::from(label)],
+            properties: Map::new(),
+        }
+    }
+
+    fn bond(src: Uuid, dst: Uuid) -> Bond {
+        Bond {
+            id: Uuid::default(),
+            label: String::from("knows"),
+            src,
+            dst,
+            properties: Map::new(),
+        }
+    }
+
+    fn graph_size(collection: &GraphCollectionFacade) -> (usize, usize) {
+        let graph_lock = collection.get_graph("MyGraph").unwrap();
+        let graph = graph_lock.read().unwrap();
+        (
+            graph.get_nodes_collection_len(),
+            graph.get_bonds_collection_len(),
+        )
+    }
+
+    #[test]
+    fn commit_transaction_passed() {
+        let collection = initialize_graph_collection();
+        let mut transaction = collection.begin_transaction("MyGraph").unwrap();
+
+        let (uuid_1, uuid_2) = collection
+            .continue_transaction(&mut transaction, |tx| {
+                Ok((tx.add_node(node("blue"))?, tx.add_node(node("green"))?))
+            })
+            .unwrap();
+        let visible = collection
+            .continue_transaction(&mut transaction, |tx| {
+                tx.add_bond(bond(uuid_1, uuid_2))?;
+                Ok(tx.graph().get_nodes_collection_len())
+            })
+            .unwrap();
+
+        // own writes are visible inside, nothing outside before commit
+        assert_eq!(2, visible);
+        assert_eq!((0, 0), graph_size(&collection));
+
+        collection.commit_transaction(transaction).unwrap();
+
+        assert_eq!((2, 1), graph_size(&collection));
+    }
+
+    #[test]
+    fn rollback_transaction_passed() {
+        let collection = initialize_graph_collection();
+        let uuid_1 = collection.add_node("MyGraph", node("blue")).unwrap();
+
+        let uuid_2 = collection.add_node("MyGraph", node("green")).unwrap();
+        collection
+            .add_bond("MyGraph", bond(uuid_1, uuid_2))
+            .unwrap();
+
+        let mut transaction = collection.begin_transaction("MyGraph").unwrap();
+        collection
+            .continue_transaction(&mut transaction, |tx| {
+                tx.delete_node(uuid_1, true)?;
+                tx.add_node(node("green"))?;
+                tx.update_node_properties(uuid_2, json!({"size": 3}).as_object().unwrap().clone())
+            })
+            .unwrap();
+        drop(transaction);
+
+        let graph_lock = collection.get_graph("MyGraph").unwrap();
+        let graph = graph_lock.read().unwrap();
+        assert_eq!(
+            (2, 1),
+            (
+                graph.get_nodes_collection_len(),
+                graph.get_bonds_collection_len()
+            )
+        );
+        assert!(graph.nodes_id_index.contains_key(&uuid_1));
+        assert!(graph.nodes_collection[graph.nodes_id_index[&uuid_2]]
+            .properties
+            .is_empty());
+    }
+
+    #[test]
+    fn failed_operations_are_taken_back_passed() {
+        let collection = initialize_graph_collection();
+        let uuid_1 = collection.add_node("MyGraph", node("blue")).unwrap();
+        let missing_id = Uuid::new_v4();
+        let mut transaction = collection.begin_transaction("MyGraph").unwrap();
+
+        let failed = collection.run_transaction("MyGraph", |tx| {
+            tx.delete_node(uuid_1, false)?;
+            tx.add_bond(bond(uuid_1, missing_id))
+        });
+        let kept = collection.continue_transaction(&mut transaction, |tx| {
+            tx.add_node(node("green"))?;
+            tx.add_bond(bond(uuid_1, missing_id))
+        });
+        let seen = collection
+            .continue_transaction(&mut transaction, |tx| {
+                Ok(tx.graph().get_nodes_collection_len())
+            })
+            .unwrap();
+
+        assert_eq!(Err(GraphError::DanglingBondEndpoint(uuid_1)), failed);
+        assert_eq!(Err(GraphError::DanglingBondEndpoint(missing_id)), kept);
+        // operations before the failed one stay in transaction
+        assert_eq!(2, seen);
+        assert_eq!((1, 0), graph_size(&collection));
+    }
+
+    #[test]
+    fn transaction_after_concurrent_commit_failed() {
+        let collection = initialize_graph_collection();
+        let mut transaction = collection.begin_transaction("MyGraph").unwrap();
+
+        collection.add_node("MyGraph", node("blue")).unwrap();
+
+        // reading on would show the commit made after begin
+        let read = collection.continue_transaction(&mut transaction, |tx| {
+            Ok(tx.graph().get_nodes_collection_len())
+        });
+
+        assert_eq!(
+            Err(GraphError::TransactionConflict(String::from("MyGraph"))),
+            read
+        );
+    }
+
+    #[test]
+    fn commit_after_concurrent_commit_failed() {
+        let collection = initialize_graph_collection();
+        let mut transaction_1 = collection.begin_transaction("MyGraph").unwrap();
+        let mut transaction_2 = collection.begin_transaction("MyGraph").unwrap();
+        collection
+            .continue_transaction(&mut transaction_1, |tx| tx.add_node(node("blue")))
+            .unwrap();
+        collection
+            .continue_transaction(&mut transaction_2, |tx| tx.add_node(node("green")))
+            .unwrap();
+
+        collection.commit_transaction(transaction_1).unwrap();
+        let commit_result = collection.commit_transaction(transaction_2);
+
+        assert_eq!(
+            Err(GraphError::TransactionConflict(String::from("MyGraph"))),
+            commit_result
+        );
+        assert_eq!((1, 0), graph_size(&collection));
+    }
+
+    #[test]
+    fn commit_after_graph_recreated_failed() {
+        let collection = initialize_graph_collection();
+        let mut transaction = collection.begin_transaction("MyGraph").unwrap();
+        collection
+            .continue_transaction(&mut transaction, |tx| tx.add_node(node("blue")))
+            .unwrap();
+
+        collection.delete_graph("MyGraph").unwrap();
+        collection
+            .add_graph(InMemoryGraph::new_graph(String::from("MyGraph")))
+            .unwrap();
+
+        assert!(collection.commit_transaction(transaction).is_err());
+        assert_eq!((0, 0), graph_size(&collection));
+    }
+
+    #[test]
+    fn run_transaction_is_atomic_passed() {
+        let collection = initialize_graph_collection();
+        let missing_id = Uuid::new_v4();
+
+        let failed = collection.run_transaction("MyGraph", |transaction| {
+            let uuid_1 = transaction.add_node(node("blue"))?;
+            transaction.add_bond(bond(uuid_1, missing_id))
+        });
+        let applied = collection.run_transaction("MyGraph", |transaction| {
+            let uuid_1 = transaction.add_node(node("blue"))?;
+            let uuid_2 = transaction.add_node(node("green"))?;
+            transaction.add_bond(bond(uuid_1, uuid_2))
+        });
+
+        assert_eq!(Err(GraphError::DanglingBondEndpoint(missing_id)), failed);
+        assert!(applied.is_ok());
+        assert_eq!((2, 1), graph_size(&collection));
+    }
+
+    #[test]
+    fn apply_operations_passed() {
+        let collection = initialize_graph_collection();
+        let operations: Vec<TransactionOperation> = serde_json::from_value(json!([
+            {"op": "add_node", "id": "6f4ac2b5-8c0f-4c8e-9a53-0b9f4a3d2e11", "labels": ["blue"]},
+            {"op": "add_node", "id": "0d7e9b51-5f0c-4a39-8d1e-1f6f2c9b7a22", "labels": ["green"]},
+            {"op": "add_bond", "label": "knows",
+             "src": "6f4ac2b5-8c0f-4c8e-9a53-0b9f4a3d2e11",
+             "dst": "0d7e9b51-5f0c-4a39-8d1e-1f6f2c9b7a22"},
+            {"op": "update_node_properties", "id": "6f4ac2b5-8c0f-4c8e-9a53-0b9f4a3d2e11",
+             "properties": {"size": 3}},
+            {"op": "delete_node", "id": "0d7e9b51-5f0c-4a39-8d1e-1f6f2c9b7a22", "detach": true},
+        ]))
+        .unwrap();
+
+        let ids = collection
+            .run_transaction("MyGraph", |transaction| {
+                operations
+                    .into_iter()
+                    .map(|x| transaction.apply(x))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .unwrap();
+
+        assert_eq!(5, ids.len());
+        assert_eq!((1, 0), graph_size(&collection));
+    }
+
+    #[test]
+    fn transaction_recovered_from_log_passed() {
+        let graphs_dir =
+            std::env::temp_dir().join(format!("avtandb_transaction_test_{}", Uuid::new_v4()));
+        let open_collection = || {
+            let storage = GraphStorage::new(graphs_dir.clone()).unwrap();
+            let graphs = storage.recover().unwrap();
+            GraphCollectionFacade::new(graphs, Some(Arc::new(storage)))
+        };
+        {
+            let collection = open_collection();
+            collection
+                .add_graph(InMemoryGraph::new_graph(String::from("MyGraph")))
+                .unwrap();
+            let mut transaction = collection.begin_transaction("MyGraph").unwrap();
+            collection
+                .continue_transaction(&mut transaction, |tx| {
+                    let uuid_1 = tx.add_node(node("blue"))?;
+                    let uuid_2 = tx.add_node(node("green"))?;
+                    tx.add_bond(bond(uuid_1, uuid_2))
+                })
+                .unwrap();
+            collection.commit_transaction(transaction).unwrap();
+        }
+
+        let collection = open_collection();
+
+        assert_eq!((2, 1), graph_size(&collection));
+        let _ = std::fs::remove_dir_all(&graphs_dir);
+    }
+
+    #[test]
+    fn registry_transaction_passed() {
+        let collection = initialize_graph_collection();
+        let registry = TransactionRegistry::default();
+
+        let id = registry.begin(&collection, "MyGraph").unwrap();
+        registry
+            .with_transaction(&collection, "MyGraph", id, |x| x.add_node(node("blue")))
+            .unwrap();
+
+        assert_eq!(
+            Err(GraphError::TransactionNotFound(id)),
+            registry.with_transaction(&collection, "OtherGraph", id, |x| x.add_node(node("blue")))
+        );
+        registry.commit(&collection, "MyGraph", id).unwrap();
+        assert_eq!(
+            Err(GraphError::TransactionNotFound(id)),
+            registry.commit(&collection, "MyGraph", id)
+        );
+        assert_eq!((1, 0), graph_size(&collection));
+
+        let id = registry.begin(&collection, "MyGraph").unwrap();
+        registry
+            .with_transaction(&collection, "MyGraph", id, |x| x.add_node(node("green")))
+            .unwrap();
+        registry.rollback("MyGraph", id).unwrap();
+        assert_eq!((1, 0), graph_size(&collection));
+    }
+
+    #[test]
+    fn registry_expired_transaction_failed() {
+        let collection = initialize_graph_collection();
+        let registry = TransactionRegistry::new(Duration::from_millis(20), 8);
+
+        let id = registry.begin(&collection, "MyGraph").unwrap();
+        std::thread::sleep(Duration::from_millis(40));
+
+        assert_eq!(
+            Err(GraphError::TransactionNotFound(id)),
+            registry.with_transaction(&collection, "MyGraph", id, |x| x.add_node(node("blue")))
+        );
+        assert_eq!(
+            Err(GraphError::TransactionNotFound(id)),
+            registry.commit(&collection, "MyGraph", id)
+        );
+        assert_eq!((0, 0), graph_size(&collection));
+    }
+
+    #[test]
+    fn registry_too_many_transactions_failed() {
+        let collection = initialize_graph_collection();
+        let registry = TransactionRegistry::new(Duration::from_secs(60), 2);
+
+        let first = registry.begin(&collection, "MyGraph").unwrap();
+        registry.begin(&collection, "MyGraph").unwrap();
+
+        assert_eq!(
+            Err(GraphError::TooManyTransactions(2)),
+            registry.begin(&collection, "MyGraph")
+        );
+        registry.rollback("MyGraph", first).unwrap();
+        assert!(registry.begin(&collection, "MyGraph").is_ok());
+    }
+}
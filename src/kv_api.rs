@@ -1,54 +1,86 @@
+use crate::kv_model::{ExpiryDTO, KVError, KeyExpiryDTO};
 use crate::AppState;
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
 
+/// Adds value, `ttl` or `expire_at` in query string makes it expire
 pub async fn create_value(
     data: web::Data<AppState>,
     web::Path(key): web::Path<String>,
+    expiry: web::Query<ExpiryDTO>,
     value: String,
-) -> impl Responder {
-    if let Err(_) = data.kv_collection.clone().add_value(key, value).await {
-        return HttpResponse::BadRequest().body("");
-    }
-    HttpResponse::Ok().body("")
+) -> Result<HttpResponse, KVError> {
+    let expires_at = expiry.expires_at(Utc::now())?;
+    data.kv_collection.add_value(key, value, expires_at).await?;
+    Ok(HttpResponse::Ok().finish())
 }
 
 pub async fn get_value(
     data: web::Data<AppState>,
     web::Path(key): web::Path<String>,
-) -> impl Responder {
-    let arc_string_value = match data.kv_collection.get_value(key).await {
-        Ok(v) => v,
-        Err(_) => return HttpResponse::NotFound().body(""),
-    };
-    HttpResponse::Ok().body(format!("{}", arc_string_value))
+) -> Result<HttpResponse, KVError> {
+    let arc_string_value = data.kv_collection.get_value(key).await?;
+    Ok(HttpResponse::Ok().body(format!("{}", arc_string_value)))
 }
 
+/// Replaces value, without `ttl` or `expire_at` key keeps its expiry
 pub async fn update_value(
     data: web::Data<AppState>,
     web::Path(key): web::Path<String>,
+    expiry: web::Query<ExpiryDTO>,
     value: String,
-) -> impl Responder {
-    if let Err(_) = data.kv_collection.clone().update_value(key, value).await {
-        return HttpResponse::BadRequest().body("");
-    }
-    HttpResponse::Ok().body("")
+) -> Result<HttpResponse, KVError> {
+    let expires_at = expiry.expires_at(Utc::now())?;
+    data.kv_collection
+        .update_value(key, value, expires_at)
+        .await?;
+    Ok(HttpResponse::Ok().finish())
 }
 
 pub async fn delete_value(
     data: web::Data<AppState>,
     web::Path(key): web::Path<String>,
-) -> impl Responder {
-    if let Err(_) = data.kv_collection.clone().remove_value(key).await {
-        return HttpResponse::BadRequest().body("");
-    }
-    HttpResponse::Ok().body("")
-}
-
-pub async fn get_all_keys(data: web::Data<AppState>) -> impl Responder {
-    let keys = match data.kv_collection.clone().get_all_keys().await {
-        Err(_) => return HttpResponse::BadRequest().body(""),
-        Ok(v) => v,
-    };
-    let res = serde_json::to_vec(&keys).expect("deser err");
-    HttpResponse::Ok().body(res)
+) -> Result<HttpResponse, KVError> {
+    data.kv_collection.remove_value(key).await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+pub async fn get_all_keys(data: web::Data<AppState>) -> Result<HttpResponse, KVError> {
+    let keys = data.kv_collection.get_all_keys().await?;
+    Ok(HttpResponse::Ok().json(keys))
+}
+
+/// Compacts persisted log of store, no-op for memory only store
+pub async fn compact(data: web::Data<AppState>) -> Result<HttpResponse, KVError> {
+    data.kv_collection.compact().await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// EXPIRE, sets `ttl` or `expire_at` of existing key
+pub async fn expire_value(
+    data: web::Data<AppState>,
+    web::Path(key): web::Path<String>,
+    expiry: web::Query<ExpiryDTO>,
+) -> Result<HttpResponse, KVError> {
+    let expires_at = expiry.required_expires_at(Utc::now())?;
+    data.kv_collection.set_expiry(key, expires_at).await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// TTL, time left before key expires
+pub async fn get_ttl(
+    data: web::Data<AppState>,
+    web::Path(key): web::Path<String>,
+) -> Result<HttpResponse, KVError> {
+    let expires_at = data.kv_collection.get_expiry(key).await?;
+    Ok(HttpResponse::Ok().json(KeyExpiryDTO::new(expires_at, Utc::now())))
+}
+
+/// PERSIST, makes key never expire
+pub async fn persist_value(
+    data: web::Data<AppState>,
+    web::Path(key): web::Path<String>,
+) -> Result<HttpResponse, KVError> {
+    data.kv_collection.persist(key).await?;
+    Ok(HttpResponse::Ok().finish())
 }
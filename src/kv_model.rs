@@ -1,32 +1,243 @@
+use crate::kv_watch::{KVChangeKind, KVWatchHub};
+use actix_web::error::BlockingError;
+use actix_web::web;
 use std::sync::Arc;
 // use std::sync::RwLock;
 use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::future::Future;
+use std::io::{self, BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
 use tokio::sync::RwLock;
 //use chrono::{DateTime};
 
+/// Reason KV operation failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum KVError {
+    DuplicateKey(String),
+    KeyNotFound(String),
+    /// Malformed or conflicting ttl / expire_at
+    InvalidExpiry(String),
+    /// Watch pattern without exactly one of key, prefix or glob
+    InvalidPattern(String),
+    /// Websocket connection has no such watch subscription
+    SubscriptionNotFound(u64),
+    /// Watch session was closed for falling behind
+    WatchClosed,
+    Storage(String),
+}
+
+impl KVError {
+    /// Stable machine readable code
+    pub fn code(&self) -> &'static str {
+        match self {
+            KVError::DuplicateKey(_) => "DUPLICATE_KEY",
+            KVError::KeyNotFound(_) => "KEY_NOT_FOUND",
+            KVError::InvalidExpiry(_) => "INVALID_EXPIRY",
+            KVError::InvalidPattern(_) => "INVALID_PATTERN",
+            KVError::SubscriptionNotFound(_) => "SUBSCRIPTION_NOT_FOUND",
+            KVError::WatchClosed => "WATCH_CLOSED",
+            KVError::Storage(_) => "STORAGE_ERROR",
+        }
+    }
+}
+
+impl fmt::Display for KVError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KVError::DuplicateKey(key) => write!(f, "key \"{}\" already exists", key),
+            KVError::KeyNotFound(key) => write!(f, "key \"{}\" not found", key),
+            KVError::InvalidExpiry(message) => write!(f, "invalid expiry: {}", message),
+            KVError::InvalidPattern(message) => write!(f, "invalid pattern: {}", message),
+            KVError::SubscriptionNotFound(id) => write!(f, "subscription {} not found", id),
+            KVError::WatchClosed => write!(f, "watch closed, changes were not read in time"),
+            KVError::Storage(message) => write!(f, "storage error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for KVError {}
+
+impl From<io::Error> for KVError {
+    fn from(e: io::Error) -> Self {
+        KVError::Storage(e.to_string())
+    }
+}
+
+/// How often background sweeper evicts expired keys
+pub const KV_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Boxed future returned by KVStore operations, so the trait stays object safe
+pub type KVFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, KVError>> + 'a>>;
+
+/// Common interface of all KV backends, AppState holds one of them as a trait object.
+///
+/// Key with expiry passed is invisible to every operation, as if it was removed,
+/// even before evict_expired drops it.
 pub trait KVStore {
-    fn add_value(&mut self, key: String, value: String) -> Result<(), ()>;
-    fn get_value(&self, key: String) -> Result<Arc<String>, ()>;
-    fn remove_key(&mut self, key: String) -> Result<(), ()>;
-    fn update_value(&mut self, key: String, value: String) -> Result<(), ()>;
+    /// Adds key, expiring at `expires_at` if set
+    fn add_value(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> KVFuture<'_, ()>;
+    fn get_value(&self, key: String) -> KVFuture<'_, Arc<String>>;
+    fn remove_value(&self, key: String) -> KVFuture<'_, ()>;
+    /// Replaces value, `expires_at` None keeps expiry key already has
+    fn update_value(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> KVFuture<'_, ()>;
+    fn get_all_keys(&self) -> KVFuture<'_, Vec<String>>;
+    /// Sets expiry of existing key (EXPIRE)
+    fn set_expiry(&self, key: String, expires_at: DateTime<Utc>) -> KVFuture<'_, ()>;
+    /// Expiry of existing key, None for key that never expires (TTL)
+    fn get_expiry(&self, key: String) -> KVFuture<'_, Option<DateTime<Utc>>>;
+    /// Makes existing key never expire (PERSIST)
+    fn persist(&self, key: String) -> KVFuture<'_, ()>;
+    /// Removes expired keys, returning their number
+    fn evict_expired(&self) -> KVFuture<'_, usize>;
+    /// Rewrites persisted log with live keys only, nothing to do for memory only stores
+    fn compact(&self) -> KVFuture<'_, ()> {
+        Box::pin(async { Ok(()) })
+    }
+    /// Subscriptions notified of every add, update, remove and eviction
+    fn watch_hub(&self) -> &KVWatchHub;
+}
+
+/// Expiry of key as seconds from now or as RFC 3339 time, at most one of them.
+/// Query string of KV writes and body part of websocket writes.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ExpiryDTO {
+    pub ttl: Option<u64>,
+    pub expire_at: Option<String>,
+}
+
+impl ExpiryDTO {
+    /// Absolute expiry, None if neither ttl nor expire_at is set
+    pub fn expires_at(&self, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, KVError> {
+        match (self.ttl, &self.expire_at) {
+            (None, None) => Ok(None),
+            (Some(_), Some(_)) => Err(KVError::InvalidExpiry(String::from(
+                "set either ttl or expire_at, not both",
+            ))),
+            (Some(ttl), None) => chrono::Duration::from_std(Duration::from_secs(ttl))
+                .ok()
+                .and_then(|x| now.checked_add_signed(x))
+                .map(Some)
+                .ok_or_else(|| KVError::InvalidExpiry(format!("ttl {} is too large", ttl))),
+            (None, Some(expire_at)) => DateTime::parse_from_rfc3339(expire_at)
+                .map(|x| Some(x.with_timezone(&Utc)))
+                .map_err(|e| KVError::InvalidExpiry(format!("expire_at: {}", e))),
+        }
+    }
+
+    /// Absolute expiry of EXPIRE, where one of ttl or expire_at is required
+    pub fn required_expires_at(&self, now: DateTime<Utc>) -> Result<DateTime<Utc>, KVError> {
+        self.expires_at(now)?
+            .ok_or_else(|| KVError::InvalidExpiry(String::from("ttl or expire_at is required")))
+    }
+}
+
+/// Answer of TTL lookup, both fields None for key that never expires
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeyExpiryDTO {
+    /// Whole seconds left, rounded up
+    pub ttl: Option<i64>,
+    /// RFC 3339
+    pub expires_at: Option<String>,
+}
+
+impl KeyExpiryDTO {
+    pub fn new(expires_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Self {
+        KeyExpiryDTO {
+            ttl: expires_at.map(|x| ((x - now).num_milliseconds().max(0) + 999) / 1000),
+            expires_at: expires_at.map(|x| x.to_rfc3339()),
+        }
+    }
 }
 
 /// Facade over main hash map
 pub struct InMemoryKVStore {
     pub kv_hash_map: Arc<RwLock<HashMap<String, Arc<String>>>>,
+    /// Always locked after kv_hash_map
+    pub ttl_map: Arc<RwLock<TtlMap>>,
+    /// Published to under kv_hash_map write lock
+    pub watch_hub: Arc<KVWatchHub>,
 }
 
 impl Clone for InMemoryKVStore {
     fn clone(&self) -> Self {
         Self {
             kv_hash_map: self.kv_hash_map.clone(),
+            ttl_map: self.ttl_map.clone(),
+            watch_hub: self.watch_hub.clone(),
         }
     }
 }
 
+/// Expiry times of keys, keys without entry never expire
+#[derive(Debug, Default)]
 pub struct TtlMap {
-    pub kv_hash_map: Arc<RwLock<HashMap<String, DateTime<Utc>>>>,
+    pub expiry_map: HashMap<String, DateTime<Utc>>,
+}
+
+impl TtlMap {
+    pub fn new() -> Self {
+        TtlMap::default()
+    }
+
+    pub fn is_expired(&self, key: &str, now: DateTime<Utc>) -> bool {
+        self.expiry_map.get(key).is_some_and(|x| *x <= now)
+    }
+
+    pub fn get(&self, key: &str) -> Option<DateTime<Utc>> {
+        self.expiry_map.get(key).copied()
+    }
+
+    /// Sets expiry of key, None makes it never expire
+    pub fn set(&mut self, key: String, expires_at: Option<DateTime<Utc>>) {
+        match expires_at {
+            None => {
+                self.expiry_map.remove(&key);
+            }
+            Some(x) => {
+                self.expiry_map.insert(key, x);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.expiry_map.remove(key);
+    }
+
+    pub fn expired_keys(&self, now: DateTime<Utc>) -> Vec<String> {
+        self.expiry_map
+            .iter()
+            .filter(|(_, x)| **x <= now)
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+}
+
+/// Key is stored and not expired
+fn is_live(
+    hash_map: &HashMap<String, Arc<String>>,
+    ttl_map: &TtlMap,
+    key: &str,
+    now: DateTime<Utc>,
+) -> bool {
+    hash_map.contains_key(key) && !ttl_map.is_expired(key, now)
 }
 
 impl InMemoryKVStore {
@@ -34,93 +245,668 @@ impl InMemoryKVStore {
     pub fn new() -> Self {
         InMemoryKVStore {
             kv_hash_map: Arc::new(RwLock::new(HashMap::new())),
+            ttl_map: Arc::new(RwLock::new(TtlMap::new())),
+            watch_hub: Arc::new(KVWatchHub::new()),
         }
     }
+}
 
-    pub async fn add_value(&mut self, key: String, value: String) -> Result<(), ()> {
-        // NOT SURE IF self....lock() - is a good idea
-        let mut hash_map = self.kv_hash_map.write().await;
-        if let Some(_) = hash_map.get(&key) {
-            return Err(());
-        }
-        hash_map.insert(key, Arc::new(value));
-        Ok(())
+impl KVStore for InMemoryKVStore {
+    fn add_value(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> KVFuture<'_, ()> {
+        Box::pin(async move {
+            // NOT SURE IF self....lock() - is a good idea
+            let mut hash_map = self.kv_hash_map.write().await;
+            let mut ttl_map = self.ttl_map.write().await;
+            if is_live(&hash_map, &ttl_map, &key, Utc::now()) {
+                return Err(KVError::DuplicateKey(key));
+            }
+            // expired key being replaced is dropped the way sweep drops it
+            if let Some(old_value) = hash_map.get(&key) {
+                self.watch_hub
+                    .publish(KVChangeKind::Expired, &key, Some(old_value), None);
+            }
+            ttl_map.set(key.clone(), expires_at);
+            let value = Arc::new(value);
+            self.watch_hub
+                .publish(KVChangeKind::Added, &key, None, Some(&value));
+            hash_map.insert(key, value);
+            Ok(())
+        })
     }
 
     /// Get value
-    pub async fn get_value(&self, key: String) -> Result<Arc<String>, ()> {
-        let hash_map = self.kv_hash_map.read().await;
-        let val = hash_map.get(&key);
+    fn get_value(&self, key: String) -> KVFuture<'_, Arc<String>> {
+        Box::pin(async move {
+            let hash_map = self.kv_hash_map.read().await;
+            let ttl_map = self.ttl_map.read().await;
+            if ttl_map.is_expired(&key, Utc::now()) {
+                return Err(KVError::KeyNotFound(key));
+            }
+            let val = hash_map.get(&key);
 
-        return match val {
-            Some(inner_val) => Ok(inner_val.clone()),
-            None => Err(()),
-        };
+            return match val {
+                Some(inner_val) => Ok(inner_val.clone()),
+                None => Err(KVError::KeyNotFound(key)),
+            };
+        })
     }
 
     /// Removes Key-Value Pair from KV collection
-    pub async fn remove_value(&mut self, key: String) -> Result<(), ()> {
-        let mut hash_map = self.kv_hash_map.write().await;
-        match hash_map.remove(&key) {
-            Some(_) => Ok(()),
-            None => Err(()),
-        }
+    fn remove_value(&self, key: String) -> KVFuture<'_, ()> {
+        Box::pin(async move {
+            let mut hash_map = self.kv_hash_map.write().await;
+            let mut ttl_map = self.ttl_map.write().await;
+            let expired = ttl_map.is_expired(&key, Utc::now());
+            ttl_map.remove(&key);
+            match hash_map.remove(&key) {
+                Some(old_value) if !expired => {
+                    self.watch_hub
+                        .publish(KVChangeKind::Removed, &key, Some(&old_value), None);
+                    Ok(())
+                }
+                // found expired, dropped the way sweep drops it
+                Some(old_value) => {
+                    self.watch_hub
+                        .publish(KVChangeKind::Expired, &key, Some(&old_value), None);
+                    Err(KVError::KeyNotFound(key))
+                }
+                None => Err(KVError::KeyNotFound(key)),
+            }
+        })
     }
 
     /// Updates the value by key
-    pub async fn update_value(&mut self, key: String, value: String) -> Result<(), ()> {
-        let mut hash_map = self.kv_hash_map.write().await;
-        match hash_map.get(&key) {
-            None => Err(()),
-            Some(_) => {
-                hash_map.insert(key, Arc::new(value));
-                Ok(())
+    fn update_value(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> KVFuture<'_, ()> {
+        Box::pin(async move {
+            let mut hash_map = self.kv_hash_map.write().await;
+            let mut ttl_map = self.ttl_map.write().await;
+            if !is_live(&hash_map, &ttl_map, &key, Utc::now()) {
+                return Err(KVError::KeyNotFound(key));
             }
-        }
+            if expires_at.is_some() {
+                ttl_map.set(key.clone(), expires_at);
+            }
+            let value = Arc::new(value);
+            let old_value = hash_map.insert(key.clone(), value.clone());
+            self.watch_hub.publish(
+                KVChangeKind::Updated,
+                &key,
+                old_value.as_ref(),
+                Some(&value),
+            );
+            Ok(())
+        })
     }
 
     /// Get all Keys Collection
-    pub async fn get_all_keys(&self) -> Result<Vec<String>, ()> {
-        // NOT SURE IF self....lock() - is a good idea
-        let hash_map = self.kv_hash_map.read().await;
+    fn get_all_keys(&self) -> KVFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            // NOT SURE IF self....lock() - is a good idea
+            let hash_map = self.kv_hash_map.read().await;
+            let ttl_map = self.ttl_map.read().await;
+            let now = Utc::now();
+
+            // TODO: think about .clone() ?????
+            let vals: Vec<String> = hash_map
+                .iter()
+                .filter(|(x, _)| !ttl_map.is_expired(x, now))
+                .map(|(x, _)| x.clone())
+                .collect();
+            Ok(vals)
+        })
+    }
+
+    fn set_expiry(&self, key: String, expires_at: DateTime<Utc>) -> KVFuture<'_, ()> {
+        Box::pin(async move {
+            let hash_map = self.kv_hash_map.read().await;
+            let mut ttl_map = self.ttl_map.write().await;
+            if !is_live(&hash_map, &ttl_map, &key, Utc::now()) {
+                return Err(KVError::KeyNotFound(key));
+            }
+            ttl_map.set(key, Some(expires_at));
+            Ok(())
+        })
+    }
 
-        // TODO: think about .clone() ?????
-        let vals: Vec<String> = hash_map.iter().map(|(x, _)| x.clone()).collect();
-        Ok(vals)
+    fn get_expiry(&self, key: String) -> KVFuture<'_, Option<DateTime<Utc>>> {
+        Box::pin(async move {
+            let hash_map = self.kv_hash_map.read().await;
+            let ttl_map = self.ttl_map.read().await;
+            if !is_live(&hash_map, &ttl_map, &key, Utc::now()) {
+                return Err(KVError::KeyNotFound(key));
+            }
+            Ok(ttl_map.get(&key))
+        })
+    }
+
+    fn persist(&self, key: String) -> KVFuture<'_, ()> {
+        Box::pin(async move {
+            let hash_map = self.kv_hash_map.read().await;
+            let mut ttl_map = self.ttl_map.write().await;
+            if !is_live(&hash_map, &ttl_map, &key, Utc::now()) {
+                return Err(KVError::KeyNotFound(key));
+            }
+            ttl_map.remove(&key);
+            Ok(())
+        })
     }
+
+    fn evict_expired(&self) -> KVFuture<'_, usize> {
+        Box::pin(async move {
+            let mut hash_map = self.kv_hash_map.write().await;
+            let mut ttl_map = self.ttl_map.write().await;
+            let expired = ttl_map.expired_keys(Utc::now());
+            for key in expired.iter() {
+                ttl_map.remove(key);
+                if let Some(old_value) = hash_map.remove(key) {
+                    self.watch_hub
+                        .publish(KVChangeKind::Expired, key, Some(&old_value), None);
+                }
+            }
+            Ok(expired.len())
+        })
+    }
+
+    fn watch_hub(&self) -> &KVWatchHub {
+        &self.watch_hub
+    }
+}
+
+/// Name of the append-only log file inside the data directory
+const DURABLE_LOG_FILE_NAME: &str = "kv.log";
+/// Temporary file the compacted log is written to before it replaces the main one
+const DURABLE_COMPACT_FILE_NAME: &str = "kv.log.compact";
+/// Minimal number of stale records before compaction is even considered
+const DURABLE_COMPACTION_THRESHOLD: usize = 1024;
+
+/// Single record of the durable append-only log, one JSON document per line.
+/// Expiry is kept as unix milliseconds.
+#[derive(Debug, Serialize, Deserialize)]
+enum DurableLogRecord {
+    Put {
+        key: String,
+        value: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expires_at: Option<i64>,
+    },
+    Remove {
+        key: String,
+    },
+    /// New expiry of stored key, None makes it never expire
+    Expire {
+        key: String,
+        expires_at: Option<i64>,
+    },
+}
+
+fn to_millis(time: DateTime<Utc>) -> i64 {
+    time.timestamp_millis()
+}
+
+fn from_millis(millis: i64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_millis_opt(millis).single()
 }
 
 // Stores KV on filesystem
-pub struct DurableKVStore {}
+//
+// Every mutation is appended to `kv.log` and fsynced before the call returns,
+// values are served from the in-memory map rebuilt by replaying the log on open.
+// When the log holds more stale records than live keys it is rewritten
+// to a temporary file and atomically renamed over the old one.
+pub struct DurableKVStore {
+    state: Arc<DurableKVState>,
+}
+
+/// Shared with blocking pool threads running log operations
+struct DurableKVState {
+    data_dir: PathBuf,
+    log: Mutex<DurableLog>,
+    /// Published to under log mutex
+    watch_hub: KVWatchHub,
+}
+
+/// Mutable part of DurableKVStore, guarded by a single mutex so appends stay ordered
+struct DurableLog {
+    log_file: File,
+    kv_hash_map: HashMap<String, Arc<String>>,
+    ttl_map: TtlMap,
+    stale_records: usize,
+}
+
+/// Map state rebuilt from log
+struct ReplayedLog {
+    kv_hash_map: HashMap<String, Arc<String>>,
+    ttl_map: TtlMap,
+    stale_records: usize,
+}
 
 impl DurableKVStore {
-    fn new() -> Self {
-        todo!();
+    /// Opens (or creates) store in data_dir, replaying the existing log
+    pub fn new(data_dir: PathBuf) -> io::Result<Self> {
+        fs::create_dir_all(&data_dir)?;
+        let log_path = data_dir.join(DURABLE_LOG_FILE_NAME);
+
+        // Leftover of a compaction that crashed before rename - main log is still valid
+        let compact_path = data_dir.join(DURABLE_COMPACT_FILE_NAME);
+        if compact_path.exists() {
+            fs::remove_file(&compact_path)?;
+        }
+
+        let mut log_file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&log_path)?;
+        let replayed = DurableKVStore::replay_log(&mut log_file)?;
+
+        Ok(DurableKVStore {
+            state: Arc::new(DurableKVState {
+                data_dir,
+                log: Mutex::new(DurableLog {
+                    log_file,
+                    kv_hash_map: replayed.kv_hash_map,
+                    ttl_map: replayed.ttl_map,
+                    stale_records: replayed.stale_records,
+                }),
+                watch_hub: KVWatchHub::new(),
+            }),
+        })
+    }
+
+    /// Rebuilds map from log, cutting off torn tail left by a crash mid-write
+    fn replay_log(log_file: &mut File) -> io::Result<ReplayedLog> {
+        let mut kv_hash_map = HashMap::new();
+        let mut ttl_map = TtlMap::new();
+        let mut stale_records = 0;
+        let mut valid_len: u64 = 0;
+
+        log_file.seek(SeekFrom::Start(0))?;
+        let mut reader = BufReader::new(&*log_file);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line)?;
+            if read == 0 || !line.ends_with('\n') {
+                break;
+            }
+            let record: DurableLogRecord = match serde_json::from_str(line.trim_end()) {
+                Ok(r) => r,
+                Err(_) => break,
+            };
+            match record {
+                DurableLogRecord::Put {
+                    key,
+                    value,
+                    expires_at,
+                } => {
+                    ttl_map.set(key.clone(), expires_at.and_then(from_millis));
+                    if kv_hash_map.insert(key, Arc::new(value)).is_some() {
+                        stale_records += 1;
+                    }
+                }
+                DurableLogRecord::Remove { key } => {
+                    kv_hash_map.remove(&key);
+                    ttl_map.remove(&key);
+                    // both the remove and the put it cancels are dead now
+                    stale_records += 2;
+                }
+                DurableLogRecord::Expire { key, expires_at } => {
+                    if kv_hash_map.contains_key(&key) {
+                        ttl_map.set(key, expires_at.and_then(from_millis));
+                    }
+                    stale_records += 1;
+                }
+            }
+            valid_len += read as u64;
+        }
+
+        if valid_len < log_file.metadata()?.len() {
+            log_file.set_len(valid_len)?;
+            log_file.sync_all()?;
+        }
+
+        Ok(ReplayedLog {
+            kv_hash_map,
+            ttl_map,
+            stale_records,
+        })
+    }
+}
+
+impl DurableKVState {
+    /// Compacts log if stale records outweigh live keys. Called after the mutation
+    /// is already durable, so failure is only reported and compaction retried later.
+    fn maybe_compact(&self, log: &mut DurableLog) {
+        if log.stale_records < DURABLE_COMPACTION_THRESHOLD
+            || log.stale_records < log.kv_hash_map.len()
+        {
+            return;
+        }
+        if let Err(e) = self.compact_locked(log) {
+            eprintln!("kv log compaction failed: {}", e);
+        }
+    }
+
+    fn compact_locked(&self, log: &mut DurableLog) -> io::Result<()> {
+        let log_path = self.data_dir.join(DURABLE_LOG_FILE_NAME);
+        let compact_path = self.data_dir.join(DURABLE_COMPACT_FILE_NAME);
+
+        {
+            let compact_file = File::create(&compact_path)?;
+            let mut writer = BufWriter::new(&compact_file);
+            for (key, value) in log.kv_hash_map.iter() {
+                let record = DurableLogRecord::Put {
+                    key: key.clone(),
+                    value: value.to_string(),
+                    expires_at: log.ttl_map.get(key).map(to_millis),
+                };
+                let line = serde_json::to_string(&record)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                writer.write_all(line.as_bytes())?;
+                writer.write_all(b"\n")?;
+            }
+            writer.flush()?;
+            drop(writer);
+            compact_file.sync_all()?;
+        }
+
+        fs::rename(&compact_path, &log_path)?;
+        // make the rename itself durable
+        File::open(&self.data_dir)?.sync_all()?;
+
+        log.log_file = OpenOptions::new().read(true).append(true).open(&log_path)?;
+        log.stale_records = 0;
+        Ok(())
+    }
+
+    fn add_value_sync(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(), KVError> {
+        let mut log = self.log.lock().unwrap();
+        if log.is_live(&key, Utc::now()) {
+            return Err(KVError::DuplicateKey(key));
+        }
+        let record = DurableLogRecord::Put {
+            key: key.clone(),
+            value: value.clone(),
+            expires_at: expires_at.map(to_millis),
+        };
+        log.append_record(&record)?;
+        // expired key being replaced is dropped the way sweep drops it
+        if let Some(old_value) = log.kv_hash_map.get(&key) {
+            self.watch_hub
+                .publish(KVChangeKind::Expired, &key, Some(old_value), None);
+            log.stale_records += 1;
+        }
+        log.ttl_map.set(key.clone(), expires_at);
+        let value = Arc::new(value);
+        self.watch_hub
+            .publish(KVChangeKind::Added, &key, None, Some(&value));
+        log.kv_hash_map.insert(key, value);
+        Ok(())
+    }
+
+    fn get_value_sync(&self, key: String) -> Result<Arc<String>, KVError> {
+        let log = self.log.lock().unwrap();
+        if !log.is_live(&key, Utc::now()) {
+            return Err(KVError::KeyNotFound(key));
+        }
+        Ok(log.kv_hash_map[&key].clone())
+    }
+
+    fn remove_value_sync(&self, key: String) -> Result<(), KVError> {
+        let mut log = self.log.lock().unwrap();
+        if !log.is_live(&key, Utc::now()) {
+            return Err(KVError::KeyNotFound(key));
+        }
+        let old_value = log.remove_key(key.clone())?;
+        self.watch_hub
+            .publish(KVChangeKind::Removed, &key, old_value.as_ref(), None);
+        self.maybe_compact(&mut log);
+        Ok(())
+    }
+
+    fn update_value_sync(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(), KVError> {
+        let mut log = self.log.lock().unwrap();
+        if !log.is_live(&key, Utc::now()) {
+            return Err(KVError::KeyNotFound(key));
+        }
+        // every put carries expiry, so the kept one is written again
+        let expires_at = expires_at.or_else(|| log.ttl_map.get(&key));
+        let record = DurableLogRecord::Put {
+            key: key.clone(),
+            value: value.clone(),
+            expires_at: expires_at.map(to_millis),
+        };
+        log.append_record(&record)?;
+        log.ttl_map.set(key.clone(), expires_at);
+        let value = Arc::new(value);
+        let old_value = log.kv_hash_map.insert(key.clone(), value.clone());
+        self.watch_hub.publish(
+            KVChangeKind::Updated,
+            &key,
+            old_value.as_ref(),
+            Some(&value),
+        );
+        log.stale_records += 1;
+        self.maybe_compact(&mut log);
+        Ok(())
+    }
+
+    fn get_all_keys_sync(&self) -> Result<Vec<String>, KVError> {
+        let log = self.log.lock().unwrap();
+        let now = Utc::now();
+        Ok(log
+            .kv_hash_map
+            .keys()
+            .filter(|x| !log.ttl_map.is_expired(x, now))
+            .cloned()
+            .collect())
+    }
+
+    fn set_expiry_sync(
+        &self,
+        key: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(), KVError> {
+        let mut log = self.log.lock().unwrap();
+        if !log.is_live(&key, Utc::now()) {
+            return Err(KVError::KeyNotFound(key));
+        }
+        let record = DurableLogRecord::Expire {
+            key: key.clone(),
+            expires_at: expires_at.map(to_millis),
+        };
+        log.append_record(&record)?;
+        log.ttl_map.set(key, expires_at);
+        log.stale_records += 1;
+        self.maybe_compact(&mut log);
+        Ok(())
+    }
+
+    fn get_expiry_sync(&self, key: String) -> Result<Option<DateTime<Utc>>, KVError> {
+        let log = self.log.lock().unwrap();
+        if !log.is_live(&key, Utc::now()) {
+            return Err(KVError::KeyNotFound(key));
+        }
+        Ok(log.ttl_map.get(&key))
+    }
+
+    fn evict_expired_sync(&self) -> Result<usize, KVError> {
+        let mut log = self.log.lock().unwrap();
+        let expired = log.ttl_map.expired_keys(Utc::now());
+        let evicted = expired.len();
+        for key in expired {
+            let old_value = log.remove_key(key.clone())?;
+            self.watch_hub
+                .publish(KVChangeKind::Expired, &key, old_value.as_ref(), None);
+        }
+        self.maybe_compact(&mut log);
+        Ok(evicted)
+    }
+}
+
+impl DurableLog {
+    /// Appends record and fsyncs it - the commit point of every mutation.
+    /// Record that is not fully written and synced is cut off again, so the failed
+    /// mutation is not replayed and later records are not lost behind it.
+    fn append_record(&mut self, record: &DurableLogRecord) -> io::Result<()> {
+        let mut line = serde_json::to_string(record)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        line.push('\n');
+        let len_before = self.log_file.metadata()?.len();
+        let appended = self
+            .log_file
+            .write_all(line.as_bytes())
+            .and_then(|_| self.log_file.sync_data());
+        if appended.is_err() {
+            let _ = self.log_file.set_len(len_before);
+        }
+        appended
+    }
+
+    fn is_live(&self, key: &str, now: DateTime<Utc>) -> bool {
+        is_live(&self.kv_hash_map, &self.ttl_map, key, now)
+    }
+
+    /// Logs and applies removal of stored key, returning its value
+    fn remove_key(&mut self, key: String) -> io::Result<Option<Arc<String>>> {
+        let record = DurableLogRecord::Remove { key: key.clone() };
+        self.append_record(&record)?;
+        self.ttl_map.remove(&key);
+        self.stale_records += 2;
+        Ok(self.kv_hash_map.remove(&key))
     }
 }
 
+// Disk operations fsync and wait for the log mutex, so they run on the blocking
+// thread pool instead of stalling the executor thread serving other requests
 impl KVStore for DurableKVStore {
     /// Add value to disk storage
-    fn add_value(&mut self, key: String, value: String) -> Result<(), ()> {
-        todo!();
+    fn add_value(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> KVFuture<'_, ()> {
+        let state = self.state.clone();
+        Box::pin(run_blocking(move || {
+            state.add_value_sync(key, value, expires_at)
+        }))
     }
 
     /// Get value
-    fn get_value(&self, key: String) -> Result<Arc<String>, ()> {
-        todo!();
+    fn get_value(&self, key: String) -> KVFuture<'_, Arc<String>> {
+        let state = self.state.clone();
+        Box::pin(run_blocking(move || state.get_value_sync(key)))
     }
 
-    fn remove_key(&mut self, key: String) -> Result<(), ()> {
-        todo!();
+    /// Removes Key-Value Pair from disk storage
+    fn remove_value(&self, key: String) -> KVFuture<'_, ()> {
+        let state = self.state.clone();
+        Box::pin(run_blocking(move || state.remove_value_sync(key)))
     }
 
-    fn update_value(&mut self, key: String, value: String) -> Result<(), ()> {
-        todo!();
+    /// Updates the value by key on disk storage
+    fn update_value(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> KVFuture<'_, ()> {
+        let state = self.state.clone();
+        Box::pin(run_blocking(move || {
+            state.update_value_sync(key, value, expires_at)
+        }))
     }
+
+    /// Get all Keys Collection
+    fn get_all_keys(&self) -> KVFuture<'_, Vec<String>> {
+        let state = self.state.clone();
+        Box::pin(run_blocking(move || state.get_all_keys_sync()))
+    }
+
+    fn set_expiry(&self, key: String, expires_at: DateTime<Utc>) -> KVFuture<'_, ()> {
+        let state = self.state.clone();
+        Box::pin(run_blocking(move || {
+            state.set_expiry_sync(key, Some(expires_at))
+        }))
+    }
+
+    fn get_expiry(&self, key: String) -> KVFuture<'_, Option<DateTime<Utc>>> {
+        let state = self.state.clone();
+        Box::pin(run_blocking(move || state.get_expiry_sync(key)))
+    }
+
+    fn persist(&self, key: String) -> KVFuture<'_, ()> {
+        let state = self.state.clone();
+        Box::pin(run_blocking(move || state.set_expiry_sync(key, None)))
+    }
+
+    fn evict_expired(&self) -> KVFuture<'_, usize> {
+        let state = self.state.clone();
+        Box::pin(run_blocking(move || state.evict_expired_sync()))
+    }
+
+    /// Rewrites log with only live keys and atomically swaps it in
+    fn compact(&self) -> KVFuture<'_, ()> {
+        let state = self.state.clone();
+        Box::pin(run_blocking(move || {
+            let mut log = state.log.lock().unwrap();
+            Ok(state.compact_locked(&mut log)?)
+        }))
+    }
+
+    fn watch_hub(&self) -> &KVWatchHub {
+        &self.state.watch_hub
+    }
+}
+
+/// Runs operation on actix blocking thread pool
+async fn run_blocking<T, F>(operation: F) -> Result<T, KVError>
+where
+    F: FnOnce() -> Result<T, KVError> + Send + 'static,
+    T: Send + 'static,
+{
+    web::block(operation).await.map_err(|e| match e {
+        BlockingError::Error(e) => e,
+        BlockingError::Canceled => KVError::Storage(String::from("blocking operation canceled")),
+    })
 }
 
 /// To choose which KV type to use
+#[derive(Debug, PartialEq)]
 pub enum KVType {
     INMemory,
     Durable,
 }
+
+impl FromStr for KVType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "in_memory" | "inmemory" | "memory" => Ok(KVType::INMemory),
+            "durable" | "disk" => Ok(KVType::Durable),
+            other => Err(format!("unknown kv type \"{}\"", other)),
+        }
+    }
+}
@@ -0,0 +1,392 @@
+#[cfg(test)]
+mod kv_store_tests {
+    use crate::kv_model::{
+        DurableKVStore, ExpiryDTO, InMemoryKVStore, KVError, KVStore, KeyExpiryDTO,
+    };
+    use crate::sharded_kv_graph::ShardedKVStore;
+    use chrono::{Duration, TimeZone, Utc};
+    use std::fs::OpenOptions;
+    use std::future::Future;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    fn temp_data_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("avtandb_kv_test_{}", Uuid::new_v4()))
+    }
+
+    fn block_on<F: Future + 'static>(fut: F) -> F::Output {
+        actix_web::rt::System::new("kv_store_tests").block_on(fut)
+    }
+
+    /// Behaviour every KVStore backend has to share
+    async fn check_kv_store_behaviour(store: Box<dyn KVStore>) {
+        let adding_result = store
+            .add_value(String::from("foo"), String::from("bar"), None)
+            .await;
+        assert!(adding_result.is_ok());
+        assert_eq!(
+            "bar",
+            store.get_value(String::from("foo")).await.unwrap().as_str()
+        );
+
+        // duplicate add fails and keeps old value
+        let duplicate_result = store
+            .add_value(String::from("foo"), String::from("baz"), None)
+            .await;
+        assert_eq!(
+            Err(KVError::DuplicateKey(String::from("foo"))),
+            duplicate_result
+        );
+        assert_eq!(
+            "bar",
+            store.get_value(String::from("foo")).await.unwrap().as_str()
+        );
+
+        // update on missing key fails
+        let updating_result = store
+            .update_value(String::from("lol"), String::from("kek"), None)
+            .await;
+        assert_eq!(
+            Err(KVError::KeyNotFound(String::from("lol"))),
+            updating_result
+        );
+        assert_eq!(
+            Err(KVError::KeyNotFound(String::from("lol"))),
+            store.get_value(String::from("lol")).await
+        );
+
+        // remove on missing key fails
+        assert_eq!(
+            Err(KVError::KeyNotFound(String::from("lol"))),
+            store.remove_value(String::from("lol")).await
+        );
+
+        store
+            .update_value(String::from("foo"), String::from("baz"), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            "baz",
+            store.get_value(String::from("foo")).await.unwrap().as_str()
+        );
+        assert_eq!(
+            vec![String::from("foo")],
+            store.get_all_keys().await.unwrap()
+        );
+
+        store.remove_value(String::from("foo")).await.unwrap();
+        assert!(store.get_value(String::from("foo")).await.is_err());
+        assert_eq!(0, store.get_all_keys().await.unwrap().len());
+    }
+
+    /// Expiry behaviour every KVStore backend has to share
+    async fn check_kv_store_expiry(store: Box<dyn KVStore>) {
+        let past = Utc::now() - Duration::seconds(1);
+        let future = Utc::now() + Duration::hours(1);
+
+        store
+            .add_value(String::from("foo"), String::from("bar"), Some(future))
+            .await
+            .unwrap();
+        store
+            .add_value(String::from("lol"), String::from("kek"), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            Some(future),
+            store.get_expiry(String::from("foo")).await.unwrap()
+        );
+        assert_eq!(None, store.get_expiry(String::from("lol")).await.unwrap());
+
+        // update without expiry keeps it
+        store
+            .update_value(String::from("foo"), String::from("baz"), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            Some(future),
+            store.get_expiry(String::from("foo")).await.unwrap()
+        );
+
+        store.persist(String::from("foo")).await.unwrap();
+        assert_eq!(None, store.get_expiry(String::from("foo")).await.unwrap());
+
+        // expired key is invisible before eviction
+        store.set_expiry(String::from("foo"), past).await.unwrap();
+        assert_eq!(
+            Err(KVError::KeyNotFound(String::from("foo"))),
+            store.get_value(String::from("foo")).await
+        );
+        assert_eq!(
+            Err(KVError::KeyNotFound(String::from("foo"))),
+            store.persist(String::from("foo")).await
+        );
+        assert_eq!(
+            vec![String::from("lol")],
+            store.get_all_keys().await.unwrap()
+        );
+
+        // expired key can be added again
+        store
+            .add_value(String::from("foo"), String::from("new"), Some(past))
+            .await
+            .unwrap();
+        assert_eq!(1, store.evict_expired().await.unwrap());
+        assert_eq!(0, store.evict_expired().await.unwrap());
+        assert_eq!(
+            vec![String::from("lol")],
+            store.get_all_keys().await.unwrap()
+        );
+    }
+
+    #[test]
+    fn in_memory_kv_store_behaviour_passed() {
+        let store = InMemoryKVStore::new();
+        block_on(check_kv_store_behaviour(Box::new(store)));
+    }
+
+    #[test]
+    fn durable_kv_store_behaviour_passed() {
+        let data_dir = temp_data_dir();
+        let store = DurableKVStore::new(data_dir.clone()).unwrap();
+        block_on(check_kv_store_behaviour(Box::new(store)));
+        let _ = std::fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn sharded_kv_store_behaviour_passed() {
+        let store = ShardedKVStore::new(4);
+        block_on(check_kv_store_behaviour(Box::new(store)));
+    }
+
+    #[test]
+    fn in_memory_kv_store_expiry_passed() {
+        let store = InMemoryKVStore::new();
+        block_on(check_kv_store_expiry(Box::new(store)));
+    }
+
+    #[test]
+    fn sharded_kv_store_expiry_passed() {
+        let store = ShardedKVStore::new(4);
+        block_on(check_kv_store_expiry(Box::new(store)));
+    }
+
+    #[test]
+    fn durable_kv_store_expiry_passed() {
+        let data_dir = temp_data_dir();
+        let store = DurableKVStore::new(data_dir.clone()).unwrap();
+        block_on(check_kv_store_expiry(Box::new(store)));
+        let _ = std::fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn durable_expiry_survives_reopen_passed() {
+        let data_dir = temp_data_dir();
+        let store_dir = data_dir.clone();
+        block_on(async move {
+            // millisecond precision is what the log keeps
+            let future = Utc
+                .timestamp_millis_opt(Utc::now().timestamp_millis() + 3_600_000)
+                .single();
+            let store = DurableKVStore::new(store_dir.clone()).unwrap();
+            store
+                .add_value(String::from("foo"), String::from("bar"), future)
+                .await
+                .unwrap();
+            store
+                .add_value(String::from("lol"), String::from("kek"), future)
+                .await
+                .unwrap();
+            store.persist(String::from("lol")).await.unwrap();
+            store
+                .add_value(String::from("old"), String::from("x"), None)
+                .await
+                .unwrap();
+            store
+                .set_expiry(String::from("old"), Utc::now() - Duration::seconds(1))
+                .await
+                .unwrap();
+            drop(store);
+
+            let store = DurableKVStore::new(store_dir.clone()).unwrap();
+
+            assert_eq!(future, store.get_expiry(String::from("foo")).await.unwrap());
+            assert_eq!(None, store.get_expiry(String::from("lol")).await.unwrap());
+            assert!(store.get_value(String::from("old")).await.is_err());
+            store.compact().await.unwrap();
+            drop(store);
+            let store = DurableKVStore::new(store_dir.clone()).unwrap();
+            assert_eq!(future, store.get_expiry(String::from("foo")).await.unwrap());
+        });
+        let _ = std::fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn expiry_dto_passed() {
+        let now = Utc::now();
+        let ttl = ExpiryDTO {
+            ttl: Some(10),
+            expire_at: None,
+        };
+        let expire_at = ExpiryDTO {
+            ttl: None,
+            expire_at: Some(String::from("2030-01-02T03:04:05Z")),
+        };
+        let both = ExpiryDTO {
+            ttl: Some(10),
+            expire_at: Some(String::from("2030-01-02T03:04:05Z")),
+        };
+        let malformed = ExpiryDTO {
+            ttl: None,
+            expire_at: Some(String::from("tomorrow")),
+        };
+
+        assert_eq!(None, ExpiryDTO::default().expires_at(now).unwrap());
+        assert_eq!(
+            Some(now + Duration::seconds(10)),
+            ttl.expires_at(now).unwrap()
+        );
+        assert_eq!(
+            "2030-01-02T03:04:05+00:00",
+            expire_at.expires_at(now).unwrap().unwrap().to_rfc3339()
+        );
+        assert!(matches!(
+            both.expires_at(now),
+            Err(KVError::InvalidExpiry(_))
+        ));
+        assert!(matches!(
+            malformed.expires_at(now),
+            Err(KVError::InvalidExpiry(_))
+        ));
+        assert!(ExpiryDTO {
+            ttl: Some(u64::MAX),
+            expire_at: None
+        }
+        .expires_at(now)
+        .is_err());
+    }
+
+    #[test]
+    fn key_expiry_dto_rounds_up_passed() {
+        let now = Utc::now();
+
+        let expiry = KeyExpiryDTO::new(Some(now + Duration::milliseconds(1500)), now);
+
+        assert_eq!(Some(2), expiry.ttl);
+        assert_eq!(
+            KeyExpiryDTO {
+                ttl: None,
+                expires_at: None
+            },
+            KeyExpiryDTO::new(None, now)
+        );
+    }
+
+    #[test]
+    fn durable_values_survive_reopen_passed() {
+        let data_dir = temp_data_dir();
+        let store_dir = data_dir.clone();
+        block_on(async move {
+            let store = DurableKVStore::new(store_dir.clone()).unwrap();
+            store
+                .add_value(String::from("foo"), String::from("bar"), None)
+                .await
+                .unwrap();
+            store
+                .add_value(String::from("lol"), String::from("kek"), None)
+                .await
+                .unwrap();
+            store
+                .update_value(String::from("foo"), String::from("baz"), None)
+                .await
+                .unwrap();
+            store.remove_value(String::from("lol")).await.unwrap();
+            drop(store);
+
+            let store = DurableKVStore::new(store_dir.clone()).unwrap();
+
+            assert_eq!(
+                "baz",
+                store.get_value(String::from("foo")).await.unwrap().as_str()
+            );
+            assert!(store.get_value(String::from("lol")).await.is_err());
+            assert_eq!(1, store.get_all_keys().await.unwrap().len());
+        });
+        let _ = std::fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn durable_torn_tail_is_discarded_on_reopen_passed() {
+        let data_dir = temp_data_dir();
+        let store_dir = data_dir.clone();
+        block_on(async move {
+            let store = DurableKVStore::new(store_dir.clone()).unwrap();
+            store
+                .add_value(String::from("foo"), String::from("bar"), None)
+                .await
+                .unwrap();
+            drop(store);
+
+            // simulate crash in the middle of appending a record
+            let mut log_file = OpenOptions::new()
+                .append(true)
+                .open(store_dir.join("kv.log"))
+                .unwrap();
+            log_file.write_all(b"{\"Put\":{\"key\":\"lo").unwrap();
+            drop(log_file);
+
+            let store = DurableKVStore::new(store_dir.clone()).unwrap();
+            store
+                .add_value(String::from("lol"), String::from("kek"), None)
+                .await
+                .unwrap();
+            drop(store);
+            let store = DurableKVStore::new(store_dir.clone()).unwrap();
+
+            assert_eq!(
+                "bar",
+                store.get_value(String::from("foo")).await.unwrap().as_str()
+            );
+            assert_eq!(
+                "kek",
+                store.get_value(String::from("lol")).await.unwrap().as_str()
+            );
+        });
+        let _ = std::fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn durable_compaction_keeps_live_values_passed() {
+        let data_dir = temp_data_dir();
+        let store_dir = data_dir.clone();
+        block_on(async move {
+            let store = DurableKVStore::new(store_dir.clone()).unwrap();
+            store
+                .add_value(String::from("foo"), String::from("0"), None)
+                .await
+                .unwrap();
+            for i in 1..2000 {
+                store
+                    .update_value(String::from("foo"), i.to_string(), None)
+                    .await
+                    .unwrap();
+            }
+            store.compact().await.unwrap();
+            drop(store);
+
+            let log_len = std::fs::read_to_string(store_dir.join("kv.log"))
+                .unwrap()
+                .lines()
+                .count();
+            let store = DurableKVStore::new(store_dir.clone()).unwrap();
+
+            assert_eq!(1, log_len);
+            assert_eq!(
+                "1999",
+                store.get_value(String::from("foo")).await.unwrap().as_str()
+            );
+        });
+        let _ = std::fs::remove_dir_all(data_dir);
+    }
+}
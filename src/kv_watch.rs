@@ -0,0 +1,296 @@
+use crate::kv_model::KVError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+
+/// Keys a subscription follows
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyPattern {
+    Key(String),
+    Prefix(String),
+    /// `*` matches any run of characters, `?` matches single character
+    Glob(String),
+}
+
+impl KeyPattern {
+    pub fn matches(&self, key: &str) -> bool {
+        match self {
+            KeyPattern::Key(x) => x == key,
+            KeyPattern::Prefix(x) => key.starts_with(x.as_str()),
+            KeyPattern::Glob(x) => glob_matches(x, key),
+        }
+    }
+}
+
+/// Pattern of watch request, exactly one field is set
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct KeyPatternDTO {
+    pub key: Option<String>,
+    pub prefix: Option<String>,
+    pub glob: Option<String>,
+}
+
+impl KeyPatternDTO {
+    pub fn pattern(self) -> Result<KeyPattern, KVError> {
+        match (self.key, self.prefix, self.glob) {
+            (Some(x), None, None) => Ok(KeyPattern::Key(x)),
+            (None, Some(x), None) => Ok(KeyPattern::Prefix(x)),
+            (None, None, Some(x)) => Ok(KeyPattern::Glob(x)),
+            _ => Err(KVError::InvalidPattern(String::from(
+                "set exactly one of key, prefix or glob",
+            ))),
+        }
+    }
+}
+
+/// Iterative wildcard match, backtracking only to the last `*`
+fn glob_matches(pattern: &str, key: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let key: Vec<char> = key.chars().collect();
+    let (mut p, mut k) = (0, 0);
+    // position of last `*` and key position it was tried at
+    let mut star: Option<(usize, usize)> = None;
+
+    while k < key.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == key[k]) {
+            p += 1;
+            k += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, k));
+            p += 1;
+        } else if let Some((star_p, star_k)) = star {
+            // let the `*` swallow one more character
+            star = Some((star_p, star_k + 1));
+            p = star_p + 1;
+            k = star_k + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|x| *x == '*')
+}
+
+/// What happened to the key
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KVChangeKind {
+    Added,
+    Updated,
+    Removed,
+    /// Dropped by expiry sweep
+    Expired,
+}
+
+/// Change pushed to subscriber, `old_value` is None for added key, `new_value` for removed one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KVChangeEventDTO {
+    pub subscription: u64,
+    /// Store wide, grows by one with every change
+    pub revision: u64,
+    pub event: KVChangeKind,
+    pub key: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// Changes one session may have undelivered, session falling further behind is closed
+pub const WATCH_BUFFER_SIZE: usize = 1024;
+
+struct Subscription {
+    session: u64,
+    pattern: KeyPattern,
+    /// Revision read at subscribe, changes up to it are not sent
+    after: u64,
+}
+
+#[derive(Default)]
+struct WatchState {
+    next_session: u64,
+    next_subscription: u64,
+    sessions: HashMap<u64, Sender<KVChangeEventDTO>>,
+    subscriptions: HashMap<u64, Subscription>,
+}
+
+/// Subscriptions of one KV store.
+///
+/// Stores publish while still holding their write lock, so revision order is
+/// the order changes were applied in. Without subscribers publishing only
+/// bumps revision, a change racing with the first subscribe of shards sharing
+/// hub may then arrive after one with higher revision.
+#[derive(Default)]
+pub struct KVWatchHub {
+    revision: AtomicU64,
+    /// Number of subscriptions, read before taking state lock
+    subscribers: AtomicUsize,
+    state: Mutex<WatchState>,
+}
+
+impl KVWatchHub {
+    pub fn new() -> Self {
+        KVWatchHub::default()
+    }
+
+    /// Registers session sending to bounded channel, returns its id and receiver
+    fn open_session(&self) -> (u64, Receiver<KVChangeEventDTO>) {
+        let (sender, receiver) = mpsc::channel(WATCH_BUFFER_SIZE);
+        let mut state = self.state.lock().unwrap();
+        state.next_session += 1;
+        let id = state.next_session;
+        state.sessions.insert(id, sender);
+        (id, receiver)
+    }
+
+    /// Drops session sender and its subscriptions, which ends receiver
+    fn close_session(&self, state: &mut WatchState, session: u64) {
+        state.sessions.remove(&session);
+        let before = state.subscriptions.len();
+        state.subscriptions.retain(|_, x| x.session != session);
+        self.subscribers
+            .fetch_sub(before - state.subscriptions.len(), Ordering::SeqCst);
+    }
+
+    /// Registers pattern, matching changes go to session until unsubscribe.
+    /// Returns subscription id and current revision, every change after it is sent.
+    /// Fails if session was closed for falling behind.
+    fn subscribe(&self, session: u64, pattern: KeyPattern) -> Result<(u64, u64), KVError> {
+        let mut state = self.state.lock().unwrap();
+        if !state.sessions.contains_key(&session) {
+            return Err(KVError::WatchClosed);
+        }
+        // counted before revision is read, so publish bumping revision later sees it
+        self.subscribers.fetch_add(1, Ordering::SeqCst);
+        let after = self.revision.load(Ordering::SeqCst);
+        state.next_subscription += 1;
+        let id = state.next_subscription;
+        state.subscriptions.insert(
+            id,
+            Subscription {
+                session,
+                pattern,
+                after,
+            },
+        );
+        Ok((id, after))
+    }
+
+    /// Returns false if there is no such subscription
+    fn unsubscribe(&self, id: u64) -> bool {
+        let removed = self.state.lock().unwrap().subscriptions.remove(&id);
+        if removed.is_some() {
+            self.subscribers.fetch_sub(1, Ordering::SeqCst);
+        }
+        removed.is_some()
+    }
+
+    /// Assigns next revision to change and sends it to every matching subscription
+    pub fn publish(
+        &self,
+        event: KVChangeKind,
+        key: &str,
+        old_value: Option<&Arc<String>>,
+        new_value: Option<&Arc<String>>,
+    ) {
+        if self.subscribers.load(Ordering::SeqCst) == 0 {
+            let revision = self.revision.fetch_add(1, Ordering::SeqCst) + 1;
+            if self.subscribers.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            let mut state = self.state.lock().unwrap();
+            self.send(&mut state, revision, event, key, old_value, new_value);
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        let revision = self.revision.fetch_add(1, Ordering::SeqCst) + 1;
+        self.send(&mut state, revision, event, key, old_value, new_value);
+    }
+
+    fn send(
+        &self,
+        state: &mut WatchState,
+        revision: u64,
+        event: KVChangeKind,
+        key: &str,
+        old_value: Option<&Arc<String>>,
+        new_value: Option<&Arc<String>>,
+    ) {
+        let WatchState {
+            sessions,
+            subscriptions,
+            ..
+        } = state;
+        // session whose buffer is full or receiver is gone is closed after
+        let mut failed = Vec::new();
+        for (id, subscription) in subscriptions.iter() {
+            if revision <= subscription.after || !subscription.pattern.matches(key) {
+                continue;
+            }
+            let sender = match sessions.get_mut(&subscription.session) {
+                None => continue,
+                Some(x) => x,
+            };
+            let change = KVChangeEventDTO {
+                subscription: *id,
+                revision,
+                event,
+                key: String::from(key),
+                old_value: old_value.map(|x| x.to_string()),
+                new_value: new_value.map(|x| x.to_string()),
+            };
+            if sender.try_send(change).is_err() {
+                sessions.remove(&subscription.session);
+                failed.push(subscription.session);
+            }
+        }
+        for session in failed {
+            self.close_session(state, session);
+        }
+    }
+}
+
+/// Subscriptions of one websocket connection, all delivered to one receiver.
+/// Receiver ends when session is closed, by close or by hub when it falls behind.
+pub struct KVWatchSession {
+    id: u64,
+    subscriptions: Vec<u64>,
+}
+
+impl KVWatchSession {
+    pub fn new(hub: &KVWatchHub) -> (Self, Receiver<KVChangeEventDTO>) {
+        let (id, receiver) = hub.open_session();
+        let session = KVWatchSession {
+            id,
+            subscriptions: Vec::new(),
+        };
+        (session, receiver)
+    }
+
+    /// Subscription id and revision it starts after, see KVWatchHub::subscribe
+    pub fn subscribe(
+        &mut self,
+        hub: &KVWatchHub,
+        pattern: KeyPattern,
+    ) -> Result<(u64, u64), KVError> {
+        let (id, revision) = hub.subscribe(self.id, pattern)?;
+        self.subscriptions.push(id);
+        Ok((id, revision))
+    }
+
+    /// Only subscriptions made by this session can be cancelled
+    pub fn unsubscribe(&mut self, hub: &KVWatchHub, id: u64) -> bool {
+        match self.subscriptions.iter().position(|x| *x == id) {
+            None => false,
+            Some(position) => {
+                self.subscriptions.remove(position);
+                hub.unsubscribe(id)
+            }
+        }
+    }
+
+    /// Cancels all subscriptions, which closes receiver
+    pub fn close(self, hub: &KVWatchHub) {
+        let mut state = hub.state.lock().unwrap();
+        hub.close_session(&mut state, self.id);
+    }
+}
@@ -0,0 +1,220 @@
+#[cfg(test)]
+mod watch_tests {
+    use crate::kv_model::KVError;
+    use crate::kv_model::{DurableKVStore, InMemoryKVStore, KVStore};
+    use crate::kv_watch::{
+        KVChangeEventDTO, KVChangeKind, KVWatchHub, KVWatchSession, KeyPattern, WATCH_BUFFER_SIZE,
+    };
+    use crate::sharded_kv_graph::ShardedKVStore;
+    use chrono::{Duration, Utc};
+    use std::future::Future;
+    use std::sync::Arc;
+    use tokio::sync::mpsc::Receiver;
+    use uuid::Uuid;
+
+    fn block_on<F: Future + 'static>(fut: F) -> F::Output {
+        actix_web::rt::System::new("kv_watch_tests").block_on(fut)
+    }
+
+    fn drain(changes: &mut Receiver<KVChangeEventDTO>) -> Vec<KVChangeEventDTO> {
+        let mut drained = Vec::new();
+        while let Ok(change) = changes.try_recv() {
+            drained.push(change);
+        }
+        drained
+    }
+
+    fn glob(pattern: &str) -> KeyPattern {
+        KeyPattern::Glob(String::from(pattern))
+    }
+
+    #[test]
+    fn key_pattern_matches_passed() {
+        assert!(KeyPattern::Key(String::from("user:1")).matches("user:1"));
+        assert!(!KeyPattern::Key(String::from("user:1")).matches("user:10"));
+        assert!(KeyPattern::Prefix(String::from("user:")).matches("user:10"));
+        assert!(!KeyPattern::Prefix(String::from("user:")).matches("users"));
+
+        assert!(glob("user:*:name").matches("user:42:name"));
+        assert!(glob("user:*:name").matches("user::name"));
+        assert!(!glob("user:*:name").matches("user:42:email"));
+        assert!(glob("a*b*c").matches("axxbyybc"));
+        assert!(!glob("a*b*c").matches("axxbyy"));
+        assert!(glob("k?y").matches("key"));
+        assert!(!glob("k?y").matches("ky"));
+        assert!(glob("*").matches(""));
+        assert!(glob("ключ*").matches("ключ1"));
+    }
+
+    #[test]
+    fn hub_delivers_matching_changes_passed() {
+        let hub = KVWatchHub::new();
+        let (mut session, mut changes) = KVWatchSession::new(&hub);
+        let value = Arc::new(String::from("Ann"));
+        hub.publish(KVChangeKind::Added, "user:0", None, Some(&value));
+        let (users, revision) = session
+            .subscribe(&hub, KeyPattern::Prefix(String::from("user:")))
+            .unwrap();
+        assert_eq!(1, revision);
+
+        hub.publish(KVChangeKind::Added, "user:1", None, Some(&value));
+        hub.publish(KVChangeKind::Added, "order:1", None, Some(&value));
+        hub.publish(KVChangeKind::Removed, "user:1", Some(&value), None);
+
+        let received = drain(&mut changes);
+        assert_eq!(2, received.len());
+        assert_eq!((users, 2), (received[0].subscription, received[0].revision));
+        assert_eq!(Some(String::from("Ann")), received[0].new_value);
+        assert_eq!(KVChangeKind::Removed, received[1].event);
+        assert_eq!(4, received[1].revision);
+        let (_, revision) = session
+            .subscribe(&hub, KeyPattern::Key(String::from("order:2")))
+            .unwrap();
+        assert_eq!(4, revision);
+
+        assert!(session.unsubscribe(&hub, users));
+        assert!(!session.unsubscribe(&hub, users));
+        hub.publish(KVChangeKind::Added, "user:2", None, Some(&value));
+        assert!(drain(&mut changes).is_empty());
+    }
+
+    #[test]
+    fn closed_session_ends_receiver_passed() {
+        block_on(async {
+            let hub = KVWatchHub::new();
+            let (mut session, mut changes) = KVWatchSession::new(&hub);
+            session
+                .subscribe(&hub, KeyPattern::Key(String::from("foo")))
+                .unwrap();
+            session.subscribe(&hub, glob("f*")).unwrap();
+
+            session.close(&hub);
+
+            assert_eq!(None, changes.recv().await);
+        });
+    }
+
+    #[test]
+    fn lagging_session_closed_failed() {
+        block_on(async {
+            let hub = KVWatchHub::new();
+            let (mut session, mut changes) = KVWatchSession::new(&hub);
+            let (mut other, mut other_changes) = KVWatchSession::new(&hub);
+            session.subscribe(&hub, glob("*")).unwrap();
+            session.subscribe(&hub, glob("f*")).unwrap();
+            other.subscribe(&hub, glob("bar")).unwrap();
+            let value = Arc::new(String::from("x"));
+
+            for _ in 0..WATCH_BUFFER_SIZE {
+                hub.publish(KVChangeKind::Added, "bar", None, Some(&value));
+            }
+            drain(&mut other_changes);
+            hub.publish(KVChangeKind::Added, "bar", None, Some(&value));
+
+            // buffered changes are still delivered, then receiver ends
+            assert_eq!(WATCH_BUFFER_SIZE, drain(&mut changes).len());
+            assert_eq!(None, changes.recv().await);
+            assert_eq!(
+                Err(KVError::WatchClosed),
+                session.subscribe(&hub, glob("*"))
+            );
+            assert_eq!(1, drain(&mut other_changes).len());
+            assert!(other.subscribe(&hub, glob("foo")).is_ok());
+        });
+    }
+
+    /// Changes every KVStore backend has to publish
+    async fn check_kv_store_changes(store: Box<dyn KVStore>) {
+        let (mut session, mut changes) = KVWatchSession::new(store.watch_hub());
+        let (subscription, _) = session.subscribe(store.watch_hub(), glob("foo*")).unwrap();
+
+        store
+            .add_value(String::from("foo"), String::from("a"), None)
+            .await
+            .unwrap();
+        store
+            .add_value(String::from("bar"), String::from("x"), None)
+            .await
+            .unwrap();
+        store
+            .update_value(String::from("foo"), String::from("b"), None)
+            .await
+            .unwrap();
+        store.remove_value(String::from("foo")).await.unwrap();
+        // failed operations publish nothing
+        assert!(store.remove_value(String::from("foo")).await.is_err());
+        store
+            .add_value(
+                String::from("food"),
+                String::from("c"),
+                Some(Utc::now() - Duration::seconds(1)),
+            )
+            .await
+            .unwrap();
+        store.evict_expired().await.unwrap();
+        // expired key found lazily is reported too, whether removed or replaced
+        store
+            .add_value(
+                String::from("fool"),
+                String::from("d"),
+                Some(Utc::now() - Duration::seconds(1)),
+            )
+            .await
+            .unwrap();
+        assert!(store.remove_value(String::from("fool")).await.is_err());
+        store
+            .add_value(String::from("fool"), String::from("e"), None)
+            .await
+            .unwrap();
+
+        let received = drain(&mut changes);
+        let summary: Vec<_> = received
+            .iter()
+            .map(|x| {
+                assert_eq!(subscription, x.subscription);
+                (
+                    x.revision,
+                    x.event,
+                    x.key.as_str(),
+                    x.old_value.as_deref(),
+                    x.new_value.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (1, KVChangeKind::Added, "foo", None, Some("a")),
+                (3, KVChangeKind::Updated, "foo", Some("a"), Some("b")),
+                (4, KVChangeKind::Removed, "foo", Some("b"), None),
+                (5, KVChangeKind::Added, "food", None, Some("c")),
+                (6, KVChangeKind::Expired, "food", Some("c"), None),
+                (7, KVChangeKind::Added, "fool", None, Some("d")),
+                (8, KVChangeKind::Expired, "fool", Some("d"), None),
+                (9, KVChangeKind::Added, "fool", None, Some("e")),
+            ],
+            summary
+        );
+        session.close(store.watch_hub());
+    }
+
+    #[test]
+    fn in_memory_kv_store_changes_passed() {
+        let store = InMemoryKVStore::new();
+        block_on(check_kv_store_changes(Box::new(store)));
+    }
+
+    #[test]
+    fn sharded_kv_store_changes_passed() {
+        let store = ShardedKVStore::new(4);
+        block_on(check_kv_store_changes(Box::new(store)));
+    }
+
+    #[test]
+    fn durable_kv_store_changes_passed() {
+        let data_dir =
+            std::env::temp_dir().join(format!("avtandb_kv_watch_test_{}", Uuid::new_v4()));
+        let store = DurableKVStore::new(data_dir.clone()).unwrap();
+        block_on(check_kv_store_changes(Box::new(store)));
+        let _ = std::fs::remove_dir_all(data_dir);
+    }
+}
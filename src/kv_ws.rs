@@ -1,128 +1,159 @@
+use crate::api_error::ErrorResponceDto;
+use crate::kv_model::{ExpiryDTO, KVError, KVStore, KeyExpiryDTO};
+use crate::kv_watch::{KVWatchSession, KeyPatternDTO};
 use crate::AppState;
-use actix_send_websocket::{Message, WebSocket};
+use actix_send_websocket::{CloseCode, Message, WebSocket};
 use actix_web::web;
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
+/// KV operation requested over websocket, `op` names it
 #[derive(Debug, Serialize, Deserialize)]
-pub struct AddKVRequestDto {
-    pub key: String,
-    pub value: String,
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum KVOperation {
+    /// Optional `ttl` or `expire_at` makes key expire
+    Add {
+        key: String,
+        value: String,
+        #[serde(flatten)]
+        expiry: ExpiryDTO,
+    },
+    Get {
+        key: String,
+    },
+    /// Without `ttl` or `expire_at` key keeps its expiry
+    Update {
+        key: String,
+        value: String,
+        #[serde(flatten)]
+        expiry: ExpiryDTO,
+    },
+    Delete {
+        key: String,
+    },
+    Keys,
+    /// One of `ttl` or `expire_at` is required
+    Expire {
+        key: String,
+        #[serde(flatten)]
+        expiry: ExpiryDTO,
+    },
+    Ttl {
+        key: String,
+    },
+    Persist {
+        key: String,
+    },
+    /// Pushes change of every key matching one of `key`, `prefix` or `glob`
+    Watch {
+        #[serde(flatten)]
+        pattern: KeyPatternDTO,
+    },
+    Unwatch {
+        subscription: u64,
+    },
 }
 
+/// Message of KV websocket
 #[derive(Debug, Serialize, Deserialize)]
-pub struct GetKVRequestDto {
-    pub key: String,
+pub struct KVRequestDto {
+    /// Any JSON value chosen by client, echoed in responce to match it with request
+    #[serde(default)]
+    pub id: Option<Value>,
+    #[serde(flatten)]
+    pub operation: KVOperation,
 }
 
+/// Answer to every request, `error` is set if and only if it failed
 #[derive(Debug, Serialize, Deserialize)]
 pub struct KVResponceDto {
-    pub error: String,
-    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponceDto>,
+    /// Answer of get
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Answer of keys
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keys: Option<Vec<String>>,
+    /// Answer of ttl
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiry: Option<KeyExpiryDTO>,
+    /// Answer of watch, carried by every change it pushes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subscription: Option<u64>,
+    /// Answer of watch, revision of the latest change before subscription
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u64>,
 }
 
-pub async fn add_kv_ws(data: web::Data<AppState>, ws: WebSocket) -> impl actix_web::Responder {
-    init_ws_conn(data, ws, WsMethod::AddKvWs).await
-}
+impl KVResponceDto {
+    fn ok(id: Option<Value>) -> Self {
+        KVResponceDto {
+            id,
+            error: None,
+            value: None,
+            keys: None,
+            expiry: None,
+            subscription: None,
+            revision: None,
+        }
+    }
 
-pub async fn get_kv_ws(data: web::Data<AppState>, ws: WebSocket) -> impl actix_web::Responder {
-    init_ws_conn(data, ws, WsMethod::GetKvWs).await
-}
+    fn error(id: Option<Value>, error: ErrorResponceDto) -> Self {
+        KVResponceDto {
+            error: Some(error),
+            ..KVResponceDto::ok(id)
+        }
+    }
 
-enum WsMethod {
-    AddKvWs,
-    GetKvWs,
-    DeleteKvWs,
-    UpdateKvWs,
+    /// Request text is not a valid request document
+    fn invalid_body(id: Option<Value>, e: serde_json::Error) -> Self {
+        KVResponceDto::error(
+            id,
+            ErrorResponceDto {
+                code: String::from("INVALID_BODY"),
+                message: e.to_string(),
+            },
+        )
+    }
 }
 
-async fn init_ws_conn(
-    data: web::Data<AppState>,
-    ws: WebSocket,
-    method: WsMethod,
-) -> impl actix_web::Responder {
+/// Single KV websocket endpoint, every text message is one KVRequestDto.
+/// Changes of watched keys are pushed as KVChangeEventDTO messages.
+pub async fn kv_ws(data: web::Data<AppState>, ws: WebSocket) -> impl actix_web::Responder {
     // stream is the async iterator of incoming client websocket messages.
     // res is the response we return to client.
     // tx is a sender to push new websocket message to client response.
     let (mut stream, res, tx) = ws.into_parts();
+    let (mut session, mut changes) = KVWatchSession::new(data.kv_collection.watch_hub());
+
+    // forwards changes until session is closed or client is gone,
+    // session closed by hub for falling behind closes connection
+    let changes_tx = tx.clone();
+    actix_web::rt::spawn(async move {
+        while let Some(change) = changes.recv().await {
+            let text = serde_json::to_string(&change).expect("err serializing");
+            if changes_tx.text(text).is_err() {
+                return;
+            }
+        }
+        let _ = changes_tx.close(Some(CloseCode::Policy.into()));
+    });
+
     // spawn the stream handling so we don't block the response to client.
     actix_web::rt::spawn(async move {
+        let kv_collection = data.kv_collection.as_ref();
         while let Some(Ok(msg)) = stream.next().await {
             let result = match msg {
-                // we echo text message and ping message to client.
-                Message::Text(text) => match method {
-                    WsMethod::AddKvWs => {
-                        let add_kv_request_dto_res: serde_json::Result<AddKVRequestDto> =
-                            serde_json::from_str(&text);
-                        let add_kv_request_dto = match add_kv_request_dto_res {
-                            Err(e) => {
-                                let _ = tx.text(format!("Error serializing {e}"));
-                                continue;
-                            }
-                            Ok(a) => a,
-                        };
-                        let add_val_res = data
-                            .kv_collection
-                            .clone()
-                            .add_value(add_kv_request_dto.key, add_kv_request_dto.value)
-                            .await;
-                        if let Err(_) = add_val_res {
-                            let responce = KVResponceDto {
-                                error: String::from("Add value error"),
-                                value: String::from(""),
-                            };
-                            let answer = serde_json::to_string(&responce).expect("err serializing");
-                            let _ = tx.text(answer);
-                            continue;
-                        }
-                        let responce = KVResponceDto {
-                            error: String::from(""),
-                            value: String::from(""),
-                        };
-                        let answer = serde_json::to_string(&responce).expect("err serializing");
-                        tx.text(answer)
-                    }
-                    WsMethod::GetKvWs => {
-                        let get_kv_request_dto_res: serde_json::Result<GetKVRequestDto> =
-                            serde_json::from_str(&text);
-                        let get_kv_request_dto = match get_kv_request_dto_res {
-                            Err(e) => {
-                                let resp = KVResponceDto {
-                                    error: format!("{e}"),
-                                    value: String::from(""),
-                                };
-                                let _ =
-                                    tx.text(serde_json::to_string(&resp).expect("err serializing"));
-                                continue;
-                            }
-                            Ok(a) => a,
-                        };
-                        let get_val_res = match data
-                            .kv_collection
-                            .clone()
-                            .get_value(get_kv_request_dto.key)
-                            .await
-                        {
-                            Err(_) => {
-                                let _ = tx.text("");
-                                continue;
-                            }
-                            Ok(v) => v,
-                        };
-                        let responce = KVResponceDto {
-                            error: String::from(""),
-                            value: format!("{get_val_res}"),
-                        };
-                        let answer = serde_json::to_string(&responce).expect("err serializing");
-                        tx.text(answer)
-                    }
-                    WsMethod::DeleteKvWs => {
-                        todo!()
-                    }
-                    WsMethod::UpdateKvWs => todo!(),
-                },
+                Message::Text(text) => {
+                    let responce = handle_request(kv_collection, &mut session, &text).await;
+                    tx.text(serde_json::to_string(&responce).expect("err serializing"))
+                }
                 Message::Ping(bytes) => tx.pong(&bytes),
                 Message::Close(reason) => {
-                    println!("{}", reason.clone().unwrap().description.unwrap());
                     let _ = tx.close(reason);
                     // force end the stream when we have a close message.
                     break;
@@ -135,7 +166,76 @@ async fn init_ws_conn(
                 break;
             }
         }
+        session.close(kv_collection.watch_hub());
     });
 
     res
 }
+
+/// Runs request text against store, failures are answered with error responce
+pub async fn handle_request(
+    kv_collection: &dyn KVStore,
+    session: &mut KVWatchSession,
+    text: &str,
+) -> KVResponceDto {
+    // id is picked out first, so even malformed request gets it back
+    let document: Value = match serde_json::from_str(text) {
+        Err(e) => return KVResponceDto::invalid_body(None, e),
+        Ok(v) => v,
+    };
+    let id = document.get("id").cloned();
+    let request: KVRequestDto = match serde_json::from_value(document) {
+        Err(e) => return KVResponceDto::invalid_body(id, e),
+        Ok(r) => r,
+    };
+
+    match run_operation(kv_collection, session, request.operation).await {
+        Ok(responce) => KVResponceDto { id, ..responce },
+        Err(e) => KVResponceDto::error(id, ErrorResponceDto::from(&e)),
+    }
+}
+
+async fn run_operation(
+    kv_collection: &dyn KVStore,
+    session: &mut KVWatchSession,
+    operation: KVOperation,
+) -> Result<KVResponceDto, KVError> {
+    let mut responce = KVResponceDto::ok(None);
+    match operation {
+        KVOperation::Add { key, value, expiry } => {
+            let expires_at = expiry.expires_at(Utc::now())?;
+            kv_collection.add_value(key, value, expires_at).await?;
+        }
+        KVOperation::Get { key } => {
+            let value = kv_collection.get_value(key).await?;
+            responce.value = Some(value.to_string());
+        }
+        KVOperation::Update { key, value, expiry } => {
+            let expires_at = expiry.expires_at(Utc::now())?;
+            kv_collection.update_value(key, value, expires_at).await?;
+        }
+        KVOperation::Delete { key } => kv_collection.remove_value(key).await?,
+        KVOperation::Keys => responce.keys = Some(kv_collection.get_all_keys().await?),
+        KVOperation::Expire { key, expiry } => {
+            let expires_at = expiry.required_expires_at(Utc::now())?;
+            kv_collection.set_expiry(key, expires_at).await?;
+        }
+        KVOperation::Ttl { key } => {
+            let expires_at = kv_collection.get_expiry(key).await?;
+            responce.expiry = Some(KeyExpiryDTO::new(expires_at, Utc::now()));
+        }
+        KVOperation::Persist { key } => kv_collection.persist(key).await?,
+        KVOperation::Watch { pattern } => {
+            let (subscription, revision) =
+                session.subscribe(kv_collection.watch_hub(), pattern.pattern()?)?;
+            responce.subscription = Some(subscription);
+            responce.revision = Some(revision);
+        }
+        KVOperation::Unwatch { subscription } => {
+            if !session.unsubscribe(kv_collection.watch_hub(), subscription) {
+                return Err(KVError::SubscriptionNotFound(subscription));
+            }
+        }
+    }
+    Ok(responce)
+}
@@ -0,0 +1,219 @@
+#[cfg(test)]
+mod kv_websocket_tests {
+    use crate::kv_model::{InMemoryKVStore, KVStore};
+    use crate::kv_watch::KVWatchSession;
+    use crate::kv_ws::{handle_request, KVResponceDto};
+    use serde_json::json;
+    use std::future::Future;
+
+    fn block_on<F: Future + 'static>(fut: F) -> F::Output {
+        actix_web::rt::System::new("kv_ws_tests").block_on(fut)
+    }
+
+    fn error_code(responce: &KVResponceDto) -> Option<&str> {
+        responce.error.as_ref().map(|x| x.code.as_str())
+    }
+
+    #[test]
+    fn multiplexed_operations_passed() {
+        block_on(async {
+            let store = InMemoryKVStore::new();
+            let (mut session, _changes) = KVWatchSession::new(store.watch_hub());
+
+            let added = handle_request(
+                &store,
+                &mut session,
+                r#"{"op": "add", "id": 1, "key": "foo", "value": "bar"}"#,
+            )
+            .await;
+            let got = handle_request(
+                &store,
+                &mut session,
+                r#"{"op": "get", "id": "a", "key": "foo"}"#,
+            )
+            .await;
+            let updated = handle_request(
+                &store,
+                &mut session,
+                r#"{"op": "update", "key": "foo", "value": "baz"}"#,
+            )
+            .await;
+            let got_updated =
+                handle_request(&store, &mut session, r#"{"op": "get", "key": "foo"}"#).await;
+            let keys = handle_request(&store, &mut session, r#"{"op": "keys", "id": 2}"#).await;
+            let deleted =
+                handle_request(&store, &mut session, r#"{"op": "delete", "key": "foo"}"#).await;
+            let got_deleted = handle_request(
+                &store,
+                &mut session,
+                r#"{"op": "get", "id": 3, "key": "foo"}"#,
+            )
+            .await;
+
+            assert_eq!(Some(json!(1)), added.id);
+            assert_eq!(None, added.error.map(|x| x.code));
+            assert_eq!(Some(json!("a")), got.id);
+            assert_eq!(Some(String::from("bar")), got.value);
+            assert!(updated.error.is_none());
+            assert_eq!(Some(String::from("baz")), got_updated.value);
+            assert_eq!(Some(vec![String::from("foo")]), keys.keys);
+            assert!(deleted.error.is_none());
+            assert_eq!(Some(json!(3)), got_deleted.id);
+            assert_eq!(Some("KEY_NOT_FOUND"), error_code(&got_deleted));
+            assert_eq!(None, got_deleted.value);
+        });
+    }
+
+    #[test]
+    fn expiry_operations_passed() {
+        block_on(async {
+            let store = InMemoryKVStore::new();
+            let (mut session, _changes) = KVWatchSession::new(store.watch_hub());
+            handle_request(
+                &store,
+                &mut session,
+                r#"{"op": "add", "key": "foo", "value": "bar", "ttl": 60}"#,
+            )
+            .await;
+
+            let ttl = handle_request(&store, &mut session, r#"{"op": "ttl", "key": "foo"}"#).await;
+            let persisted =
+                handle_request(&store, &mut session, r#"{"op": "persist", "key": "foo"}"#).await;
+            let ttl_persisted =
+                handle_request(&store, &mut session, r#"{"op": "ttl", "key": "foo"}"#).await;
+            let expire_missing =
+                handle_request(&store, &mut session, r#"{"op": "expire", "key": "foo"}"#).await;
+            let expired = handle_request(
+                &store,
+                &mut session,
+                r#"{"op": "expire", "key": "foo", "expire_at": "2000-01-01T00:00:00Z"}"#,
+            )
+            .await;
+            let got = handle_request(&store, &mut session, r#"{"op": "get", "key": "foo"}"#).await;
+
+            assert_eq!(Some(60), ttl.expiry.unwrap().ttl);
+            assert!(persisted.error.is_none());
+            assert_eq!(None, ttl_persisted.expiry.unwrap().ttl);
+            assert_eq!(Some("INVALID_EXPIRY"), error_code(&expire_missing));
+            assert!(expired.error.is_none());
+            assert_eq!(Some("KEY_NOT_FOUND"), error_code(&got));
+        });
+    }
+
+    #[test]
+    fn malformed_request_failed() {
+        block_on(async {
+            let store = InMemoryKVStore::new();
+            let (mut session, _changes) = KVWatchSession::new(store.watch_hub());
+
+            let not_json = handle_request(&store, &mut session, "add foo").await;
+            let unknown_op = handle_request(
+                &store,
+                &mut session,
+                r#"{"op": "drop", "id": 7, "key": "foo"}"#,
+            )
+            .await;
+            let missing_key =
+                handle_request(&store, &mut session, r#"{"op": "get", "id": 8}"#).await;
+
+            assert_eq!(None, not_json.id);
+            assert_eq!(Some("INVALID_BODY"), error_code(&not_json));
+            assert_eq!(Some(json!(7)), unknown_op.id);
+            assert_eq!(Some("INVALID_BODY"), error_code(&unknown_op));
+            assert_eq!(Some(json!(8)), missing_key.id);
+            assert_eq!(Some("INVALID_BODY"), error_code(&missing_key));
+        });
+    }
+
+    #[test]
+    fn responce_omits_unset_fields_passed() {
+        block_on(async {
+            let store = InMemoryKVStore::new();
+            let (mut session, _changes) = KVWatchSession::new(store.watch_hub());
+            handle_request(
+                &store,
+                &mut session,
+                r#"{"op": "add", "key": "foo", "value": ""}"#,
+            )
+            .await;
+
+            let got = handle_request(
+                &store,
+                &mut session,
+                r#"{"op": "get", "id": 1, "key": "foo"}"#,
+            )
+            .await;
+
+            assert_eq!(
+                json!({"id": 1, "value": ""}),
+                serde_json::to_value(&got).unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn watch_operations_passed() {
+        block_on(async {
+            let store = InMemoryKVStore::new();
+            let (mut session, mut changes) = KVWatchSession::new(store.watch_hub());
+            handle_request(
+                &store,
+                &mut session,
+                r#"{"op": "add", "key": "a", "value": "1"}"#,
+            )
+            .await;
+
+            let watched = handle_request(
+                &store,
+                &mut session,
+                r#"{"op": "watch", "id": 1, "prefix": "user:"}"#,
+            )
+            .await;
+            let subscription = watched.subscription.unwrap();
+            handle_request(
+                &store,
+                &mut session,
+                r#"{"op": "add", "key": "user:1", "value": "Ann"}"#,
+            )
+            .await;
+            let unwatched = handle_request(
+                &store,
+                &mut session,
+                &format!(r#"{{"op": "unwatch", "subscription": {}}}"#, subscription),
+            )
+            .await;
+            let unwatched_again = handle_request(
+                &store,
+                &mut session,
+                &format!(r#"{{"op": "unwatch", "subscription": {}}}"#, subscription),
+            )
+            .await;
+            let ambiguous = handle_request(
+                &store,
+                &mut session,
+                r#"{"op": "watch", "key": "a", "glob": "a*"}"#,
+            )
+            .await;
+            handle_request(&store, &mut session, r#"{"op": "delete", "key": "user:1"}"#).await;
+
+            assert_eq!(Some(json!(1)), watched.id);
+            assert_eq!(Some(1), watched.revision);
+            let change = changes.try_recv().unwrap();
+            assert_eq!(
+                json!({
+                    "subscription": subscription,
+                    "revision": 2,
+                    "event": "added",
+                    "key": "user:1",
+                    "old_value": null,
+                    "new_value": "Ann"
+                }),
+                serde_json::to_value(&change).unwrap()
+            );
+            assert!(unwatched.error.is_none());
+            assert_eq!(Some("SUBSCRIPTION_NOT_FOUND"), error_code(&unwatched_again));
+            assert_eq!(Some("INVALID_PATTERN"), error_code(&ambiguous));
+            assert!(changes.try_recv().is_err());
+        });
+    }
+}
@@ -1,15 +1,46 @@
 mod api;
+mod api_error;
+mod api_error_tests;
+mod api_tests;
 mod core_model;
 mod core_model_tests;
+mod graph_algorithms;
+mod graph_algorithms_tests;
+mod graph_export;
+mod graph_export_tests;
+mod graph_feed;
+mod graph_feed_tests;
+mod graph_import;
+mod graph_import_tests;
+mod graph_paths;
+mod graph_paths_tests;
+mod graph_storage;
+mod graph_storage_tests;
+mod graph_transaction;
+mod graph_transaction_tests;
 mod kv_api;
 mod kv_model;
+mod kv_model_tests;
+mod kv_watch;
+mod kv_watch_tests;
 mod kv_ws;
+mod kv_ws_tests;
+mod property_index;
+mod query_engine;
+mod query_engine_tests;
+mod query_parser;
+mod query_parser_tests;
+mod query_planner;
+mod query_planner_tests;
 mod sharded_kv_graph;
+mod sharded_kv_graph_tests;
 mod substrate_kv_api;
 
 use actix_web::{get, web, App, HttpResponse, HttpServer, Responder};
+use std::env;
+use std::io;
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::sync::RwLock;
 
 // use sp_core::crypto::Pair;
 // use sp_keyring::AccountKeyring;
@@ -25,25 +56,133 @@ async fn main() -> std::io::Result<()> {
     let url = "0.0.0.0:18085";
     print_console_avtan(&url);
 
+    // READ STARTUP CONFIGURATION FROM ENVIRONMENT
+    let config =
+        AppConfig::from_env().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
     // CREATE GLOBAL STATE INITIALIZING GRAPH COLLECTION AND KV COLLECTION
-    let app_state = web::Data::new(AppState::new());
+    let app_state = web::Data::new(AppState::new(&config)?);
+
+    // EVICT EXPIRED KV KEYS IN BACKGROUND, READS SKIP THEM EVEN BEFORE EVICTION
+    let sweeper_state = app_state.clone();
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(kv_model::KV_SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = sweeper_state.kv_collection.evict_expired().await {
+                eprintln!("kv expiry sweep failed: {}", e);
+            }
+        }
+    });
 
     // START HTTP SERVER WITH GLOBAL STATE
     HttpServer::new(move || {
         App::new()
             .app_data(app_state.clone())
             .app_data(actix_send_websocket::WsConfig::new().disable_heartbeat())
+            // BAD BODY, QUERY OR PATH ANSWER WITH THE SAME JSON ERROR BODY AS HANDLERS
+            .app_data(web::JsonConfig::default().error_handler(api_error::json_error_handler))
+            .app_data(web::QueryConfig::default().error_handler(api_error::query_error_handler))
+            .app_data(web::PathConfig::default().error_handler(api_error::path_error_handler))
             // TEST ENDPOINTS
             .route("/get_test_val", web::get().to(api::get_test_val_by_key))
             .route("/get_graph", web::post().to(api::create_graph))
+            // GRAPH:
+            .route("/graphs", web::post().to(api::add_graph))
+            .route("/graphs", web::get().to(api::get_graphs))
+            .route("/graphs/{name}", web::get().to(api::get_graph_by_name))
+            .route("/graphs/{name}", web::put().to(api::rename_graph))
+            .route("/graphs/{name}", web::delete().to(api::delete_graph))
+            .route("/graphs/{name}/nodes", web::post().to(api::add_node))
+            .route("/graphs/{name}/nodes", web::get().to(api::get_nodes))
+            .route("/graphs/{name}/nodes/{id}", web::get().to(api::get_node))
+            .route(
+                "/graphs/{name}/nodes/{id}",
+                web::delete().to(api::delete_node),
+            )
+            .route(
+                "/graphs/{name}/nodes/{id}/properties",
+                web::put().to(api::update_node_properties),
+            )
+            .route(
+                "/graphs/{name}/nodes/{id}/neighbours",
+                web::get().to(api::get_neighbours),
+            )
+            .route("/graphs/{name}/bonds", web::post().to(api::add_bond))
+            .route("/graphs/{name}/bonds/{id}", web::get().to(api::get_bond))
+            .route(
+                "/graphs/{name}/bonds/{id}",
+                web::delete().to(api::delete_bond),
+            )
+            .route(
+                "/graphs/{name}/indexes",
+                web::get().to(api::get_property_indexes),
+            )
+            .route(
+                "/graphs/{name}/indexes",
+                web::post().to(api::create_property_index),
+            )
+            .route(
+                "/graphs/{name}/indexes/{label}/{property}",
+                web::delete().to(api::drop_property_index),
+            )
+            .route("/graphs/{name}/paths", web::get().to(api::get_paths))
+            .route(
+                "/graphs/{name}/algorithms",
+                web::post().to(api::run_algorithm),
+            )
+            .route("/graphs/{name}/export", web::get().to(api::export_graph))
+            .route("/graphs/{name}/query", web::post().to(api::run_query))
+            .route("/graphs/{name}/batch", web::post().to(api::run_batch))
+            .route(
+                "/graphs/{name}/snapshot",
+                web::post().to(api::snapshot_graph),
+            )
+            // IMPORT FILES ARE FAR LARGER THAN DEFAULT JSON LIMIT
+            .service(
+                web::resource("/graphs/{name}/import")
+                    .app_data(
+                        web::JsonConfig::default()
+                            .limit(graph_import::IMPORT_BODY_LIMIT)
+                            .error_handler(api_error::json_error_handler),
+                    )
+                    .route(web::post().to(api::import_graph)),
+            )
+            .route(
+                "/graphs/{name}/transactions",
+                web::post().to(api::begin_transaction),
+            )
+            .route(
+                "/graphs/{name}/transactions/{id}",
+                web::delete().to(api::rollback_transaction),
+            )
+            .route(
+                "/graphs/{name}/transactions/{id}/operations",
+                web::post().to(api::apply_transaction_operations),
+            )
+            .route(
+                "/graphs/{name}/transactions/{id}/query",
+                web::post().to(api::run_transaction_query),
+            )
+            .route(
+                "/graphs/{name}/transactions/{id}/commit",
+                web::post().to(api::commit_transaction),
+            )
             // KV - STORE:
             .route("/kv/value/{key}", web::post().to(kv_api::create_value))
             .route("/kv/value/{key}", web::get().to(kv_api::get_value))
             .route("/kv/value/{key}", web::put().to(kv_api::update_value))
             .route("/kv/value/{key}", web::delete().to(kv_api::delete_value))
             .route("/kv/get_all_keys", web::get().to(kv_api::get_all_keys))
-            .route("/ws/add_kv/", web::get().to(kv_ws::add_kv_ws))
-            .route("/ws/add_kv/", web::get().to(kv_ws::get_kv_ws))
+            .route("/kv/compact", web::post().to(kv_api::compact))
+            .route("/kv/ttl/{key}", web::put().to(kv_api::expire_value))
+            .route("/kv/ttl/{key}", web::get().to(kv_api::get_ttl))
+            .route("/kv/ttl/{key}", web::delete().to(kv_api::persist_value))
+            .route("/ws/kv/", web::get().to(kv_ws::kv_ws))
+            .route(
+                "/ws/graphs/{name}/changes",
+                web::get().to(graph_feed::graph_feed_ws),
+            )
             .service(hi)
     })
     .bind(url)?
@@ -51,30 +190,90 @@ async fn main() -> std::io::Result<()> {
     .await
 }
 
+/// Env variable selecting KV backend: "in_memory" (default) or "durable"
+const KV_TYPE_ENV: &str = "AVTAN_KV_TYPE";
+/// Env variable with number of in_memory KV shards, 1 (default) keeps a single map
+const KV_SHARDS_ENV: &str = "AVTAN_KV_SHARDS";
+/// Env variable with directory for all persisted data
+const DATA_DIR_ENV: &str = "AVTAN_DATA_DIR";
+const DEFAULT_DATA_DIR: &str = "./avtan_data";
+
+/// Startup configuration
+pub struct AppConfig {
+    kv_type: kv_model::KVType,
+    kv_shards: usize,
+    data_dir: PathBuf,
+}
+
+impl AppConfig {
+    /// Reads configuration from environment, falling back to defaults
+    fn from_env() -> Result<AppConfig, String> {
+        let kv_type = match env::var(KV_TYPE_ENV) {
+            Ok(v) => v.parse::<kv_model::KVType>()?,
+            Err(_) => kv_model::KVType::INMemory,
+        };
+        let kv_shards = match env::var(KV_SHARDS_ENV) {
+            Ok(v) => match v.trim().parse::<usize>() {
+                Ok(n) if n > 0 => n,
+                _ => return Err(format!("{} must be a positive number", KV_SHARDS_ENV)),
+            },
+            Err(_) => 1,
+        };
+        if kv_shards > 1 && kv_type != kv_model::KVType::INMemory {
+            return Err(format!("{} applies to in_memory kv only", KV_SHARDS_ENV));
+        }
+        let data_dir = env::var(DATA_DIR_ENV).unwrap_or_else(|_| DEFAULT_DATA_DIR.to_string());
+
+        Ok(AppConfig {
+            kv_type,
+            kv_shards,
+            data_dir: PathBuf::from(data_dir),
+        })
+    }
+}
+
 // WRAPPER STRUCT TO PROVIDE GLOBAL STATE
 pub struct AppState {
     graph_collection: core_model::GraphCollectionFacade,
-    kv_collection: kv_model::InMemoryKVStore,
+    graph_transactions: graph_transaction::TransactionRegistry,
+    kv_collection: Box<dyn kv_model::KVStore + Send + Sync>,
 }
 
 impl AppState {
-    fn new() -> AppState {
-        AppState {
-            graph_collection: AppState::initialize_graph_collection(),
-            kv_collection: AppState::initialize_kv_store(),
-        }
+    fn new(config: &AppConfig) -> io::Result<AppState> {
+        Ok(AppState {
+            graph_collection: AppState::initialize_graph_collection(config)?,
+            graph_transactions: graph_transaction::TransactionRegistry::default(),
+            kv_collection: AppState::initialize_kv_store(config)?,
+        })
     }
 
-    /// initialize common graph collection for all programm lifetime
-    fn initialize_graph_collection() -> core_model::GraphCollectionFacade {
-        core_model::GraphCollectionFacade {
-            in_memory_graph_collection: Arc::new(RwLock::new(Vec::new())),
-        }
+    /// initialize common graph collection for all programm lifetime,
+    /// restoring graphs persisted by previous runs
+    fn initialize_graph_collection(
+        config: &AppConfig,
+    ) -> io::Result<core_model::GraphCollectionFacade> {
+        let graph_storage = graph_storage::GraphStorage::new(config.data_dir.join("graphs"))?;
+        let graphs = graph_storage.recover()?;
+        Ok(core_model::GraphCollectionFacade::new(
+            graphs,
+            Some(Arc::new(graph_storage)),
+        ))
     }
 
-    // initialize kv store for all programm lifetime
-    fn initialize_kv_store() -> kv_model::InMemoryKVStore {
-        kv_model::InMemoryKVStore::new()
+    // initialize kv store of configured type for all programm lifetime
+    fn initialize_kv_store(
+        config: &AppConfig,
+    ) -> io::Result<Box<dyn kv_model::KVStore + Send + Sync>> {
+        match config.kv_type {
+            kv_model::KVType::INMemory if config.kv_shards > 1 => Ok(Box::new(
+                sharded_kv_graph::ShardedKVStore::new(config.kv_shards),
+            )),
+            kv_model::KVType::INMemory => Ok(Box::new(kv_model::InMemoryKVStore::new())),
+            kv_model::KVType::Durable => Ok(Box::new(kv_model::DurableKVStore::new(
+                config.data_dir.join("kv"),
+            )?)),
+        }
     }
 }
 
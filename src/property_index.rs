@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+use uuid::Uuid;
+
+/// Scalar property value as it is ordered inside an index.
+/// Values of different types never compare equal: Bool < Number < String.
+#[derive(Debug, Clone)]
+pub enum IndexValue {
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl IndexValue {
+    /// Only scalar JSON values are indexable, null, arrays and objects are not
+    pub fn from_json(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(b) => Some(IndexValue::Bool(*b)),
+            Value::Number(n) => n.as_f64().map(IndexValue::Number),
+            Value::String(s) => Some(IndexValue::String(s.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn is_same_type(&self, other: &IndexValue) -> bool {
+        self.type_rank() == other.type_rank()
+    }
+
+    fn type_rank(&self) -> u8 {
+        match self {
+            IndexValue::Bool(_) => 0,
+            IndexValue::Number(_) => 1,
+            IndexValue::String(_) => 2,
+        }
+    }
+}
+
+impl Ord for IndexValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (IndexValue::Bool(a), IndexValue::Bool(b)) => a.cmp(b),
+            (IndexValue::Number(a), IndexValue::Number(b)) => a.total_cmp(b),
+            (IndexValue::String(a), IndexValue::String(b)) => a.cmp(b),
+            _ => self.type_rank().cmp(&other.type_rank()),
+        }
+    }
+}
+
+impl PartialOrd for IndexValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for IndexValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for IndexValue {}
+
+/// User declared index over nodes having `label` and scalar `property`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PropertyIndexDefinition {
+    pub label: String,
+    pub property: String,
+}
+
+/// Property index: value -> ids of nodes holding it.
+/// Only definition is persisted, entries are rebuilt from nodes on load.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyIndex {
+    pub definition: PropertyIndexDefinition,
+    #[serde(skip)]
+    entries: BTreeMap<IndexValue, BTreeSet<Uuid>>,
+}
+
+impl PropertyIndex {
+    /// ctor of empty index
+    pub fn new(definition: PropertyIndexDefinition) -> Self {
+        PropertyIndex {
+            definition,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Key under which node is indexed, None if node is not covered by index
+    pub fn index_key(
+        &self,
+        labels: &[String],
+        properties: &serde_json::Map<String, Value>,
+    ) -> Option<IndexValue> {
+        if !labels.contains(&self.definition.label) {
+            return None;
+        }
+        properties
+            .get(&self.definition.property)
+            .and_then(IndexValue::from_json)
+    }
+
+    pub fn insert(&mut self, key: IndexValue, node_id: Uuid) {
+        self.entries.entry(key).or_default().insert(node_id);
+    }
+
+    pub fn remove(&mut self, key: &IndexValue, node_id: &Uuid) {
+        let is_empty = match self.entries.get_mut(key) {
+            None => return,
+            Some(ids) => {
+                ids.remove(node_id);
+                ids.is_empty()
+            }
+        };
+        if is_empty {
+            self.entries.remove(key);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Ids of nodes whose value is equal to key
+    pub fn get_equal(&self, key: &IndexValue) -> Vec<Uuid> {
+        match self.entries.get(key) {
+            None => Vec::new(),
+            Some(ids) => ids.iter().copied().collect(),
+        }
+    }
+
+    /// Ids of nodes whose value is inside range, ordered by value
+    pub fn get_range(&self, from: Bound<IndexValue>, to: Bound<IndexValue>) -> Vec<Uuid> {
+        // BTreeMap::range panics on inverted or empty-excluded ranges
+        let is_empty_range = match (&from, &to) {
+            (Bound::Included(a), Bound::Included(b)) => a > b,
+            (Bound::Included(a), Bound::Excluded(b))
+            | (Bound::Excluded(a), Bound::Included(b))
+            | (Bound::Excluded(a), Bound::Excluded(b)) => a >= b,
+            _ => false,
+        };
+        if is_empty_range {
+            return Vec::new();
+        }
+
+        // Open ends must not leak into values of other types
+        let type_rank = match (&from, &to) {
+            (Bound::Included(v), _) | (Bound::Excluded(v), _) => Some(v.type_rank()),
+            (_, Bound::Included(v)) | (_, Bound::Excluded(v)) => Some(v.type_rank()),
+            _ => None,
+        };
+
+        self.entries
+            .range((from, to))
+            .filter(|(k, _)| type_rank.is_none_or(|rank| k.type_rank() == rank))
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect()
+    }
+}
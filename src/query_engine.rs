@@ -0,0 +1,499 @@
+use crate::core_model::{
+    properties_match, Bond, BondDirection, GraphError, InMemoryGraph, Node, Properties,
+    ReturnBondDTO, ReturnNodeDTO,
+};
+use crate::query_parser::{parse_query, CompareOp, Expr, QueryMode, RelPattern};
+use crate::query_planner::{plan_query, PlanDescription, PlanStep, QueryPlan};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::ops::Bound;
+use uuid::Uuid;
+
+/// Most rows query may return without LIMIT, keeps cross products from exhausting memory
+pub const MAX_RESULT_ROWS: usize = 100_000;
+
+/// Body of POST /graphs/{name}/query
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryDTO {
+    pub query: String,
+}
+
+/// Result set of query, one value per column in every row
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    /// Plan tree, set by EXPLAIN and PROFILE
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<PlanDescription>,
+}
+
+/// Value bound to variable, positions point into graph collections
+#[derive(Debug, Clone)]
+enum Binding {
+    Node(usize),
+    Bond(usize),
+    Bonds(Vec<usize>),
+}
+
+/// RETURN rows collected so far
+#[derive(Debug, Default)]
+struct Projection {
+    rows: Vec<Vec<Value>>,
+    /// Rows already returned, set by DISTINCT
+    seen: HashSet<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Row {
+    slots: Vec<Option<Binding>>,
+    /// Bonds matched so far, a bond is matched at most once per row
+    used_bonds: Vec<usize>,
+}
+
+impl InMemoryGraph {
+    /// Parses, plans and runs query text; EXPLAIN only plans it
+    pub fn execute_query(&self, text: &str) -> Result<QueryResult, GraphError> {
+        let query = parse_query(text)?;
+        let mode = query.mode;
+        let plan = plan_query(query, self)?;
+        Ok(match mode {
+            QueryMode::Run => self.execute_plan(&plan)?,
+            QueryMode::Explain => QueryResult {
+                columns: plan.columns(),
+                rows: Vec::new(),
+                plan: Some(plan.describe(None)),
+            },
+            QueryMode::Profile => {
+                let (mut result, rows) = self.profile_plan(&plan)?;
+                result.plan = Some(plan.describe(Some(&rows)));
+                result
+            }
+        })
+    }
+
+    /// Runs already validated plan
+    pub fn execute_plan(&self, plan: &QueryPlan) -> Result<QueryResult, GraphError> {
+        Ok(self.profile_plan(plan)?.0)
+    }
+
+    /// Runs plan, also returning number of rows every operator produced
+    pub fn profile_plan(&self, plan: &QueryPlan) -> Result<(QueryResult, Vec<usize>), GraphError> {
+        let mut counts = Vec::with_capacity(plan.operators_len());
+        counts.resize(plan.steps.len(), 0);
+        let mut projection = Projection::default();
+        let row = Row {
+            slots: vec![None; plan.variables.len()],
+            used_bonds: Vec::new(),
+        };
+        self.stream_rows(plan, 0, &row, &mut counts, &mut projection)?;
+
+        if plan.distinct {
+            counts.push(projection.seen.len());
+        }
+        if plan.limit.is_some() {
+            counts.push(projection.rows.len());
+        }
+        counts.push(projection.rows.len());
+        let result = QueryResult {
+            columns: plan.columns(),
+            rows: projection.rows,
+            plan: None,
+        };
+        Ok((result, counts))
+    }
+
+    /// Passes row through steps from step on, depth first so that LIMIT stops
+    /// the work early; false once no more rows are needed
+    fn stream_rows(
+        &self,
+        plan: &QueryPlan,
+        step: usize,
+        row: &Row,
+        counts: &mut [usize],
+        projection: &mut Projection,
+    ) -> Result<bool, GraphError> {
+        let plan_step = match plan.steps.get(step) {
+            None => return self.project(plan, row, projection),
+            Some(s) => s,
+        };
+        for new_row in self.execute_step(plan, plan_step, row) {
+            counts[step] += 1;
+            if !self.stream_rows(plan, step + 1, &new_row, counts, projection)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn execute_step(&self, plan: &QueryPlan, step: &PlanStep, row: &Row) -> Vec<Row> {
+        match step {
+            PlanStep::NodeScan {
+                slot,
+                labels,
+                properties,
+            } => (0..self.nodes_collection.len())
+                .filter(|x| node_matches(&self.nodes_collection[*x], labels, properties))
+                .map(|x| bind(row, *slot, Binding::Node(x)))
+                .collect(),
+            PlanStep::LabelScan {
+                slot,
+                label,
+                labels,
+                properties,
+            } => match self.labels_index.get(label) {
+                None => Vec::new(),
+                Some(positions) => positions
+                    .iter()
+                    .filter(|x| node_matches(&self.nodes_collection[**x], labels, properties))
+                    .map(|x| bind(row, *slot, Binding::Node(*x)))
+                    .collect(),
+            },
+            PlanStep::PropertyIndexSeek {
+                slot,
+                label,
+                labels,
+                properties,
+            } => match self.property_index_candidates(label, properties) {
+                None => Vec::new(),
+                Some(positions) => positions
+                    .into_iter()
+                    .filter(|x| node_matches(&self.nodes_collection[*x], labels, properties))
+                    .map(|x| bind(row, *slot, Binding::Node(x)))
+                    .collect(),
+            },
+            PlanStep::PropertyIndexRangeSeek {
+                slot,
+                label,
+                property,
+                from,
+                to,
+                labels,
+                properties,
+            } => {
+                // planner only seeks scalar bounds, these never fail
+                let nodes = match (from, to) {
+                    (Bound::Included(a), Bound::Included(b)) if a == b => {
+                        self.get_nodes_by_property_value(label, property, a.clone())
+                    }
+                    _ => {
+                        self.get_nodes_by_property_range(label, property, from.clone(), to.clone())
+                    }
+                };
+                nodes
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|x| node_matches(x, labels, properties))
+                    .map(|x| bind(row, *slot, Binding::Node(self.nodes_id_index[&x.id])))
+                    .collect()
+            }
+            PlanStep::NodeByIdSeek {
+                slot,
+                id,
+                labels,
+                properties,
+            } => match self.nodes_id_index.get(id) {
+                Some(x) if node_matches(&self.nodes_collection[*x], labels, properties) => {
+                    vec![bind(row, *slot, Binding::Node(*x))]
+                }
+                _ => Vec::new(),
+            },
+            PlanStep::NodeFilter {
+                slot,
+                labels,
+                properties,
+            } => match &row.slots[*slot] {
+                Some(Binding::Node(x))
+                    if node_matches(&self.nodes_collection[*x], labels, properties) =>
+                {
+                    vec![row.clone()]
+                }
+                _ => Vec::new(),
+            },
+            PlanStep::Expand {
+                from,
+                rel_slot,
+                to,
+                rel,
+                to_labels,
+                to_properties,
+                to_bound,
+                reversed,
+            } => {
+                let from_position = match &row.slots[*from] {
+                    Some(Binding::Node(x)) => *x,
+                    _ => return Vec::new(),
+                };
+                let target = match (&row.slots[*to], to_bound) {
+                    (Some(Binding::Node(x)), true) => Some(*x),
+                    _ => None,
+                };
+
+                let mut rows = Vec::new();
+                for (bonds, node_position) in self.expand(from_position, rel, &row.used_bonds) {
+                    if target.is_some_and(|x| x != node_position) {
+                        continue;
+                    }
+                    let node = &self.nodes_collection[node_position];
+                    if !node_matches(node, to_labels, to_properties) {
+                        continue;
+                    }
+                    let rel_binding = if rel.is_variable_length() {
+                        // bonds are listed in pattern order
+                        let mut pattern_bonds = bonds.clone();
+                        if *reversed {
+                            pattern_bonds.reverse();
+                        }
+                        Binding::Bonds(pattern_bonds)
+                    } else {
+                        Binding::Bond(bonds[0])
+                    };
+                    let mut new_row = bind(row, *rel_slot, rel_binding);
+                    new_row.slots[*to] = Some(Binding::Node(node_position));
+                    new_row.used_bonds.extend(bonds);
+                    rows.push(new_row);
+                }
+                rows
+            }
+            PlanStep::Filter(expr) => {
+                if self.eval(plan, expr, row) == Value::Bool(true) {
+                    vec![row.clone()]
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+    }
+
+    /// Every (bonds walked, reached node) within hops range of relationship pattern,
+    /// no bond is walked twice
+    fn expand(
+        &self,
+        from_position: usize,
+        rel: &RelPattern,
+        used_bonds: &[usize],
+    ) -> Vec<(Vec<usize>, usize)> {
+        let mut found = Vec::new();
+        let mut path = Vec::new();
+        self.expand_from(from_position, rel, used_bonds, &mut path, &mut found);
+        found
+    }
+
+    fn expand_from(
+        &self,
+        node_position: usize,
+        rel: &RelPattern,
+        used_bonds: &[usize],
+        path: &mut Vec<usize>,
+        found: &mut Vec<(Vec<usize>, usize)>,
+    ) {
+        let hops = path.len() as u32;
+        if hops >= rel.min_hops {
+            found.push((path.clone(), node_position));
+        }
+        if hops >= rel.max_hops {
+            return;
+        }
+
+        let node_id = self.nodes_collection[node_position].id;
+        for (bond_position, other_id) in self.adjacent_bond_positions(node_id, rel.direction) {
+            if path.contains(&bond_position) || used_bonds.contains(&bond_position) {
+                continue;
+            }
+            let bond = &self.bonds_collection[bond_position];
+            if !rel.types.is_empty() && !rel.types.contains(&bond.label) {
+                continue;
+            }
+            if !properties_match(&bond.properties, &rel.properties) {
+                continue;
+            }
+            let other_position = match self.nodes_id_index.get(&other_id) {
+                None => continue,
+                Some(x) => *x,
+            };
+            path.push(bond_position);
+            self.expand_from(other_position, rel, used_bonds, path, found);
+            path.pop();
+        }
+    }
+
+    /// Like get_adjacent_bonds, but undirected walk sees self loop once
+    fn adjacent_bond_positions(
+        &self,
+        node_id: Uuid,
+        direction: BondDirection,
+    ) -> Vec<(usize, Uuid)> {
+        let mut adjacent = Vec::new();
+        if direction != BondDirection::Ingoing {
+            for bond in self.get_outgoing_bonds(node_id) {
+                adjacent.push((self.bonds_id_index[&bond.id], bond.dst));
+            }
+        }
+        if direction != BondDirection::Outgoing {
+            for bond in self.get_incoming_bonds(node_id) {
+                if direction == BondDirection::Both && bond.src == bond.dst {
+                    continue;
+                }
+                adjacent.push((self.bonds_id_index[&bond.id], bond.src));
+            }
+        }
+        adjacent
+    }
+
+    /// Evaluates RETURN items of row; false once LIMIT is reached
+    fn project(
+        &self,
+        plan: &QueryPlan,
+        row: &Row,
+        projection: &mut Projection,
+    ) -> Result<bool, GraphError> {
+        if plan.limit.is_some_and(|x| projection.rows.len() >= x) {
+            return Ok(false);
+        }
+        let values: Vec<Value> = plan
+            .returns
+            .iter()
+            .map(|x| self.eval(plan, &x.expr, row))
+            .collect();
+        if plan.distinct
+            && !projection
+                .seen
+                .insert(Value::Array(values.clone()).to_string())
+        {
+            return Ok(true);
+        }
+        if projection.rows.len() >= MAX_RESULT_ROWS {
+            return Err(GraphError::InvalidQuery(format!(
+                "query returns more than {} rows, narrow it down or add LIMIT",
+                MAX_RESULT_ROWS
+            )));
+        }
+        projection.rows.push(values);
+        Ok(plan.limit.is_none_or(|x| projection.rows.len() < x))
+    }
+
+    fn eval(&self, plan: &QueryPlan, expr: &Expr, row: &Row) -> Value {
+        match expr {
+            Expr::Literal(v) => v.clone(),
+            Expr::Variable(_) | Expr::Property(_, _) | Expr::Id(_) => {
+                self.eval_binding(plan, expr, row)
+            }
+            Expr::Not(e) => match as_bool(&self.eval(plan, e, row)) {
+                None => Value::Null,
+                Some(b) => Value::Bool(!b),
+            },
+            Expr::And(l, r) => {
+                match (
+                    as_bool(&self.eval(plan, l, row)),
+                    as_bool(&self.eval(plan, r, row)),
+                ) {
+                    (Some(false), _) | (_, Some(false)) => Value::Bool(false),
+                    (Some(true), Some(true)) => Value::Bool(true),
+                    _ => Value::Null,
+                }
+            }
+            Expr::Or(l, r) => match (
+                as_bool(&self.eval(plan, l, row)),
+                as_bool(&self.eval(plan, r, row)),
+            ) {
+                (Some(true), _) | (_, Some(true)) => Value::Bool(true),
+                (Some(false), Some(false)) => Value::Bool(false),
+                _ => Value::Null,
+            },
+            Expr::Compare(op, l, r) => {
+                compare(*op, &self.eval(plan, l, row), &self.eval(plan, r, row))
+            }
+        }
+    }
+
+    /// Bound variable as JSON document, or its property (null if missing)
+    fn eval_binding(&self, plan: &QueryPlan, expr: &Expr, row: &Row) -> Value {
+        let (variable, key) = match expr {
+            Expr::Variable(v) | Expr::Id(v) => (v, None),
+            Expr::Property(v, key) => (v, Some(key)),
+            _ => return Value::Null,
+        };
+        let binding = match plan.slot_of(variable).and_then(|x| row.slots[x].as_ref()) {
+            None => return Value::Null,
+            Some(b) => b,
+        };
+        if let Expr::Id(_) = expr {
+            return match binding {
+                Binding::Node(x) => Value::String(self.nodes_collection[*x].id.to_string()),
+                Binding::Bond(x) => Value::String(self.bonds_collection[*x].id.to_string()),
+                Binding::Bonds(_) => Value::Null,
+            };
+        }
+        match (binding, key) {
+            (Binding::Node(x), None) => node_to_json(&self.nodes_collection[*x]),
+            (Binding::Bond(x), None) => bond_to_json(&self.bonds_collection[*x]),
+            (Binding::Bonds(xs), None) => Value::Array(
+                xs.iter()
+                    .map(|x| bond_to_json(&self.bonds_collection[*x]))
+                    .collect(),
+            ),
+            (Binding::Node(x), Some(key)) => {
+                property_value(&self.nodes_collection[*x].properties, key)
+            }
+            (Binding::Bond(x), Some(key)) => {
+                property_value(&self.bonds_collection[*x].properties, key)
+            }
+            (Binding::Bonds(_), Some(_)) => Value::Null,
+        }
+    }
+}
+
+fn bind(row: &Row, slot: usize, binding: Binding) -> Row {
+    let mut new_row = row.clone();
+    new_row.slots[slot] = Some(binding);
+    new_row
+}
+
+fn node_matches(node: &Node, labels: &[String], properties: &Properties) -> bool {
+    labels.iter().all(|x| node.labels.contains(x)) && properties_match(&node.properties, properties)
+}
+
+fn node_to_json(node: &Node) -> Value {
+    serde_json::to_value(ReturnNodeDTO::from(node)).unwrap_or(Value::Null)
+}
+
+fn bond_to_json(bond: &Bond) -> Value {
+    serde_json::to_value(ReturnBondDTO::from(bond)).unwrap_or(Value::Null)
+}
+
+fn property_value(properties: &Properties, key: &str) -> Value {
+    properties.get(key).cloned().unwrap_or(Value::Null)
+}
+
+fn as_bool(value: &Value) -> Option<bool> {
+    value.as_bool()
+}
+
+/// Comparison with null or values of different types is null
+fn compare(op: CompareOp, left: &Value, right: &Value) -> Value {
+    let ordering = match (left, right) {
+        (Value::Null, _) | (_, Value::Null) => return Value::Null,
+        (Value::Number(a), Value::Number(b)) => a.as_f64().partial_cmp(&b.as_f64()),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        (a, b) => match op {
+            CompareOp::Eq => return Value::Bool(a == b),
+            CompareOp::Neq => return Value::Bool(a != b),
+            _ => None,
+        },
+    };
+    let ordering = match ordering {
+        None => return Value::Null,
+        Some(o) => o,
+    };
+    Value::Bool(match op {
+        CompareOp::Eq => ordering == Ordering::Equal,
+        CompareOp::Neq => ordering != Ordering::Equal,
+        CompareOp::Lt => ordering == Ordering::Less,
+        CompareOp::Le => ordering != Ordering::Greater,
+        CompareOp::Gt => ordering == Ordering::Greater,
+        CompareOp::Ge => ordering != Ordering::Less,
+    })
+}
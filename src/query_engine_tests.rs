@@ -0,0 +1,183 @@
+#[cfg(test)]
+mod query_execution_tests {
+    use crate::core_model::{Bond, GraphError, InMemoryGraph, Node};
+    use serde_json::{json, Map, Value};
+    use uuid::Uuid;
+
+    fn person(graph: &mut InMemoryGraph, name: &str, age: i64) -> Uuid {
+        let id = Uuid::new_v4();
+        graph
+            .add_node(Node {
+                id,
+                labels: vec![String::from("Person")],
+                properties: json!({"name": name, "age": age})
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+            })
+            .unwrap();
+        id
+    }
+
+    fn bond(graph: &mut InMemoryGraph, label: &str, src: Uuid, dst: Uuid) {
+        graph
+            .add_bond(Bond {
+                id: Uuid::new_v4(),
+                label: String::from(label),
+                src,
+                dst,
+                properties: Map::new(),
+            })
+            .unwrap();
+    }
+
+    /// ann(42) -KNOWS-> bob(25) -KNOWS-> cid(35) -KNOWS-> dan(19), ann -LIKES-> cid
+    fn people_graph() -> InMemoryGraph {
+        let mut graph = InMemoryGraph::new_graph(String::from("people"));
+        let ann = person(&mut graph, "ann", 42);
+        let bob = person(&mut graph, "bob", 25);
+        let cid = person(&mut graph, "cid", 35);
+        let dan = person(&mut graph, "dan", 19);
+        bond(&mut graph, "KNOWS", ann, bob);
+        bond(&mut graph, "KNOWS", bob, cid);
+        bond(&mut graph, "KNOWS", cid, dan);
+        bond(&mut graph, "LIKES", ann, cid);
+        graph
+    }
+
+    fn names(rows: &[Vec<Value>]) -> Vec<String> {
+        let mut names: Vec<String> = rows
+            .iter()
+            .map(|x| x[0].as_str().unwrap().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn variable_length_match_passed() {
+        let graph = people_graph();
+
+        let result = graph
+            .execute_query(
+                "MATCH (a:Person)-[:KNOWS*1..3]->(b) WHERE a.age > 30 RETURN b.name AS name",
+            )
+            .unwrap();
+
+        assert_eq!(vec!["name"], result.columns);
+        // ann reaches bob, cid, dan; cid reaches dan
+        assert_eq!(vec!["bob", "cid", "dan", "dan"], names(&result.rows));
+    }
+
+    #[test]
+    fn match_returns_documents_passed() {
+        let graph = people_graph();
+
+        let result = graph
+            .execute_query("MATCH (a {name: 'ann'})-[r:LIKES]->(b) RETURN a, r, b.age")
+            .unwrap();
+
+        assert_eq!(vec!["a", "r", "b.age"], result.columns);
+        assert_eq!(1, result.rows.len());
+        assert_eq!(json!("ann"), result.rows[0][0]["properties"]["name"]);
+        assert_eq!(json!("LIKES"), result.rows[0][1]["label"]);
+        assert_eq!(json!(35), result.rows[0][2]);
+    }
+
+    #[test]
+    fn undirected_and_incoming_match_passed() {
+        let graph = people_graph();
+
+        let incoming = graph
+            .execute_query("MATCH (a {name: 'cid'})<--(b) RETURN b.name")
+            .unwrap();
+        let undirected = graph
+            .execute_query("MATCH (a {name: 'cid'})-[:KNOWS]-(b) RETURN b.name")
+            .unwrap();
+
+        assert_eq!(vec!["ann", "bob"], names(&incoming.rows));
+        assert_eq!(vec!["bob", "dan"], names(&undirected.rows));
+    }
+
+    #[test]
+    fn multiple_patterns_join_on_shared_variable_passed() {
+        let graph = people_graph();
+
+        // triangle ann -> bob -> cid <- ann
+        let result = graph
+            .execute_query("MATCH (a)-[:KNOWS]->(b)-[:KNOWS]->(c), (a)-[:LIKES]->(c) RETURN a.name")
+            .unwrap();
+
+        assert_eq!(vec!["ann"], names(&result.rows));
+    }
+
+    #[test]
+    fn distinct_and_limit_passed() {
+        let graph = people_graph();
+
+        let all = graph
+            .execute_query("MATCH (a)-->(b) RETURN a.name")
+            .unwrap();
+        let distinct = graph
+            .execute_query("MATCH (a)-->(b) RETURN DISTINCT a.name")
+            .unwrap();
+        let limited = graph
+            .execute_query("MATCH (a)-->(b) RETURN DISTINCT a.name LIMIT 2")
+            .unwrap();
+
+        assert_eq!(vec!["ann", "ann", "bob", "cid"], names(&all.rows));
+        assert_eq!(vec!["ann", "bob", "cid"], names(&distinct.rows));
+        assert_eq!(2, limited.rows.len());
+    }
+
+    #[test]
+    fn cross_product_limit_passed() {
+        let mut graph = InMemoryGraph::new_graph(String::from("crowd"));
+        for x in 0..400 {
+            person(&mut graph, &format!("p{}", x), x);
+        }
+
+        let limited = graph
+            .execute_query("PROFILE MATCH (a), (b) RETURN a LIMIT 1")
+            .unwrap();
+        let unlimited = graph.execute_query("MATCH (a), (b) RETURN a");
+
+        assert_eq!(1, limited.rows.len());
+        let scan = &limited.plan.unwrap().children[0].children[0];
+        assert_eq!(Some(1), scan.rows);
+        assert!(matches!(unlimited, Err(GraphError::InvalidQuery(_))));
+    }
+
+    #[test]
+    fn where_null_handling_passed() {
+        let graph = people_graph();
+
+        let missing = graph
+            .execute_query("MATCH (a) WHERE a.missing = 1 OR NOT a.missing = 1 RETURN a")
+            .unwrap();
+        let string_compare = graph
+            .execute_query("MATCH (a) WHERE a.name >= 'c' AND a.age <> 19 RETURN a.name")
+            .unwrap();
+
+        assert_eq!(0, missing.rows.len());
+        assert_eq!(vec!["cid"], names(&string_compare.rows));
+    }
+
+    #[test]
+    fn invalid_variables_failed() {
+        let graph = people_graph();
+
+        for text in [
+            "MATCH (a) RETURN b",
+            "MATCH (a) WHERE b.x = 1 RETURN a",
+            "MATCH (a)-[r]->(b), (r) RETURN a",
+            "MATCH (a)-[r]->(b)-[r]->(c) RETURN a",
+        ] {
+            assert!(
+                matches!(graph.execute_query(text), Err(GraphError::InvalidQuery(_))),
+                "{}",
+                text
+            );
+        }
+    }
+}
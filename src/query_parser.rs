@@ -0,0 +1,689 @@
+use crate::core_model::{BondDirection, GraphError, Properties};
+use serde_json::{Number, Value};
+use std::fmt;
+
+/// Upper bound of `*` and `*n..` relationship patterns, keeps expansion finite
+pub const MAX_VARIABLE_LENGTH: u32 = 15;
+
+/// Deepest nesting of parentheses, NOT, AND and OR in WHERE, keeps parsing and
+/// evaluation within the stack
+pub const MAX_EXPRESSION_DEPTH: usize = 256;
+
+/// Parsed `[EXPLAIN | PROFILE] MATCH ... [WHERE ...] RETURN ... [LIMIT n]` query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub mode: QueryMode,
+    pub patterns: Vec<PathPattern>,
+    pub where_clause: Option<Expr>,
+    pub distinct: bool,
+    pub returns: Vec<ReturnItem>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QueryMode {
+    /// Runs query and returns its rows
+    Run,
+    /// Returns plan with estimated row counts, query is not run
+    Explain,
+    /// Runs query, returns its rows and plan with actual row counts
+    Profile,
+}
+
+/// `(a)-[r]->(b)<-[s]-(c)...`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathPattern {
+    pub start: NodePattern,
+    pub steps: Vec<(RelPattern, NodePattern)>,
+}
+
+/// `(variable:Label1:Label2 {key: literal})`, anonymous nodes get generated variable
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodePattern {
+    pub variable: String,
+    pub labels: Vec<String>,
+    pub properties: Properties,
+}
+
+/// `-[variable:TYPE1|TYPE2*min..max {key: literal}]->`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelPattern {
+    pub variable: String,
+    pub types: Vec<String>,
+    pub direction: BondDirection,
+    pub min_hops: u32,
+    pub max_hops: u32,
+    pub properties: Properties,
+}
+
+impl RelPattern {
+    pub fn is_variable_length(&self) -> bool {
+        self.min_hops != 1 || self.max_hops != 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    Variable(String),
+    Property(String, String),
+    /// `id(variable)`, id of bound node or bond as string
+    Id(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareOp {
+    Eq,
+    Neq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Literal(v) => write!(f, "{}", v),
+            Expr::Variable(v) => write!(f, "{}", v),
+            Expr::Property(v, key) => write!(f, "{}.{}", v, key),
+            Expr::Id(v) => write!(f, "id({})", v),
+            Expr::Not(e) => write!(f, "NOT {}", e),
+            Expr::And(l, r) => write!(f, "({} AND {})", l, r),
+            Expr::Or(l, r) => write!(f, "({} OR {})", l, r),
+            Expr::Compare(op, l, r) => write!(f, "{} {} {}", l, op, r),
+        }
+    }
+}
+
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            CompareOp::Eq => "=",
+            CompareOp::Neq => "<>",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// `expr [AS alias]`, alias defaults to expression text
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReturnItem {
+    pub expr: Expr,
+    pub alias: String,
+}
+
+/// True for variables generated for anonymous pattern elements
+pub fn is_anonymous(variable: &str) -> bool {
+    variable.starts_with(ANONYMOUS_PREFIX)
+}
+
+/// Space can not appear in identifiers, so generated names never clash with user ones
+const ANONYMOUS_PREFIX: &str = " anon";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    /// Backtick quoted identifier, never a keyword
+    QuotedIdent(String),
+    Str(String),
+    Num(Number),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    Colon,
+    Comma,
+    Dot,
+    DotDot,
+    Pipe,
+    Star,
+    Dash,
+    Arrow,
+    LArrow,
+    Eq,
+    Neq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    End,
+}
+
+/// Parses query text, errors carry position of offending token
+pub fn parse_query(text: &str) -> Result<Query, GraphError> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        anonymous_count: 0,
+        depth: 0,
+    };
+    parser.parse_query()
+}
+
+fn syntax_error(position: usize, message: &str) -> GraphError {
+    GraphError::InvalidQuery(format!("{} at {}", message, position))
+}
+
+/// Splits text into tokens paired with their byte offsets
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, GraphError> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (offset, c) = chars[i];
+        let next = chars.get(i + 1).map(|x| x.1);
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let (token, len) = match (c, next) {
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            ('[', _) => (Token::LBracket, 1),
+            (']', _) => (Token::RBracket, 1),
+            ('{', _) => (Token::LBrace, 1),
+            ('}', _) => (Token::RBrace, 1),
+            (':', _) => (Token::Colon, 1),
+            (',', _) => (Token::Comma, 1),
+            ('|', _) => (Token::Pipe, 1),
+            ('*', _) => (Token::Star, 1),
+            ('.', Some('.')) => (Token::DotDot, 2),
+            ('.', _) => (Token::Dot, 1),
+            ('-', Some('>')) => (Token::Arrow, 2),
+            ('-', _) => (Token::Dash, 1),
+            ('<', Some('-')) => (Token::LArrow, 2),
+            ('<', Some('>')) => (Token::Neq, 2),
+            ('<', Some('=')) => (Token::Le, 2),
+            ('<', _) => (Token::Lt, 1),
+            ('>', Some('=')) => (Token::Ge, 2),
+            ('>', _) => (Token::Gt, 1),
+            ('!', Some('=')) => (Token::Neq, 2),
+            ('=', _) => (Token::Eq, 1),
+            ('\'', _) | ('"', _) => {
+                let (value, len) = read_string(&chars[i..], c, offset)?;
+                (Token::Str(value), len)
+            }
+            ('`', _) => {
+                let (value, len) = read_string(&chars[i..], c, offset)?;
+                (Token::QuotedIdent(value), len)
+            }
+            (c, _) if c.is_ascii_digit() => read_number(&chars[i..], offset)?,
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|x| x.1.is_alphanumeric() || x.1 == '_')
+                    .count();
+                let ident: String = chars[i..i + len].iter().map(|x| x.1).collect();
+                (Token::Ident(ident), len)
+            }
+            _ => return Err(syntax_error(offset, &format!("unexpected '{}'", c))),
+        };
+        tokens.push((token, offset));
+        i += len;
+    }
+
+    tokens.push((Token::End, text.len()));
+    Ok(tokens)
+}
+
+/// Reads quoted text starting at opening quote, returns value and consumed chars
+fn read_string(
+    chars: &[(usize, char)],
+    quote: char,
+    offset: usize,
+) -> Result<(String, usize), GraphError> {
+    let mut value = String::new();
+    let mut i = 1;
+    while i < chars.len() {
+        match chars[i].1 {
+            '\\' if i + 1 < chars.len() => {
+                value.push(match chars[i + 1].1 {
+                    'n' => '\n',
+                    't' => '\t',
+                    other => other,
+                });
+                i += 2;
+            }
+            c if c == quote => return Ok((value, i + 1)),
+            c => {
+                value.push(c);
+                i += 1;
+            }
+        }
+    }
+    Err(syntax_error(offset, "unterminated string"))
+}
+
+/// Integer or decimal, `1..3` is read as 1 followed by `..`
+fn read_number(chars: &[(usize, char)], offset: usize) -> Result<(Token, usize), GraphError> {
+    let mut len = chars.iter().take_while(|x| x.1.is_ascii_digit()).count();
+    let is_decimal = chars.get(len).map(|x| x.1) == Some('.')
+        && chars.get(len + 1).is_some_and(|x| x.1.is_ascii_digit());
+    if is_decimal {
+        len += 1 + chars[len + 1..]
+            .iter()
+            .take_while(|x| x.1.is_ascii_digit())
+            .count();
+    }
+    let text: String = chars[..len].iter().map(|x| x.1).collect();
+
+    let number = if is_decimal {
+        text.parse::<f64>().ok().and_then(Number::from_f64)
+    } else {
+        text.parse::<u64>().ok().map(Number::from)
+    };
+    match number {
+        None => Err(syntax_error(offset, "invalid number")),
+        Some(n) => Ok((Token::Num(n), len)),
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    anonymous_count: usize,
+    /// Expression nesting at current position
+    depth: usize,
+}
+
+impl Parser {
+    fn parse_query(&mut self) -> Result<Query, GraphError> {
+        let mode = if self.eat_keyword("EXPLAIN") {
+            QueryMode::Explain
+        } else if self.eat_keyword("PROFILE") {
+            QueryMode::Profile
+        } else {
+            QueryMode::Run
+        };
+        self.expect_keyword("MATCH")?;
+        let mut patterns = vec![self.parse_path()?];
+        while self.eat(&Token::Comma) {
+            patterns.push(self.parse_path()?);
+        }
+
+        let where_clause = if self.eat_keyword("WHERE") {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+
+        self.expect_keyword("RETURN")?;
+        let distinct = self.eat_keyword("DISTINCT");
+        let mut returns = vec![self.parse_return_item()?];
+        while self.eat(&Token::Comma) {
+            returns.push(self.parse_return_item()?);
+        }
+
+        let limit = if self.eat_keyword("LIMIT") {
+            match self.next() {
+                Token::Num(n) if n.is_u64() => Some(n.as_u64().unwrap() as usize),
+                _ => return Err(self.error_at_previous("LIMIT expects non-negative integer")),
+            }
+        } else {
+            None
+        };
+
+        if self.peek() != &Token::End {
+            return Err(self.error("unexpected trailing input"));
+        }
+        Ok(Query {
+            mode,
+            patterns,
+            where_clause,
+            distinct,
+            returns,
+            limit,
+        })
+    }
+
+    fn parse_path(&mut self) -> Result<PathPattern, GraphError> {
+        let start = self.parse_node()?;
+        let mut steps = Vec::new();
+        while matches!(self.peek(), Token::Dash | Token::LArrow) {
+            let rel = self.parse_rel()?;
+            let node = self.parse_node()?;
+            steps.push((rel, node));
+        }
+        Ok(PathPattern { start, steps })
+    }
+
+    fn parse_node(&mut self) -> Result<NodePattern, GraphError> {
+        self.expect(&Token::LParen, "expected '('")?;
+        let variable = self.parse_optional_variable();
+        let mut labels = Vec::new();
+        while self.eat(&Token::Colon) {
+            labels.push(self.parse_name()?);
+        }
+        let properties = self.parse_optional_properties()?;
+        self.expect(&Token::RParen, "expected ')'")?;
+        Ok(NodePattern {
+            variable,
+            labels,
+            properties,
+        })
+    }
+
+    /// `-[...]->`, `<-[...]-`, `-[...]-` or short forms `-->`, `<--`, `--`
+    fn parse_rel(&mut self) -> Result<RelPattern, GraphError> {
+        let is_incoming = match self.next() {
+            Token::LArrow => true,
+            Token::Dash => false,
+            _ => return Err(self.error_at_previous("expected relationship")),
+        };
+
+        let mut rel = RelPattern {
+            variable: String::new(),
+            types: Vec::new(),
+            direction: BondDirection::Both,
+            min_hops: 1,
+            max_hops: 1,
+            properties: Properties::new(),
+        };
+        if self.eat(&Token::LBracket) {
+            rel.variable = self.parse_optional_variable();
+            if self.eat(&Token::Colon) {
+                rel.types.push(self.parse_name()?);
+                while self.eat(&Token::Pipe) {
+                    self.eat(&Token::Colon);
+                    rel.types.push(self.parse_name()?);
+                }
+            }
+            if self.eat(&Token::Star) {
+                let (min_hops, max_hops) = self.parse_hops()?;
+                rel.min_hops = min_hops;
+                rel.max_hops = max_hops;
+            }
+            rel.properties = self.parse_optional_properties()?;
+            self.expect(&Token::RBracket, "expected ']'")?;
+        } else {
+            rel.variable = self.anonymous_variable();
+        }
+
+        let is_outgoing = match self.next() {
+            Token::Arrow => true,
+            Token::Dash => false,
+            _ => return Err(self.error_at_previous("expected '-' or '->'")),
+        };
+        rel.direction = match (is_incoming, is_outgoing) {
+            (true, true) => return Err(self.error_at_previous("relationship has two directions")),
+            (true, false) => BondDirection::Ingoing,
+            (false, true) => BondDirection::Outgoing,
+            (false, false) => BondDirection::Both,
+        };
+        Ok(rel)
+    }
+
+    /// After `*`: nothing, `n`, `n..`, `..m` or `n..m`
+    fn parse_hops(&mut self) -> Result<(u32, u32), GraphError> {
+        let min_hops = self.parse_optional_hop_count()?;
+        if !self.eat(&Token::DotDot) {
+            return match min_hops {
+                None => Ok((1, MAX_VARIABLE_LENGTH)),
+                Some(n) => Ok((n, n)),
+            };
+        }
+        let max_hops = self.parse_optional_hop_count()?;
+        let min_hops = min_hops.unwrap_or(1);
+        let max_hops = max_hops.unwrap_or(MAX_VARIABLE_LENGTH);
+        if min_hops > max_hops || max_hops > MAX_VARIABLE_LENGTH {
+            return Err(self.error_at_previous(&format!(
+                "hops range must be within 0..{}",
+                MAX_VARIABLE_LENGTH
+            )));
+        }
+        Ok((min_hops, max_hops))
+    }
+
+    fn parse_optional_hop_count(&mut self) -> Result<Option<u32>, GraphError> {
+        if let Token::Num(n) = self.peek().clone() {
+            self.position += 1;
+            return match n.as_u64() {
+                Some(v) if v <= MAX_VARIABLE_LENGTH as u64 => Ok(Some(v as u32)),
+                _ => Err(self.error_at_previous("invalid hop count")),
+            };
+        }
+        Ok(None)
+    }
+
+    fn parse_optional_variable(&mut self) -> String {
+        match self.peek().clone() {
+            Token::Ident(name) | Token::QuotedIdent(name) => {
+                self.position += 1;
+                name
+            }
+            _ => self.anonymous_variable(),
+        }
+    }
+
+    fn anonymous_variable(&mut self) -> String {
+        self.anonymous_count += 1;
+        format!("{}{}", ANONYMOUS_PREFIX, self.anonymous_count)
+    }
+
+    /// `{key: literal, ...}` or nothing
+    fn parse_optional_properties(&mut self) -> Result<Properties, GraphError> {
+        let mut properties = Properties::new();
+        if !self.eat(&Token::LBrace) {
+            return Ok(properties);
+        }
+        if self.eat(&Token::RBrace) {
+            return Ok(properties);
+        }
+        loop {
+            let key = self.parse_name()?;
+            self.expect(&Token::Colon, "expected ':'")?;
+            let value = match self.parse_primary()? {
+                Expr::Literal(v) if !v.is_null() => v,
+                _ => return Err(self.error_at_previous("expected non-null literal")),
+            };
+            properties.insert(key, value);
+            if !self.eat(&Token::Comma) {
+                break;
+            }
+        }
+        self.expect(&Token::RBrace, "expected '}'")?;
+        Ok(properties)
+    }
+
+    fn parse_return_item(&mut self) -> Result<ReturnItem, GraphError> {
+        let expr = match self.parse_primary()? {
+            e @ Expr::Variable(_) | e @ Expr::Property(_, _) | e @ Expr::Id(_) => e,
+            _ => {
+                return Err(
+                    self.error_at_previous("RETURN expects variable, property or id(variable)")
+                )
+            }
+        };
+        let alias = if self.eat_keyword("AS") {
+            self.parse_name()?
+        } else {
+            expr.to_string()
+        };
+        Ok(ReturnItem { expr, alias })
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, GraphError> {
+        let depth = self.depth;
+        let mut left = self.parse_and()?;
+        while self.eat_keyword("OR") {
+            // every operator nests the terms before it one level deeper
+            self.nest()?;
+            let right = self.parse_and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, GraphError> {
+        let depth = self.depth;
+        let mut left = self.parse_not()?;
+        while self.eat_keyword("AND") {
+            self.nest()?;
+            let right = self.parse_not()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, GraphError> {
+        if self.eat_keyword("NOT") {
+            self.nest()?;
+            let expr = self.parse_not()?;
+            self.depth -= 1;
+            return Ok(Expr::Not(Box::new(expr)));
+        }
+        self.parse_comparison()
+    }
+
+    /// Goes one expression level deeper, failing past MAX_EXPRESSION_DEPTH
+    fn nest(&mut self) -> Result<(), GraphError> {
+        if self.depth >= MAX_EXPRESSION_DEPTH {
+            return Err(self.error_at_previous(&format!(
+                "expression nested deeper than {} levels",
+                MAX_EXPRESSION_DEPTH
+            )));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, GraphError> {
+        let left = self.parse_primary()?;
+        let op = match self.peek() {
+            Token::Eq => CompareOp::Eq,
+            Token::Neq => CompareOp::Neq,
+            Token::Lt => CompareOp::Lt,
+            Token::Le => CompareOp::Le,
+            Token::Gt => CompareOp::Gt,
+            Token::Ge => CompareOp::Ge,
+            _ => return Ok(left),
+        };
+        self.position += 1;
+        let right = self.parse_primary()?;
+        Ok(Expr::Compare(op, Box::new(left), Box::new(right)))
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, GraphError> {
+        match self.next() {
+            Token::LParen => {
+                self.nest()?;
+                let expr = self.parse_expr()?;
+                self.expect(&Token::RParen, "expected ')'")?;
+                self.depth -= 1;
+                Ok(expr)
+            }
+            Token::Str(s) => Ok(Expr::Literal(Value::String(s))),
+            Token::Num(n) => Ok(Expr::Literal(Value::Number(n))),
+            Token::Dash => match self.next() {
+                Token::Num(n) => {
+                    let negated = match n.as_u64() {
+                        Some(v) if v <= i64::MAX as u64 => Some(Number::from(-(v as i64))),
+                        _ => n.as_f64().and_then(|v| Number::from_f64(-v)),
+                    };
+                    negated
+                        .map(|x| Expr::Literal(Value::Number(x)))
+                        .ok_or_else(|| self.error_at_previous("invalid number"))
+                }
+                _ => Err(self.error_at_previous("expected number after '-'")),
+            },
+            Token::Ident(name) if name.eq_ignore_ascii_case("TRUE") => {
+                Ok(Expr::Literal(Value::Bool(true)))
+            }
+            Token::Ident(name) if name.eq_ignore_ascii_case("FALSE") => {
+                Ok(Expr::Literal(Value::Bool(false)))
+            }
+            Token::Ident(name) if name.eq_ignore_ascii_case("NULL") => {
+                Ok(Expr::Literal(Value::Null))
+            }
+            Token::Ident(name) if name.eq_ignore_ascii_case("ID") && self.eat(&Token::LParen) => {
+                let variable = self.parse_name()?;
+                self.expect(&Token::RParen, "expected ')'")?;
+                Ok(Expr::Id(variable))
+            }
+            Token::Ident(name) | Token::QuotedIdent(name) => {
+                if self.eat(&Token::Dot) {
+                    let key = self.parse_name()?;
+                    return Ok(Expr::Property(name, key));
+                }
+                Ok(Expr::Variable(name))
+            }
+            _ => Err(self.error_at_previous("expected expression")),
+        }
+    }
+
+    /// Label, type or property key: plain or backtick quoted identifier
+    fn parse_name(&mut self) -> Result<String, GraphError> {
+        match self.next() {
+            Token::Ident(name) | Token::QuotedIdent(name) => Ok(name),
+            _ => Err(self.error_at_previous("expected name")),
+        }
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position].0
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.position].0.clone();
+        if token != Token::End {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == token {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Token::Ident(name) if name.eq_ignore_ascii_case(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, token: &Token, message: &str) -> Result<(), GraphError> {
+        if self.eat(token) {
+            return Ok(());
+        }
+        Err(self.error(message))
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), GraphError> {
+        if self.eat_keyword(keyword) {
+            return Ok(());
+        }
+        Err(self.error(&format!("expected {}", keyword)))
+    }
+
+    fn error(&self, message: &str) -> GraphError {
+        syntax_error(self.tokens[self.position].1, message)
+    }
+
+    fn error_at_previous(&self, message: &str) -> GraphError {
+        syntax_error(self.tokens[self.position.saturating_sub(1)].1, message)
+    }
+}
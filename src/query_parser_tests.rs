@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod query_parsing_tests {
+    use crate::core_model::{BondDirection, GraphError};
+    use crate::query_parser::{
+        is_anonymous, parse_query, CompareOp, Expr, QueryMode, MAX_EXPRESSION_DEPTH,
+        MAX_VARIABLE_LENGTH,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn parse_path_pattern_passed() {
+        let query = parse_query(
+            "MATCH (a:Person:Admin {name: 'Ann'})-[r:KNOWS|:LIKES]->(b)<--(c) RETURN a",
+        )
+        .unwrap();
+
+        assert_eq!(1, query.patterns.len());
+        let pattern = &query.patterns[0];
+        assert_eq!("a", pattern.start.variable);
+        assert_eq!(vec!["Person", "Admin"], pattern.start.labels);
+        assert_eq!(json!({"name": "Ann"}), json!(pattern.start.properties));
+        assert_eq!(2, pattern.steps.len());
+
+        let (rel, node) = &pattern.steps[0];
+        assert_eq!("r", rel.variable);
+        assert_eq!(vec!["KNOWS", "LIKES"], rel.types);
+        assert_eq!(BondDirection::Outgoing, rel.direction);
+        assert!(!rel.is_variable_length());
+        assert_eq!("b", node.variable);
+
+        let (rel, node) = &pattern.steps[1];
+        assert!(is_anonymous(&rel.variable));
+        assert_eq!(BondDirection::Ingoing, rel.direction);
+        assert_eq!("c", node.variable);
+    }
+
+    #[test]
+    fn parse_variable_length_passed() {
+        let hops = |text: &str| {
+            let query = parse_query(text).unwrap();
+            let rel = &query.patterns[0].steps[0].0;
+            (rel.min_hops, rel.max_hops)
+        };
+
+        assert_eq!((1, 3), hops("MATCH (a)-[:KNOWS*1..3]-(b) RETURN b"));
+        assert_eq!((2, 2), hops("MATCH (a)-[*2]->(b) RETURN b"));
+        assert_eq!(
+            (1, MAX_VARIABLE_LENGTH),
+            hops("MATCH (a)-[*]->(b) RETURN b")
+        );
+        assert_eq!((0, 4), hops("MATCH (a)-[*0..4]->(b) RETURN b"));
+        assert_eq!((1, 5), hops("MATCH (a)-[*..5]->(b) RETURN b"));
+        assert!(parse_query("MATCH (a)-[*3..1]->(b) RETURN b").is_err());
+        assert!(parse_query("MATCH (a)-[*1..100]->(b) RETURN b").is_err());
+    }
+
+    #[test]
+    fn parse_where_precedence_passed() {
+        let query = parse_query(
+            "match (a) where not a.x = 1 and a.y < -2.5 or a.z return a.x as x limit 5",
+        )
+        .unwrap();
+
+        let expected = Expr::Or(
+            Box::new(Expr::And(
+                Box::new(Expr::Not(Box::new(Expr::Compare(
+                    CompareOp::Eq,
+                    Box::new(Expr::Property(String::from("a"), String::from("x"))),
+                    Box::new(Expr::Literal(json!(1))),
+                )))),
+                Box::new(Expr::Compare(
+                    CompareOp::Lt,
+                    Box::new(Expr::Property(String::from("a"), String::from("y"))),
+                    Box::new(Expr::Literal(json!(-2.5))),
+                )),
+            )),
+            Box::new(Expr::Property(String::from("a"), String::from("z"))),
+        );
+        assert_eq!(Some(expected), query.where_clause);
+        assert_eq!("x", query.returns[0].alias);
+        assert_eq!(Some(5), query.limit);
+    }
+
+    #[test]
+    fn parse_syntax_errors_failed() {
+        for text in [
+            "",
+            "RETURN a",
+            "MATCH (a RETURN a",
+            "MATCH (a)-[r]-(b)",
+            "MATCH (a)<-[r]->(b) RETURN a",
+            "MATCH (a) WHERE a.x = RETURN a",
+            "MATCH (a) RETURN a LIMIT -1",
+            "MATCH (a) RETURN a extra",
+            "MATCH (a {x: null}) RETURN a",
+            "MATCH (a) WHERE a.name = 'unterminated RETURN a",
+        ] {
+            match parse_query(text) {
+                Err(GraphError::InvalidQuery(message)) => {
+                    assert!(message.contains(" at "), "{}", message)
+                }
+                other => panic!("{:?} parsed as {:?}", text, other),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_mode_and_id_passed() {
+        let query = parse_query("PROFILE MATCH (a) WHERE id(a) = 'x' RETURN id(a)").unwrap();
+
+        assert_eq!(QueryMode::Profile, query.mode);
+        assert_eq!(
+            Some(Expr::Compare(
+                CompareOp::Eq,
+                Box::new(Expr::Id(String::from("a"))),
+                Box::new(Expr::Literal(json!("x"))),
+            )),
+            query.where_clause
+        );
+        assert_eq!("id(a)", query.returns[0].alias);
+        assert_eq!(
+            QueryMode::Explain,
+            parse_query("explain MATCH (a) RETURN a").unwrap().mode
+        );
+        assert_eq!(
+            QueryMode::Run,
+            parse_query("MATCH (a) RETURN a").unwrap().mode
+        );
+        assert!(parse_query("MATCH (a) RETURN id(a.x)").is_err());
+    }
+
+    #[test]
+    fn parse_nested_expression_passed() {
+        let nested = format!(
+            "MATCH (a) WHERE {}a.x = 1{} RETURN a",
+            "(".repeat(50),
+            ")".repeat(50)
+        );
+        let negated = format!("MATCH (a) WHERE {}a.x = 1 RETURN a", "NOT ".repeat(50));
+
+        assert!(parse_query(&nested).is_ok());
+        assert!(parse_query(&negated).is_ok());
+    }
+
+    #[test]
+    fn parse_too_deep_expression_failed() {
+        let depth = MAX_EXPRESSION_DEPTH * 100;
+        for text in [
+            format!(
+                "MATCH (a) WHERE {}a.x = 1{} RETURN a",
+                "(".repeat(depth),
+                ")".repeat(depth)
+            ),
+            format!("MATCH (a) WHERE {}a.x = 1 RETURN a", "NOT ".repeat(depth)),
+            format!(
+                "MATCH (a) WHERE a.x = 1{} RETURN a",
+                " OR a.x = 1".repeat(depth)
+            ),
+        ] {
+            assert!(
+                matches!(parse_query(&text), Err(GraphError::InvalidQuery(_))),
+                "{}",
+                &text[..40]
+            );
+        }
+    }
+}
@@ -0,0 +1,794 @@
+use crate::core_model::{BondDirection, GraphError, InMemoryGraph, Properties};
+use crate::query_parser::{
+    is_anonymous, CompareOp, Expr, NodePattern, PathPattern, Query, RelPattern, ReturnItem,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::ops::Bound;
+use uuid::Uuid;
+
+/// Share of rows assumed to pass a WHERE conjunct or a pattern property
+pub const FILTER_SELECTIVITY: f64 = 0.3;
+
+/// Single operator of query plan, executed over every row produced by previous one
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanStep {
+    /// Binds every node having all labels and properties
+    NodeScan {
+        slot: usize,
+        labels: Vec<String>,
+        properties: Properties,
+    },
+    /// Binds nodes from labels index entry of `label`, checking the rest
+    LabelScan {
+        slot: usize,
+        label: String,
+        labels: Vec<String>,
+        properties: Properties,
+    },
+    /// Binds nodes found by property index of `label` covering one of properties
+    PropertyIndexSeek {
+        slot: usize,
+        label: String,
+        labels: Vec<String>,
+        properties: Properties,
+    },
+    /// Binds nodes whose indexed property is inside range, from
+    /// `WHERE n.property <op> literal` conjuncts, that stay as filters
+    PropertyIndexRangeSeek {
+        slot: usize,
+        label: String,
+        property: String,
+        from: Bound<Value>,
+        to: Bound<Value>,
+        labels: Vec<String>,
+        properties: Properties,
+    },
+    /// Binds node found in nodes id index, from `WHERE id(n) = '...'`
+    NodeByIdSeek {
+        slot: usize,
+        id: Uuid,
+        labels: Vec<String>,
+        properties: Properties,
+    },
+    /// Checks node bound by earlier pattern against labels and properties
+    NodeFilter {
+        slot: usize,
+        labels: Vec<String>,
+        properties: Properties,
+    },
+    /// Follows bonds from bound node, binding bond(s) and node on the other end.
+    /// If other node is already bound it has to be the reached one.
+    /// Reversed expand walks pattern right to left, its direction is already flipped.
+    Expand {
+        from: usize,
+        rel_slot: usize,
+        to: usize,
+        rel: RelPattern,
+        to_labels: Vec<String>,
+        to_properties: Properties,
+        to_bound: bool,
+        reversed: bool,
+    },
+    /// Keeps rows where expression is true
+    Filter(Expr),
+}
+
+/// Planned query: operators plus what to return
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryPlan {
+    /// Variable name of every row slot
+    pub variables: Vec<String>,
+    pub steps: Vec<PlanStep>,
+    /// Estimated number of rows produced by every step
+    pub estimates: Vec<f64>,
+    pub distinct: bool,
+    pub returns: Vec<ReturnItem>,
+    pub limit: Option<usize>,
+}
+
+/// Operator of plan tree returned by EXPLAIN and PROFILE, its input is the only child
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlanDescription {
+    pub operator: String,
+    pub details: String,
+    pub estimated_rows: f64,
+    /// Actual number of rows, set only by PROFILE
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rows: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<PlanDescription>,
+}
+
+/// Collection sizes planner estimates come from
+#[derive(Debug, PartialEq)]
+pub struct GraphStatistics {
+    pub nodes: usize,
+    pub bonds: usize,
+    pub label_counts: HashMap<String, usize>,
+    pub bond_type_counts: HashMap<String, usize>,
+}
+
+impl GraphStatistics {
+    /// Label counts come from labels index, bond type counts are kept by graph itself
+    pub fn collect(graph: &InMemoryGraph) -> Self {
+        GraphStatistics {
+            nodes: graph.nodes_collection.len(),
+            bonds: graph.bonds_collection.len(),
+            label_counts: graph
+                .labels_index
+                .iter()
+                .map(|(label, positions)| (label.clone(), positions.len()))
+                .collect(),
+            bond_type_counts: graph
+                .bond_type_counts
+                .iter()
+                .map(|(label, count)| (label.clone(), *count))
+                .collect(),
+        }
+    }
+
+    fn label_count(&self, label: &str) -> f64 {
+        self.label_counts.get(label).copied().unwrap_or(0) as f64
+    }
+
+    /// Node count as divisor, never zero
+    fn node_divisor(&self) -> f64 {
+        self.nodes.max(1) as f64
+    }
+}
+
+impl QueryPlan {
+    /// Row slot of variable
+    pub fn slot_of(&self, variable: &str) -> Option<usize> {
+        self.variables.iter().position(|x| x == variable)
+    }
+
+    pub fn columns(&self) -> Vec<String> {
+        self.returns.iter().map(|x| x.alias.clone()).collect()
+    }
+
+    /// Number of operators in plan tree: steps, Distinct, Limit and ProduceResults
+    pub fn operators_len(&self) -> usize {
+        self.steps.len() + self.distinct as usize + self.limit.is_some() as usize + 1
+    }
+
+    /// Plan tree with ProduceResults on top and first step as leaf.
+    /// `rows` are actual counts of every operator in execution order, from profiling.
+    pub fn describe(&self, rows: Option<&[usize]>) -> PlanDescription {
+        let mut operators = Vec::new();
+        for (step, estimate) in self.steps.iter().zip(self.estimates.iter()) {
+            let (operator, details) = self.describe_step(step);
+            operators.push((operator, details, *estimate));
+        }
+        let mut estimate = self.estimates.last().copied().unwrap_or(1.0);
+        if self.distinct {
+            operators.push(("Distinct", self.columns().join(", "), estimate));
+        }
+        if let Some(limit) = self.limit {
+            estimate = estimate.min(limit as f64);
+            operators.push(("Limit", limit.to_string(), estimate));
+        }
+        operators.push(("ProduceResults", self.columns().join(", "), estimate));
+
+        let mut description: Option<PlanDescription> = None;
+        for (i, (operator, details, estimate)) in operators.into_iter().enumerate() {
+            description = Some(PlanDescription {
+                operator: operator.to_string(),
+                details,
+                estimated_rows: (estimate * 100.0).round() / 100.0,
+                rows: rows.and_then(|x| x.get(i).copied()),
+                children: description.into_iter().collect(),
+            });
+        }
+        description.unwrap()
+    }
+
+    fn describe_step(&self, step: &PlanStep) -> (&'static str, String) {
+        match step {
+            PlanStep::NodeScan {
+                slot,
+                labels,
+                properties,
+            } => ("NodeScan", self.node_text(*slot, labels, properties)),
+            PlanStep::LabelScan {
+                slot,
+                label,
+                labels,
+                properties,
+            } => (
+                "LabelScan",
+                format!(
+                    "{} by :{}",
+                    self.node_text(*slot, labels, properties),
+                    label
+                ),
+            ),
+            PlanStep::PropertyIndexSeek {
+                slot,
+                label,
+                labels,
+                properties,
+            } => (
+                "PropertyIndexSeek",
+                format!(
+                    "{} by index of :{}",
+                    self.node_text(*slot, labels, properties),
+                    label
+                ),
+            ),
+            PlanStep::PropertyIndexRangeSeek {
+                slot,
+                label,
+                property,
+                from,
+                to,
+                labels,
+                properties,
+            } => (
+                "PropertyIndexRangeSeek",
+                format!(
+                    "{} by index of :{} where {}",
+                    self.node_text(*slot, labels, properties),
+                    label,
+                    range_text(display_name(&self.variables[*slot]), property, from, to)
+                ),
+            ),
+            PlanStep::NodeByIdSeek {
+                slot,
+                id,
+                labels,
+                properties,
+            } => (
+                "NodeByIdSeek",
+                format!("{} by id {}", self.node_text(*slot, labels, properties), id),
+            ),
+            PlanStep::NodeFilter {
+                slot,
+                labels,
+                properties,
+            } => ("NodeFilter", self.node_text(*slot, labels, properties)),
+            PlanStep::Expand {
+                from,
+                rel_slot,
+                to,
+                rel,
+                to_labels,
+                to_properties,
+                ..
+            } => (
+                "Expand",
+                format!(
+                    "({}){}{}",
+                    display_name(&self.variables[*from]),
+                    rel_text(display_name(&self.variables[*rel_slot]), rel),
+                    self.node_text(*to, to_labels, to_properties)
+                ),
+            ),
+            PlanStep::Filter(expr) => ("Filter", expr.to_string()),
+        }
+    }
+
+    fn node_text(&self, slot: usize, labels: &[String], properties: &Properties) -> String {
+        let mut text = format!("({}", display_name(&self.variables[slot]));
+        for label in labels {
+            text.push(':');
+            text.push_str(label);
+        }
+        if !properties.is_empty() {
+            text.push(' ');
+            text.push_str(&Value::Object(properties.clone()).to_string());
+        }
+        text.push(')');
+        text
+    }
+}
+
+/// Generated names start with space, shown without it
+fn display_name(variable: &str) -> &str {
+    variable.trim_start()
+}
+
+fn rel_text(name: &str, rel: &RelPattern) -> String {
+    let mut inner = name.to_string();
+    if !rel.types.is_empty() {
+        inner.push(':');
+        inner.push_str(&rel.types.join("|"));
+    }
+    if rel.is_variable_length() {
+        inner.push_str(&format!("*{}..{}", rel.min_hops, rel.max_hops));
+    }
+    if !rel.properties.is_empty() {
+        inner.push(' ');
+        inner.push_str(&Value::Object(rel.properties.clone()).to_string());
+    }
+    match rel.direction {
+        BondDirection::Outgoing => format!("-[{}]->", inner),
+        BondDirection::Ingoing => format!("<-[{}]-", inner),
+        BondDirection::Both => format!("-[{}]-", inner),
+    }
+}
+
+/// Range as conjuncts it came from, `n.age >= 18 AND n.age < 65` or `n.age = 30`
+fn range_text(variable: &str, property: &str, from: &Bound<Value>, to: &Bound<Value>) -> String {
+    if let (Bound::Included(a), Bound::Included(b)) = (from, to) {
+        if a == b {
+            return format!("{}.{} = {}", variable, property, a);
+        }
+    }
+    let mut conditions = Vec::new();
+    match from {
+        Bound::Included(v) => conditions.push(format!("{}.{} >= {}", variable, property, v)),
+        Bound::Excluded(v) => conditions.push(format!("{}.{} > {}", variable, property, v)),
+        Bound::Unbounded => {}
+    }
+    match to {
+        Bound::Included(v) => conditions.push(format!("{}.{} <= {}", variable, property, v)),
+        Bound::Excluded(v) => conditions.push(format!("{}.{} < {}", variable, property, v)),
+        Bound::Unbounded => {}
+    }
+    conditions.join(" AND ")
+}
+
+/// Property range of node variable collected from WHERE conjuncts
+#[derive(Debug, Clone)]
+struct RangeSeek {
+    property: String,
+    from: Bound<Value>,
+    to: Bound<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VariableKind {
+    Node,
+    Rel,
+}
+
+/// Resolves variables to row slots and orders pattern matching into plan steps.
+/// Every path starts from its node with the cheapest access by graph statistics
+/// and is expanded from there in both directions; WHERE conjuncts are placed
+/// right after the step binding their last variable.
+pub fn plan_query(query: Query, graph: &InMemoryGraph) -> Result<QueryPlan, GraphError> {
+    let mut planner = Planner {
+        graph,
+        statistics: GraphStatistics::collect(graph),
+        slots: HashMap::new(),
+        variables: Vec::new(),
+        bound: HashSet::new(),
+        conjuncts: Vec::new(),
+        id_seeks: HashMap::new(),
+        range_seeks: HashMap::new(),
+        steps: Vec::new(),
+        estimates: Vec::new(),
+        rows: 1.0,
+    };
+
+    for pattern in query.patterns.iter() {
+        planner.declare_pattern(pattern)?;
+    }
+    if let Some(expr) = &query.where_clause {
+        planner.check_expr(expr)?;
+        let mut conjuncts = Vec::new();
+        split_conjuncts(expr, &mut conjuncts);
+        planner.conjuncts = conjuncts.into_iter().map(Some).collect();
+        planner.collect_id_seeks();
+        planner.collect_range_seeks();
+    }
+    for item in query.returns.iter() {
+        planner.check_expr(&item.expr)?;
+    }
+
+    for pattern in query.patterns.iter() {
+        planner.plan_pattern(pattern);
+    }
+
+    Ok(QueryPlan {
+        variables: planner.variables,
+        steps: planner.steps,
+        estimates: planner.estimates,
+        distinct: query.distinct,
+        returns: query.returns,
+        limit: query.limit,
+    })
+}
+
+struct Planner<'a> {
+    graph: &'a InMemoryGraph,
+    statistics: GraphStatistics,
+    slots: HashMap<String, (usize, VariableKind)>,
+    variables: Vec<String>,
+    /// Slots bound by steps planned so far
+    bound: HashSet<usize>,
+    /// WHERE conjuncts, taken once placed
+    conjuncts: Vec<Option<Expr>>,
+    /// Node variable -> (id, conjunct it came from)
+    id_seeks: HashMap<String, (Uuid, usize)>,
+    /// Node variable -> property ranges, in order of conjuncts
+    range_seeks: HashMap<String, Vec<RangeSeek>>,
+    steps: Vec<PlanStep>,
+    estimates: Vec<f64>,
+    /// Estimated rows produced by last step
+    rows: f64,
+}
+
+impl<'a> Planner<'a> {
+    /// Slot of variable, declaring it on first use
+    fn declare(&mut self, variable: &str, kind: VariableKind) -> Result<usize, GraphError> {
+        if let Some((slot, declared_kind)) = self.slots.get(variable) {
+            if *declared_kind != kind {
+                return Err(GraphError::InvalidQuery(format!(
+                    "variable {} is both node and relationship",
+                    variable
+                )));
+            }
+            return Ok(*slot);
+        }
+        let slot = self.variables.len();
+        self.variables.push(variable.to_string());
+        self.slots.insert(variable.to_string(), (slot, kind));
+        Ok(slot)
+    }
+
+    fn declare_pattern(&mut self, pattern: &PathPattern) -> Result<(), GraphError> {
+        self.declare(&pattern.start.variable, VariableKind::Node)?;
+        for (rel, node) in pattern.steps.iter() {
+            if !is_anonymous(&rel.variable) && self.slots.contains_key(&rel.variable) {
+                return Err(GraphError::InvalidQuery(format!(
+                    "relationship variable {} is bound twice",
+                    rel.variable
+                )));
+            }
+            self.declare(&rel.variable, VariableKind::Rel)?;
+            self.declare(&node.variable, VariableKind::Node)?;
+        }
+        Ok(())
+    }
+
+    /// Every variable used in WHERE or RETURN has to be bound by MATCH
+    fn check_expr(&self, expr: &Expr) -> Result<(), GraphError> {
+        let mut variables = Vec::new();
+        expr_variables(expr, &mut variables);
+        match variables.iter().find(|x| !self.slots.contains_key(**x)) {
+            None => Ok(()),
+            Some(v) => Err(GraphError::InvalidQuery(format!("unknown variable {}", v))),
+        }
+    }
+
+    /// Conjuncts `id(n) = '<uuid>'` over node variables
+    fn collect_id_seeks(&mut self) {
+        for (i, conjunct) in self.conjuncts.iter().enumerate() {
+            let (variable, literal) = match conjunct {
+                Some(Expr::Compare(CompareOp::Eq, l, r)) => match (l.as_ref(), r.as_ref()) {
+                    (Expr::Id(v), Expr::Literal(Value::String(s)))
+                    | (Expr::Literal(Value::String(s)), Expr::Id(v)) => (v, s),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            let id = match Uuid::parse_str(literal) {
+                Err(_) => continue,
+                Ok(id) => id,
+            };
+            if self.slots[variable].1 == VariableKind::Node {
+                self.id_seeks.entry(variable.clone()).or_insert((id, i));
+            }
+        }
+    }
+
+    /// Conjuncts comparing node property with scalar literal, first lower and
+    /// first upper bound of every property make its range
+    fn collect_range_seeks(&mut self) {
+        for conjunct in self.conjuncts.iter() {
+            let (op, variable, property, literal) = match conjunct {
+                Some(Expr::Compare(op, l, r)) => match (l.as_ref(), r.as_ref()) {
+                    (Expr::Property(v, p), Expr::Literal(x)) => (*op, v, p, x),
+                    (Expr::Literal(x), Expr::Property(v, p)) => (flip(*op), v, p, x),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            if !(literal.is_boolean() || literal.is_number() || literal.is_string()) {
+                continue;
+            }
+            if self.slots[variable].1 != VariableKind::Node {
+                continue;
+            }
+            let (from, to) = match op {
+                CompareOp::Eq => (
+                    Bound::Included(literal.clone()),
+                    Bound::Included(literal.clone()),
+                ),
+                CompareOp::Lt => (Bound::Unbounded, Bound::Excluded(literal.clone())),
+                CompareOp::Le => (Bound::Unbounded, Bound::Included(literal.clone())),
+                CompareOp::Gt => (Bound::Excluded(literal.clone()), Bound::Unbounded),
+                CompareOp::Ge => (Bound::Included(literal.clone()), Bound::Unbounded),
+                CompareOp::Neq => continue,
+            };
+
+            let ranges = self.range_seeks.entry(variable.clone()).or_default();
+            match ranges.iter_mut().find(|x| &x.property == property) {
+                None => ranges.push(RangeSeek {
+                    property: property.clone(),
+                    from,
+                    to,
+                }),
+                Some(range) => {
+                    if let Bound::Unbounded = range.from {
+                        range.from = from;
+                    }
+                    if let Bound::Unbounded = range.to {
+                        range.to = to;
+                    }
+                }
+            }
+        }
+    }
+
+    fn plan_pattern(&mut self, pattern: &PathPattern) {
+        let mut nodes = vec![&pattern.start];
+        nodes.extend(pattern.steps.iter().map(|x| &x.1));
+
+        let mut anchor = 0;
+        let mut anchor_access = self.plan_access(nodes[0]);
+        for (i, node) in nodes.iter().enumerate().skip(1) {
+            let access = self.plan_access(node);
+            if access.1 < anchor_access.1 {
+                anchor = i;
+                anchor_access = access;
+            }
+        }
+
+        let (step, factor) = anchor_access;
+        if let PlanStep::NodeByIdSeek { .. } = step {
+            let conjunct = self.id_seeks[&nodes[anchor].variable].1;
+            self.conjuncts[conjunct] = None;
+        }
+        let slot = self.slots[&nodes[anchor].variable].0;
+        self.bound.insert(slot);
+        self.push_step(step, self.rows * factor);
+
+        for i in anchor..pattern.steps.len() {
+            self.plan_expand(nodes[i], &pattern.steps[i].0, nodes[i + 1], false);
+        }
+        for i in (0..anchor).rev() {
+            self.plan_expand(nodes[i + 1], &pattern.steps[i].0, nodes[i], true);
+        }
+    }
+
+    /// Cheapest way to bind node alone and factor it multiplies row count by
+    fn plan_access(&self, node: &NodePattern) -> (PlanStep, f64) {
+        let slot = self.slots[&node.variable].0;
+        let labels = node.labels.clone();
+        let properties = node.properties.clone();
+        if self.bound.contains(&slot) {
+            return (
+                PlanStep::NodeFilter {
+                    slot,
+                    labels,
+                    properties,
+                },
+                self.node_selectivity(node),
+            );
+        }
+        if let Some((id, _)) = self.id_seeks.get(&node.variable) {
+            return (
+                PlanStep::NodeByIdSeek {
+                    slot,
+                    id: *id,
+                    labels,
+                    properties,
+                },
+                1.0,
+            );
+        }
+
+        let property_factor = FILTER_SELECTIVITY.powi(properties.len() as i32);
+        let mut best = (
+            PlanStep::NodeScan {
+                slot,
+                labels: labels.clone(),
+                properties: properties.clone(),
+            },
+            self.statistics.nodes as f64 * property_factor,
+        );
+        for label in node.labels.iter() {
+            // index covers one of properties, the rest is filtered
+            if let Some(positions) = self.graph.property_index_candidates(label, &properties) {
+                let estimate = positions.len() as f64 * property_factor / FILTER_SELECTIVITY;
+                if estimate < best.1 {
+                    best = (
+                        PlanStep::PropertyIndexSeek {
+                            slot,
+                            label: label.clone(),
+                            labels: labels.clone(),
+                            properties: properties.clone(),
+                        },
+                        estimate,
+                    );
+                }
+            }
+            // range conjuncts stay as filters, only pattern properties are counted here
+            for range in self.range_seeks.get(&node.variable).into_iter().flatten() {
+                if !self.graph.has_property_index(label, &range.property) {
+                    continue;
+                }
+                let matched = self
+                    .graph
+                    .get_nodes_by_property_range(
+                        label,
+                        &range.property,
+                        range.from.clone(),
+                        range.to.clone(),
+                    )
+                    .map(|x| x.len())
+                    .unwrap_or(0);
+                let estimate = matched as f64 * property_factor;
+                if estimate < best.1 {
+                    best = (
+                        PlanStep::PropertyIndexRangeSeek {
+                            slot,
+                            label: label.clone(),
+                            property: range.property.clone(),
+                            from: range.from.clone(),
+                            to: range.to.clone(),
+                            labels: labels.clone(),
+                            properties: properties.clone(),
+                        },
+                        estimate,
+                    );
+                }
+            }
+            let estimate = self.statistics.label_count(label) * property_factor;
+            if estimate < best.1 {
+                best = (
+                    PlanStep::LabelScan {
+                        slot,
+                        label: label.clone(),
+                        labels: labels.clone(),
+                        properties: properties.clone(),
+                    },
+                    estimate,
+                );
+            }
+        }
+        best
+    }
+
+    fn plan_expand(
+        &mut self,
+        from: &NodePattern,
+        rel: &RelPattern,
+        to: &NodePattern,
+        reversed: bool,
+    ) {
+        let from_slot = self.slots[&from.variable].0;
+        let rel_slot = self.slots[&rel.variable].0;
+        let to_slot = self.slots[&to.variable].0;
+        let to_bound = !self.bound.insert(to_slot);
+        self.bound.insert(rel_slot);
+
+        let mut rel = rel.clone();
+        if reversed {
+            rel.direction = match rel.direction {
+                BondDirection::Outgoing => BondDirection::Ingoing,
+                BondDirection::Ingoing => BondDirection::Outgoing,
+                BondDirection::Both => BondDirection::Both,
+            };
+        }
+        let mut factor = self.expand_factor(&rel) * self.node_selectivity(to);
+        if to_bound {
+            factor /= self.statistics.node_divisor();
+        }
+
+        self.push_step(
+            PlanStep::Expand {
+                from: from_slot,
+                rel_slot,
+                to: to_slot,
+                rel,
+                to_labels: to.labels.clone(),
+                to_properties: to.properties.clone(),
+                to_bound,
+                reversed,
+            },
+            self.rows * factor,
+        );
+    }
+
+    /// Expected number of walks per start node: average degree over bonds of
+    /// pattern types, summed over every allowed hop count
+    fn expand_factor(&self, rel: &RelPattern) -> f64 {
+        let bonds = if rel.types.is_empty() {
+            self.statistics.bonds
+        } else {
+            rel.types
+                .iter()
+                .map(|x| {
+                    self.statistics
+                        .bond_type_counts
+                        .get(x)
+                        .copied()
+                        .unwrap_or(0)
+                })
+                .sum()
+        };
+        let mut degree = bonds as f64 / self.statistics.node_divisor()
+            * FILTER_SELECTIVITY.powi(rel.properties.len() as i32);
+        if rel.direction == BondDirection::Both {
+            degree *= 2.0;
+        }
+        (rel.min_hops..=rel.max_hops)
+            .map(|x| degree.powi(x as i32))
+            .sum()
+    }
+
+    /// Share of nodes matching labels and properties of pattern
+    fn node_selectivity(&self, node: &NodePattern) -> f64 {
+        let label_factor = node
+            .labels
+            .iter()
+            .map(|x| self.statistics.label_count(x) / self.statistics.node_divisor())
+            .fold(1.0, f64::min);
+        label_factor * FILTER_SELECTIVITY.powi(node.properties.len() as i32)
+    }
+
+    /// Adds step, then every conjunct whose variables became bound
+    fn push_step(&mut self, step: PlanStep, estimate: f64) {
+        self.steps.push(step);
+        self.estimates.push(estimate);
+        self.rows = estimate;
+
+        for i in 0..self.conjuncts.len() {
+            let is_ready = match &self.conjuncts[i] {
+                None => false,
+                Some(expr) => {
+                    let mut variables = Vec::new();
+                    expr_variables(expr, &mut variables);
+                    variables
+                        .iter()
+                        .all(|x| self.bound.contains(&self.slots[*x].0))
+                }
+            };
+            if is_ready {
+                let expr = self.conjuncts[i].take().unwrap();
+                self.rows *= FILTER_SELECTIVITY;
+                self.steps.push(PlanStep::Filter(expr));
+                self.estimates.push(self.rows);
+            }
+        }
+    }
+}
+
+/// Operator with sides swapped, `5 < n.age` is `n.age > 5`
+fn flip(op: CompareOp) -> CompareOp {
+    match op {
+        CompareOp::Lt => CompareOp::Gt,
+        CompareOp::Le => CompareOp::Ge,
+        CompareOp::Gt => CompareOp::Lt,
+        CompareOp::Ge => CompareOp::Le,
+        op => op,
+    }
+}
+
+fn split_conjuncts(expr: &Expr, conjuncts: &mut Vec<Expr>) {
+    match expr {
+        Expr::And(l, r) => {
+            split_conjuncts(l, conjuncts);
+            split_conjuncts(r, conjuncts);
+        }
+        e => conjuncts.push(e.clone()),
+    }
+}
+
+fn expr_variables<'e>(expr: &'e Expr, variables: &mut Vec<&'e str>) {
+    match expr {
+        Expr::Literal(_) => {}
+        Expr::Variable(v) | Expr::Property(v, _) | Expr::Id(v) => variables.push(v),
+        Expr::Not(e) => expr_variables(e, variables),
+        Expr::And(l, r) | Expr::Or(l, r) | Expr::Compare(_, l, r) => {
+            expr_variables(l, variables);
+            expr_variables(r, variables);
+        }
+    }
+}
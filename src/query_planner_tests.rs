@@ -0,0 +1,245 @@
+#[cfg(test)]
+mod query_planning_tests {
+    use crate::core_model::{Bond, InMemoryGraph, Node};
+    use crate::query_parser::parse_query;
+    use crate::query_planner::{plan_query, GraphStatistics, PlanDescription, PlanStep};
+    use serde_json::{json, Map, Value};
+    use uuid::Uuid;
+
+    fn add_node(graph: &mut InMemoryGraph, label: &str, properties: Value) -> Uuid {
+        let id = Uuid::new_v4();
+        graph
+            .add_node(Node {
+                id,
+                labels: vec![String::from(label)],
+                properties: properties.as_object().unwrap().clone(),
+            })
+            .unwrap();
+        id
+    }
+
+    fn add_bond(graph: &mut InMemoryGraph, label: &str, src: Uuid, dst: Uuid) {
+        graph
+            .add_bond(Bond {
+                id: Uuid::new_v4(),
+                label: String::from(label),
+                src,
+                dst,
+                properties: Map::new(),
+            })
+            .unwrap();
+    }
+
+    /// 20 persons, each knowing the next one, person 0 manages the only admin
+    fn company_graph() -> (InMemoryGraph, Vec<Uuid>, Uuid) {
+        let mut graph = InMemoryGraph::new_graph(String::from("company"));
+        let persons: Vec<Uuid> = (0..20)
+            .map(|x| add_node(&mut graph, "Person", json!({ "num": x })))
+            .collect();
+        for pair in persons.windows(2) {
+            add_bond(&mut graph, "KNOWS", pair[0], pair[1]);
+        }
+        let admin = add_node(&mut graph, "Admin", json!({"name": "root"}));
+        add_bond(&mut graph, "MANAGES", persons[0], admin);
+        (graph, persons, admin)
+    }
+
+    fn operators(description: &PlanDescription) -> Vec<String> {
+        let mut operators = vec![description.operator.clone()];
+        let mut curr = description;
+        while let Some(child) = curr.children.first() {
+            operators.push(child.operator.clone());
+            curr = child;
+        }
+        operators.reverse();
+        operators
+    }
+
+    #[test]
+    fn collect_statistics_passed() {
+        let (graph, _, _) = company_graph();
+
+        let statistics = GraphStatistics::collect(&graph);
+
+        assert_eq!(21, statistics.nodes);
+        assert_eq!(20, statistics.bonds);
+        assert_eq!(Some(&20), statistics.label_counts.get("Person"));
+        assert_eq!(Some(&1), statistics.label_counts.get("Admin"));
+        assert_eq!(Some(&19), statistics.bond_type_counts.get("KNOWS"));
+    }
+
+    #[test]
+    fn bond_type_counts_follow_deletes_passed() {
+        let (mut graph, persons, admin) = company_graph();
+
+        graph.delete_node(admin, true).unwrap();
+        graph.delete_node(persons[5], true).unwrap();
+        let statistics = GraphStatistics::collect(&graph);
+        graph.rebuild_indexes();
+        let rebuilt = GraphStatistics::collect(&graph);
+
+        assert_eq!(17, statistics.bonds);
+        assert_eq!(Some(&17), statistics.bond_type_counts.get("KNOWS"));
+        assert_eq!(None, statistics.bond_type_counts.get("MANAGES"));
+        assert_eq!(statistics, rebuilt);
+    }
+
+    #[test]
+    fn plan_starts_from_smallest_label_passed() {
+        let (graph, _, _) = company_graph();
+        let query = parse_query("MATCH (p:Person)-[:MANAGES]->(a:Admin) RETURN p.num").unwrap();
+
+        let plan = plan_query(query, &graph).unwrap();
+
+        match &plan.steps[0] {
+            PlanStep::LabelScan { label, .. } => assert_eq!("Admin", label),
+            other => panic!("unexpected first step {:?}", other),
+        }
+        match &plan.steps[1] {
+            PlanStep::Expand { reversed, .. } => assert!(*reversed),
+            other => panic!("unexpected second step {:?}", other),
+        }
+        assert_eq!(
+            vec![vec![json!(0)]],
+            graph.execute_plan(&plan).unwrap().rows
+        );
+    }
+
+    #[test]
+    fn plan_seeks_node_by_id_passed() {
+        let (graph, persons, _) = company_graph();
+        let query = parse_query(&format!(
+            "MATCH (a:Person)-[:KNOWS]->(b) WHERE id(b) = '{}' AND a.num >= 0 RETURN a.num",
+            persons[5]
+        ))
+        .unwrap();
+
+        let plan = plan_query(query, &graph).unwrap();
+
+        assert!(matches!(plan.steps[0], PlanStep::NodeByIdSeek { .. }));
+        // id conjunct is answered by seek, the other one is filtered after expand
+        assert_eq!(3, plan.steps.len());
+        assert!(matches!(plan.steps[2], PlanStep::Filter(_)));
+        assert_eq!(
+            vec![vec![json!(4)]],
+            graph.execute_plan(&plan).unwrap().rows
+        );
+    }
+
+    #[test]
+    fn plan_uses_property_index_passed() {
+        let (mut graph, _, _) = company_graph();
+        graph
+            .create_property_index(String::from("Person"), String::from("num"))
+            .unwrap();
+        let query = parse_query("MATCH (p:Person {num: 7})-[:KNOWS]->(q) RETURN q.num").unwrap();
+
+        let plan = plan_query(query, &graph).unwrap();
+
+        assert!(matches!(plan.steps[0], PlanStep::PropertyIndexSeek { .. }));
+        assert_eq!(
+            vec![vec![json!(8)]],
+            graph.execute_plan(&plan).unwrap().rows
+        );
+    }
+
+    #[test]
+    fn plan_uses_property_index_range_passed() {
+        let (mut graph, _, _) = company_graph();
+        graph
+            .create_property_index(String::from("Person"), String::from("num"))
+            .unwrap();
+        let query = parse_query(
+            "MATCH (p:Person) WHERE p.num >= 5 AND 8 > p.num AND p.num <> 6 RETURN p.num",
+        )
+        .unwrap();
+
+        let plan = plan_query(query, &graph).unwrap();
+
+        match &plan.steps[0] {
+            PlanStep::PropertyIndexRangeSeek { property, .. } => assert_eq!("num", property),
+            step => panic!("unexpected step {:?}", step),
+        }
+        assert_eq!(
+            "(p:Person) by index of :Person where p.num >= 5 AND p.num < 8",
+            plan.describe(None).children[0].children[0].children[0].children[0].details
+        );
+        assert_eq!(
+            vec![vec![json!(5)], vec![json!(7)]],
+            graph.execute_plan(&plan).unwrap().rows
+        );
+    }
+
+    #[test]
+    fn plan_places_filters_early_passed() {
+        let (graph, _, _) = company_graph();
+        let query = parse_query(
+            "MATCH (a:Person)-[:KNOWS]->(b:Person) WHERE a.num < 3 AND b.num > a.num RETURN b.num",
+        )
+        .unwrap();
+
+        let plan = plan_query(query, &graph).unwrap();
+
+        assert!(matches!(plan.steps[1], PlanStep::Filter(_)));
+        assert!(matches!(plan.steps[3], PlanStep::Filter(_)));
+        assert!(plan.estimates[1] < plan.estimates[0]);
+    }
+
+    #[test]
+    fn reversed_variable_length_keeps_pattern_order_passed() {
+        let (graph, persons, _) = company_graph();
+
+        // anchored on b by id, so the path is expanded backwards
+        let result = graph
+            .execute_query(&format!(
+                "MATCH (a {{num: 2}})-[r:KNOWS*2]->(b) WHERE id(b) = '{}' RETURN r",
+                persons[4]
+            ))
+            .unwrap();
+
+        assert_eq!(1, result.rows.len());
+        let bonds = result.rows[0][0].as_array().unwrap();
+        assert_eq!(json!(persons[2]), bonds[0]["src"]);
+        assert_eq!(json!(persons[4]), bonds[1]["dst"]);
+    }
+
+    #[test]
+    fn explain_returns_plan_without_rows_passed() {
+        let (graph, _, _) = company_graph();
+
+        let result = graph
+            .execute_query("EXPLAIN MATCH (p:Person)-->(a:Admin) RETURN DISTINCT p LIMIT 5")
+            .unwrap();
+        let plan = result.plan.unwrap();
+
+        assert_eq!(vec!["p"], result.columns);
+        assert!(result.rows.is_empty());
+        assert_eq!(
+            vec!["LabelScan", "Expand", "Distinct", "Limit", "ProduceResults"],
+            operators(&plan)
+        );
+        assert_eq!(None, plan.rows);
+        assert!(plan.estimated_rows <= 5.0);
+    }
+
+    #[test]
+    fn profile_returns_actual_rows_passed() {
+        let (graph, _, _) = company_graph();
+
+        let result = graph
+            .execute_query("PROFILE MATCH (a:Person)-[:KNOWS]->(b) WHERE b.num > 10 RETURN b.num")
+            .unwrap();
+        let plan = result.plan.unwrap();
+
+        assert_eq!(9, result.rows.len());
+        assert_eq!(Some(9), plan.rows);
+        let filter = &plan.children[0];
+        let expand = &filter.children[0];
+        let scan = &expand.children[0];
+        assert_eq!("Filter", filter.operator);
+        assert_eq!(Some(19), expand.rows);
+        assert_eq!(Some(20), scan.rows);
+        assert_eq!("(a:Person) by :Person", scan.details);
+        assert_eq!("(a)-[anon1:KNOWS]->(b)", expand.details);
+    }
+}
@@ -1,4 +1,11 @@
 use crate::kv_model;
+use crate::kv_model::{KVError, KVFuture, KVStore};
+use crate::kv_watch::KVWatchHub;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+/// Points every shard owns on the hash ring, more points spread keys more evenly
+pub const SHARD_VIRTUAL_NODES: usize = 128;
 
 pub struct KvStoreShard {
     pub sharded_hasm_map: kv_model::InMemoryKVStore,
@@ -11,8 +18,149 @@ impl KvStoreShard {
         }
     }
 
+    /// Shard publishing its changes to hub shared with other shards
+    pub fn with_watch_hub(watch_hub: Arc<KVWatchHub>) -> Self {
+        KvStoreShard {
+            sharded_hasm_map: kv_model::InMemoryKVStore {
+                watch_hub,
+                ..kv_model::InMemoryKVStore::new()
+            },
+        }
+    }
+
     // Use it just after the shard manager had chosen this shard
-    pub async fn add_to_local_shard(&mut self, key: String, value: String) -> Result<(), ()> {
-        self.sharded_hasm_map.add_value(key, value).await
+    pub async fn add_to_local_shard(&mut self, key: String, value: String) -> Result<(), KVError> {
+        self.sharded_hasm_map.add_value(key, value, None).await
+    }
+}
+
+// Shard manager, spreads keys over in-memory shards by consistent hashing
+//
+// Every shard has its own map and lock, so writes to keys of different shards
+// don't wait for each other. Each shard owns SHARD_VIRTUAL_NODES points on a hash
+// ring and key goes to the shard owning the first point at or after key hash,
+// so changing shard count moves only keys of the ring arcs that changed hands.
+pub struct ShardedKVStore {
+    shards: Vec<KvStoreShard>,
+    /// (point, shard index) sorted by point
+    ring: Vec<(u64, usize)>,
+    /// Shared by all shards, so revisions are store wide
+    watch_hub: Arc<KVWatchHub>,
+}
+
+impl ShardedKVStore {
+    /// Store of `shard_count` empty shards, panics on zero
+    pub fn new(shard_count: usize) -> Self {
+        assert!(shard_count > 0, "sharded kv store needs at least one shard");
+        let watch_hub = Arc::new(KVWatchHub::new());
+        let shards = (0..shard_count)
+            .map(|_| KvStoreShard::with_watch_hub(watch_hub.clone()))
+            .collect();
+
+        let mut ring: Vec<(u64, usize)> = (0..shard_count)
+            .flat_map(|shard| {
+                (0..SHARD_VIRTUAL_NODES)
+                    .map(move |point| (hash_key(&format!("shard-{}-{}", shard, point)), shard))
+            })
+            .collect();
+        ring.sort_unstable();
+
+        ShardedKVStore {
+            shards,
+            ring,
+            watch_hub,
+        }
+    }
+
+    /// Index of shard owning key
+    pub fn shard_index(&self, key: &str) -> usize {
+        let hash = hash_key(key);
+        let position = self.ring.partition_point(|(point, _)| *point < hash);
+        // past the last point wraps around to the first one
+        self.ring[position % self.ring.len()].1
+    }
+
+    fn shard(&self, key: &str) -> &kv_model::InMemoryKVStore {
+        &self.shards[self.shard_index(key)].sharded_hasm_map
+    }
+}
+
+/// FNV-1a with a final mix, stable across runs and platforms unlike std hasher
+fn hash_key(key: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in key.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    // splitmix64 finalizer, FNV alone clusters similar keys
+    hash ^= hash >> 30;
+    hash = hash.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash ^= hash >> 27;
+    hash = hash.wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+impl KVStore for ShardedKVStore {
+    fn add_value(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> KVFuture<'_, ()> {
+        self.shard(&key).add_value(key, value, expires_at)
+    }
+
+    fn get_value(&self, key: String) -> KVFuture<'_, Arc<String>> {
+        self.shard(&key).get_value(key)
+    }
+
+    fn remove_value(&self, key: String) -> KVFuture<'_, ()> {
+        self.shard(&key).remove_value(key)
+    }
+
+    fn update_value(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> KVFuture<'_, ()> {
+        self.shard(&key).update_value(key, value, expires_at)
+    }
+
+    /// Keys of all shards, each shard is read on its own so the list is not a snapshot
+    fn get_all_keys(&self) -> KVFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            let mut keys = Vec::new();
+            for shard in self.shards.iter() {
+                keys.extend(shard.sharded_hasm_map.get_all_keys().await?);
+            }
+            Ok(keys)
+        })
+    }
+
+    fn set_expiry(&self, key: String, expires_at: DateTime<Utc>) -> KVFuture<'_, ()> {
+        self.shard(&key).set_expiry(key, expires_at)
+    }
+
+    fn get_expiry(&self, key: String) -> KVFuture<'_, Option<DateTime<Utc>>> {
+        self.shard(&key).get_expiry(key)
+    }
+
+    fn persist(&self, key: String) -> KVFuture<'_, ()> {
+        self.shard(&key).persist(key)
+    }
+
+    fn evict_expired(&self) -> KVFuture<'_, usize> {
+        Box::pin(async move {
+            let mut evicted = 0;
+            for shard in self.shards.iter() {
+                evicted += shard.sharded_hasm_map.evict_expired().await?;
+            }
+            Ok(evicted)
+        })
+    }
+
+    fn watch_hub(&self) -> &KVWatchHub {
+        &self.watch_hub
     }
 }
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod sharded_kv_store_tests {
+    use crate::kv_model::KVStore;
+    use crate::sharded_kv_graph::ShardedKVStore;
+    use std::future::Future;
+
+    fn block_on<F: Future + 'static>(fut: F) -> F::Output {
+        actix_web::rt::System::new("sharded_kv_graph_tests").block_on(fut)
+    }
+
+    fn keys(n: usize) -> Vec<String> {
+        (0..n).map(|x| format!("user:{}", x)).collect()
+    }
+
+    #[test]
+    fn keys_spread_over_shards_passed() {
+        let store = ShardedKVStore::new(4);
+        let mut per_shard = vec![0; 4];
+
+        for key in keys(10_000) {
+            let shard = store.shard_index(&key);
+            assert_eq!(shard, store.shard_index(&key));
+            per_shard[shard] += 1;
+        }
+
+        // even share is 2500
+        assert!(
+            per_shard.iter().all(|x| *x > 1500 && *x < 3500),
+            "uneven shards {:?}",
+            per_shard
+        );
+    }
+
+    #[test]
+    fn adding_shard_moves_few_keys_passed() {
+        let before = ShardedKVStore::new(4);
+        let after = ShardedKVStore::new(5);
+        let keys = keys(10_000);
+
+        let moved: Vec<&String> = keys
+            .iter()
+            .filter(|x| before.shard_index(x) != after.shard_index(x))
+            .collect();
+
+        // about 1/5 of keys move, and only to the new shard
+        assert!(moved.len() < 3000, "{} keys moved", moved.len());
+        assert!(moved.iter().all(|x| after.shard_index(x) == 4));
+    }
+
+    #[test]
+    fn single_shard_passed() {
+        let store = ShardedKVStore::new(1);
+
+        assert!(keys(100).iter().all(|x| store.shard_index(x) == 0));
+    }
+
+    #[test]
+    fn all_keys_across_shards_passed() {
+        block_on(async {
+            let store = ShardedKVStore::new(8);
+            for key in keys(100) {
+                store.add_value(key.clone(), key, None).await.unwrap();
+            }
+
+            let mut all_keys = store.get_all_keys().await.unwrap();
+            all_keys.sort();
+            let mut expected = keys(100);
+            expected.sort();
+
+            assert_eq!(expected, all_keys);
+            assert_eq!(
+                "user:42",
+                store
+                    .get_value(String::from("user:42"))
+                    .await
+                    .unwrap()
+                    .as_str()
+            );
+        });
+    }
+}